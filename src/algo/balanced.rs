@@ -1,10 +1,17 @@
-use crate::input::{Person, PreferenceType};
+use crate::algo::{Options, push_turn};
+use crate::config::Algo;
+use crate::input::{Person, PreferenceType, SOFT_OOO_GROUP};
 use crate::output::{Assignment, Schedule, ScheduleError};
-use chrono::{Days, NaiveDate, TimeDelta};
-use log::{debug, info, trace};
-use std::collections::HashMap;
+use chrono::{Datelike, Days, NaiveDate, TimeDelta, Weekday};
+use log::{debug, info, trace, warn};
+use std::cmp::Ordering;
+use std::collections::VecDeque;
 
-fn is_ooo_for_turn(person: &Person, start_date: NaiveDate, end_date: NaiveDate) -> bool {
+pub(super) fn is_ooo_for_turn(
+    person: &Person,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> bool {
     let mut current_date = start_date;
     while current_date < end_date {
         if person.ooo.contains(&current_date) {
@@ -16,126 +23,599 @@ fn is_ooo_for_turn(person: &Person, start_date: NaiveDate, end_date: NaiveDate)
     false
 }
 
-fn calculate_load_variance(load: &[TimeDelta]) -> f64 {
-    let n = load.len() as f64;
-    if n == 0.0 {
-        return 0.0;
-    }
-    let mean = load.iter().map(|d| d.num_seconds() as f64).sum::<f64>() / n;
-    let variance = load
-        .iter()
-        .map(|d| {
-            let diff = d.num_seconds() as f64 - mean;
-            diff * diff
-        })
-        .sum::<f64>()
-        / n;
+pub fn calculate_load_variance(load: &[TimeDelta]) -> f64 {
+    let variance = LoadSums::new(load).variance();
     trace!("Load: {:?}, variance: {}", load, variance);
     variance
 }
 
+/// The sum and sum of squares of a load vector, in seconds, to get its
+/// variance after changing one element in O(1) instead of going over the whole
+/// vector again. The sums are exact, so the variance doesn't depend on the
+/// order of the loads or on how it was computed.
+#[derive(Debug, Clone, Copy)]
+pub struct LoadSums {
+    n: i128,
+    sum: i128,
+    sum_of_squares: i128,
+}
+
+impl LoadSums {
+    pub fn new(load: &[TimeDelta]) -> Self {
+        let seconds = load.iter().map(|d| i128::from(d.num_seconds()));
+        LoadSums {
+            n: load.len() as i128,
+            sum: seconds.clone().sum(),
+            sum_of_squares: seconds.map(|s| s * s).sum(),
+        }
+    }
+
+    pub fn variance(&self) -> f64 {
+        if self.n == 0 {
+            return 0.0;
+        }
+        // n² times the variance, which is an integer.
+        let scaled = self.n * self.sum_of_squares - self.sum * self.sum;
+        scaled as f64 / (self.n * self.n) as f64
+    }
+
+    /// The variance once `delta` is added to one of the loads, whose value
+    /// is `value`.
+    pub fn variance_adding(&self, value: TimeDelta, delta: TimeDelta) -> f64 {
+        let (old, new) = (
+            i128::from(value.num_seconds()),
+            i128::from((value + delta).num_seconds()),
+        );
+        LoadSums {
+            sum: self.sum - old + new,
+            sum_of_squares: self.sum_of_squares - old * old + new * new,
+            ..*self
+        }
+        .variance()
+    }
+}
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Folds the preference group into the variance objective. The variance is
+/// converted to days² so that `preference_weight` reads as "how many days² of
+/// imbalance a one-step better preference group is worth".
+fn weighted_score(variance: f64, preference_group: i32, preference_weight: u8) -> f64 {
+    variance / (SECONDS_PER_DAY * SECONDS_PER_DAY)
+        + f64::from(preference_weight) * f64::from(preference_group)
+}
+
+/// Whether a candidate is better than the current best one. Without a
+/// `preference_weight` the preference group strictly takes precedence over
+/// the variance, otherwise both are combined into a single score. Either way,
+/// people who are softly OOO are a last resort.
+fn is_better(
+    (group, variance): (i32, f64),
+    (best_group, best_variance): (i32, f64),
+    preference_weight: Option<u8>,
+) -> bool {
+    let soft = |group: i32| group == SOFT_OOO_GROUP;
+    if soft(group) != soft(best_group) {
+        return soft(best_group);
+    }
+    outranks(
+        (group, variance),
+        (best_group, best_variance),
+        preference_weight,
+    )
+}
+
+/// Whether a preference group and variance beat the best ones, combined
+/// with `preference_weight` or with the preference group first. The groups
+/// can also be sums over several turns.
+fn outranks(
+    (group, variance): (i32, f64),
+    (best_group, best_variance): (i32, f64),
+    preference_weight: Option<u8>,
+) -> bool {
+    match preference_weight {
+        None => group < best_group || (group == best_group && variance < best_variance),
+        Some(weight) => {
+            weighted_score(variance, group, weight)
+                < weighted_score(best_variance, best_group, weight)
+        }
+    }
+}
+
+/// The Saturdays and Sundays in `[start_date, end_date)`, as a duration so
+/// that they can be balanced like the load.
+fn weekend_days(start_date: NaiveDate, end_date: NaiveDate) -> TimeDelta {
+    let days = start_date
+        .iter_days()
+        .take_while(|d| *d < end_date)
+        .filter(|d| matches!(d.weekday(), Weekday::Sat | Weekday::Sun))
+        .count();
+    TimeDelta::days(days as i64)
+}
+
+/// Whether the person wants to be on call on any day in `[start_date, end_date)`.
+fn wants_any(person: &Person, start_date: NaiveDate, end_date: NaiveDate) -> bool {
+    let mut current_date = start_date;
+    while current_date < end_date {
+        if person
+            .preferences
+            .get(&current_date)
+            .is_some_and(PreferenceType::is_want)
+        {
+            return true;
+        }
+        current_date = current_date.succ_opt().unwrap();
+    }
+    false
+}
+
+/// A `(person, turn length)` pair evaluated for the turn starting on `start`.
+#[derive(Debug)]
+pub struct Candidate {
+    pub person: usize,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub preference_group: i32,
+    pub variance: f64,
+}
+
+impl Candidate {
+    /// The load variance in days², the unit `preference_weight` is expressed in.
+    pub fn variance_days(&self) -> f64 {
+        self.variance / (SECONDS_PER_DAY * SECONDS_PER_DAY)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn schedule(
     people: Vec<Person>,
     start: NaiveDate,
     end: NaiveDate,
     min_turn_days: u8,
     max_turn_days: u8,
-    initial_load: Option<HashMap<String, TimeDelta>>,
+    preference_weight: Option<u8>,
+    balance_weekends: bool,
+    options: &Options,
 ) -> Result<Schedule, ScheduleError> {
+    schedule_with_trace(
+        people,
+        start,
+        end,
+        min_turn_days,
+        max_turn_days,
+        preference_weight,
+        balance_weekends,
+        options,
+    )
+    .map(|(schedule, _)| schedule)
+}
+
+/// Like [`schedule`], also returning every candidate that was evaluated, in
+/// order, when `options.collect_trace` is set.
+#[allow(clippy::too_many_arguments)]
+pub fn schedule_with_trace(
+    people: Vec<Person>,
+    start: NaiveDate,
+    end: NaiveDate,
+    min_turn_days: u8,
+    max_turn_days: u8,
+    preference_weight: Option<u8>,
+    balance_weekends: bool,
+    options: &Options,
+) -> Result<(Schedule, Vec<Candidate>), ScheduleError> {
+    search(
+        people,
+        start,
+        end,
+        min_turn_days,
+        max_turn_days,
+        preference_weight,
+        balance_weekends,
+        options,
+        true,
+    )
+    .map(|(mut schedule, _, trace)| {
+        if options.optimize_swaps {
+            optimize_swaps(&mut schedule, preference_weight, balance_weekends, options);
+        }
+        (schedule, trace)
+    })
+}
+
+/// Whether the people of turns `i` and `j` can be swapped: both must be
+/// available for their new turn, and neither may end up with two turns
+/// within `cooldown_turns` of each other or without `min_rest_days` between
+/// turns, which the search never assigns either. Neither may go over their
+/// `max_days`, fixed turns stay with their person and escalation-only people
+/// keep the turns the search had to fall back on them for, and no others.
+fn can_swap(schedule: &Schedule, i: usize, j: usize, options: &Options) -> bool {
+    let turns = &schedule.turns;
+    let (a, b) = (turns[i].person, turns[j].person);
+    let other = |m: usize, person: usize| m != i && m != j && turns[m].person == person;
+    let cooldown = options.cooldown_turns.max(1);
+    let back_to_back = |k: usize, person: usize| {
+        let mut nearby = k.saturating_sub(cooldown)..turns.len().min(k + cooldown + 1);
+        nearby.any(|m| other(m, person))
+    };
+    let resting = |k: usize, person: usize| {
+        (0..turns.len()).filter(|&m| other(m, person)).any(|m| {
+            let (before, after) = if m < k { (m, k) } else { (k, m) };
+            !options.is_rested(Some(turns[before].end), turns[after].start)
+        })
+    };
+    let days = |k: usize| (turns[k].end - turns[k].start).num_days();
+    // Whether `person` has room for turn `to` instead of turn `from`.
+    let has_room = |person: usize, from: usize, to: usize| {
+        let total: i64 = (0..turns.len())
+            .filter(|&m| turns[m].person == person)
+            .map(days)
+            .sum();
+        schedule.people[person].has_room_for(total - days(from), days(to))
+    };
+    // Regular turns never start within a fixed one.
+    let fixed = |k: usize| options.is_fixed(turns[k].start);
+    let escalation = |person: usize| options.is_escalation_only(&schedule.people[person]);
+    a != b
+        && !fixed(i)
+        && !fixed(j)
+        && !escalation(a)
+        && !escalation(b)
+        && !is_ooo_for_turn(&schedule.people[b], turns[i].start, turns[i].end)
+        && !is_ooo_for_turn(&schedule.people[a], turns[j].start, turns[j].end)
+        && !back_to_back(i, b)
+        && !back_to_back(j, a)
+        && !resting(i, b)
+        && !resting(j, a)
+        && has_room(a, i, j)
+        && has_room(b, j, i)
+}
+
+/// Swaps the people of pairs of turns while that improves the objective of
+/// the search, the variance of the effective load (plus that of the weekend
+/// days with `balance_weekends`) combined with the preference groups, until
+/// no swap does. The search never revisits a turn
+/// once it is assigned, so an early choice can leave an imbalance that only
+/// shows at the end. No one gets a turn they like less than the one they
+/// give away, e.g. one they are softly OOO for.
+pub fn optimize_swaps(
+    schedule: &mut Schedule,
+    preference_weight: Option<u8>,
+    balance_weekends: bool,
+    options: &Options,
+) {
+    // The loads are compared like for the last turn of the search, through
+    // the weights and ramp credits of that day.
+    let Some(date) = schedule.turns.iter().map(|t| t.start).max() else {
+        return;
+    };
+    let effective_variance = |people: &[Person], load: &[TimeDelta], weekends: &[TimeDelta]| {
+        let effective_load: Vec<TimeDelta> = people
+            .iter()
+            .zip(load)
+            .map(|(p, l)| options.effective_load(p, *l, date))
+            .collect();
+        let variance = calculate_load_variance(&effective_load);
+        if balance_weekends {
+            variance + calculate_load_variance(weekends)
+        } else {
+            variance
+        }
+    };
+    let mut load = options.initial_load(&schedule.people);
+    let mut weekends = vec![TimeDelta::zero(); schedule.people.len()];
+    for turn in &schedule.turns {
+        load[turn.person] += options.turn_load(turn.start, turn.end);
+        weekends[turn.person] += weekend_days(turn.start, turn.end);
+    }
+    let mut variance = effective_variance(&schedule.people, &load, &weekends);
+    let group = |schedule: &Schedule, person: usize, k: usize| {
+        let turn = &schedule.turns[k];
+        schedule.people[person].preference_group(turn.start, turn.end)
+    };
+    let mut groups: i32 = (0..schedule.turns.len())
+        .map(|k| group(schedule, schedule.turns[k].person, k))
+        .sum();
+
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..schedule.turns.len() {
+            for j in i + 1..schedule.turns.len() {
+                if !can_swap(schedule, i, j, options) {
+                    continue;
+                }
+                let (a, b) = (schedule.turns[i].person, schedule.turns[j].person);
+                let (a_before, a_after) = (group(schedule, a, i), group(schedule, a, j));
+                let (b_before, b_after) = (group(schedule, b, j), group(schedule, b, i));
+                if a_after > a_before || b_after > b_before {
+                    continue;
+                }
+                let next_groups = groups - a_before - b_before + a_after + b_after;
+                let delta = options.turn_load(schedule.turns[j].start, schedule.turns[j].end)
+                    - options.turn_load(schedule.turns[i].start, schedule.turns[i].end);
+                let mut next_load = load.clone();
+                next_load[a] += delta;
+                next_load[b] -= delta;
+                let weekend_delta = weekend_days(schedule.turns[j].start, schedule.turns[j].end)
+                    - weekend_days(schedule.turns[i].start, schedule.turns[i].end);
+                let mut next_weekends = weekends.clone();
+                next_weekends[a] += weekend_delta;
+                next_weekends[b] -= weekend_delta;
+                let next_variance =
+                    effective_variance(&schedule.people, &next_load, &next_weekends);
+                if outranks(
+                    (next_groups, next_variance),
+                    (groups, variance),
+                    preference_weight,
+                ) {
+                    debug!(
+                        "Swapping {} and {} for turns starting {} and {}",
+                        schedule.people[a].name,
+                        schedule.people[b].name,
+                        schedule.turns[i].start,
+                        schedule.turns[j].start
+                    );
+                    schedule.turns[i].person = b;
+                    schedule.turns[j].person = a;
+                    load = next_load;
+                    variance = next_variance;
+                    groups = next_groups;
+                    weekends = next_weekends;
+                    improved = true;
+                }
+            }
+        }
+    }
+}
+
+/// Runs the balanced search, returning the schedule, the number of
+/// `(person, turn length)` candidates that were evaluated and, with
+/// `options.collect_trace`, the candidates themselves.
+///
+/// With `prune` set, a person's turn is no longer lengthened once it can't
+/// produce a better candidate: when it would cover an OOO day or reach the end
+/// of the schedule, or when the variance started growing and no `Want` day
+/// lies ahead. With the other loads fixed, the variance is a convex function
+/// of the person's load, and the preference group can only improve through a
+/// `Want` day, so pruning never skips a candidate with a better score than
+/// the best one. It can skip candidates with the same score, though, so with
+/// a `tie_break_seed` ties may be drawn differently. Balancing weekends
+/// breaks the convexity, so only the first two conditions apply then.
+///
+/// With `balance_weekends` set, the variance of the weekend days people are
+/// on call is added to the variance of the load, so that the same people
+/// don't get all the weekends while the totals look even.
+#[allow(clippy::too_many_arguments)]
+fn search(
+    people: Vec<Person>,
+    start: NaiveDate,
+    end: NaiveDate,
+    min_turn_days: u8,
+    max_turn_days: u8,
+    preference_weight: Option<u8>,
+    balance_weekends: bool,
+    options: &Options,
+    prune: bool,
+) -> Result<(Schedule, usize, Vec<Candidate>), ScheduleError> {
     let mut turns = vec![];
     let mut current_day = start;
-    let mut load: Vec<TimeDelta> = people
-        .iter()
-        .map(|p| {
-            if let Some(ref il) = initial_load {
-                il.get(&p.id).cloned().unwrap_or(TimeDelta::zero())
-            } else {
-                TimeDelta::zero()
-            }
-        })
-        .collect();
-    let mut last_assignee: Option<usize> = None;
+    let mut load: Vec<TimeDelta> = options.initial_load(&people);
+    let mut recent_assignees: VecDeque<usize> = VecDeque::new();
+    let mut last_turn_end: Vec<Option<NaiveDate>> = vec![None; people.len()];
+    let mut days_on_call: Vec<i64> = vec![0; people.len()];
+    let mut consecutive_days: Vec<i64> = vec![0; people.len()];
+    options.record_previous_turns(
+        &people,
+        &mut last_turn_end,
+        &mut consecutive_days,
+        &mut recent_assignees,
+    );
+    let mut weekend_load: Vec<TimeDelta> = vec![TimeDelta::zero(); people.len()];
+    let mut evaluations = 0;
+    let mut candidates = vec![];
+    let mut tie_break = options.tie_break();
 
     info!("Starting balanced schedule generation");
     trace!("Initial load: {:?}", load);
 
     while current_day < end {
+        current_day = options.skip_uncovered(current_day);
+        if current_day >= end {
+            break;
+        }
+        options.check_turn_count(turns.len())?;
+        if let Some(turn) = options.fixed_turn(&people, current_day, end) {
+            info!(
+                "Keeping the fixed turn of {} {} -> {}",
+                people[turn.person].name, turn.start, turn.end
+            );
+            load[turn.person] += options.turn_load(turn.start, turn.end);
+            options.record_consecutive_days(
+                &mut consecutive_days,
+                last_turn_end[turn.person],
+                turn.person,
+                turn.start,
+                turn.end,
+            );
+            last_turn_end[turn.person] = Some(turn.end);
+            days_on_call[turn.person] += (turn.end - turn.start).num_days();
+            weekend_load[turn.person] += weekend_days(turn.start, turn.end);
+            options.record_assignee(&mut recent_assignees, turn.person);
+            current_day = turn.end;
+            push_turn(&mut turns, turn);
+            continue;
+        }
         debug!("Planning turn starting from {}", current_day);
+        // Each candidate only changes one person's load.
+        let effective_load: Vec<TimeDelta> = people
+            .iter()
+            .zip(&load)
+            .map(|(p, l)| options.effective_load(p, *l, current_day))
+            .collect();
+        let load_sums = LoadSums::new(&effective_load);
+        let weekend_sums = LoadSums::new(&weekend_load);
         let mut best_choice: Option<(usize, NaiveDate, i32, f64)> = None;
+        let longest_turn_end = options.align_turn_end(
+            current_day,
+            std::cmp::min(
+                end,
+                current_day
+                    .checked_add_days(Days::new(max_turn_days as u64))
+                    .unwrap(),
+            ),
+        );
 
-        for (i, person) in people.iter().enumerate() {
-            if Some(i) == last_assignee {
-                trace!("Skipping {} (last assignee)", person.name);
-                continue;
-            }
+        // Escalation-only people are only considered when no one else can
+        // take the turn, and the cooldown is only shortened when no one at
+        // all can.
+        let attempts = options.cooldowns().flat_map(|c| [(c, false), (c, true)]);
+        for (cooldown, escalation) in attempts {
+            for (i, person) in people.iter().enumerate() {
+                if options.is_escalation_only(person) != escalation {
+                    continue;
+                }
+                if recent_assignees
+                    .iter()
+                    .rev()
+                    .take(cooldown)
+                    .any(|&r| r == i)
+                {
+                    trace!("Skipping {} (cooldown)", person.name);
+                    continue;
+                }
+                if !options.is_rested(last_turn_end[i], current_day) {
+                    trace!("Skipping {} (resting)", person.name);
+                    continue;
+                }
 
-            for turn_len in min_turn_days..=max_turn_days {
-                let turn_end = std::cmp::min(
-                    end,
-                    current_day
-                        .checked_add_days(Days::new(turn_len as u64))
-                        .unwrap(),
-                );
+                let mut previous_variance: Option<f64> = None;
+                for turn_len in min_turn_days..=max_turn_days {
+                    let turn_end = options.align_turn_end(
+                        current_day,
+                        std::cmp::min(
+                            end,
+                            current_day
+                                .checked_add_days(Days::new(turn_len as u64))
+                                .unwrap(),
+                        ),
+                    );
 
-                if is_ooo_for_turn(person, current_day, turn_end) {
+                    if is_ooo_for_turn(person, current_day, turn_end) {
+                        trace!(
+                            "Skipping {} for turn {} -> {} (OOO)",
+                            person.name,
+                            current_day,
+                            turn_end
+                        );
+                        if prune {
+                            break;
+                        }
+                        continue;
+                    }
+                    // Longer turns would go over the cap too.
+                    let turn_days = (turn_end - current_day).num_days();
+                    if !person.has_room_for(days_on_call[i], turn_days) {
+                        trace!("Skipping {} (max days reached)", person.name);
+                        break;
+                    }
+                    if !options.is_within_consecutive_days(
+                        consecutive_days[i],
+                        last_turn_end[i],
+                        current_day,
+                        turn_end,
+                    ) {
+                        trace!("Skipping {} (max consecutive days reached)", person.name);
+                        break;
+                    }
+
+                    let preference_group = person.preference_group(current_day, turn_end);
+
+                    let next_load = load[i] + options.turn_load(current_day, turn_end);
+                    let mut variance = load_sums.variance_adding(
+                        effective_load[i],
+                        options.effective_load(person, next_load, current_day) - effective_load[i],
+                    );
+                    if balance_weekends {
+                        variance += weekend_sums
+                            .variance_adding(weekend_load[i], weekend_days(current_day, turn_end));
+                    }
+                    evaluations += 1;
+                    if options.collect_trace {
+                        candidates.push(Candidate {
+                            person: i,
+                            start: current_day,
+                            end: turn_end,
+                            preference_group,
+                            variance,
+                        });
+                    }
                     trace!(
-                        "Skipping {} for turn {} -> {} (OOO)",
+                        "Considering {} for {} -> {} (pref: {}, variance: {})",
                         person.name,
                         current_day,
-                        turn_end
+                        turn_end,
+                        preference_group,
+                        variance
                     );
-                    continue;
-                }
 
-                let mut has_want = false;
-                let mut has_not_want = false;
-                let mut d = current_day;
-                while d < turn_end {
-                    if let Some(pref) = person.preferences.get(&d) {
-                        match pref {
-                            PreferenceType::Want => has_want = true,
-                            PreferenceType::NotWant => has_not_want = true,
+                    let order = match best_choice {
+                        None => Ordering::Less,
+                        Some((best, _, current_best_group, current_best_variance)) => {
+                            let candidate = (preference_group, variance);
+                            let best_score = (current_best_group, current_best_variance);
+                            if is_better(candidate, best_score, preference_weight) {
+                                Ordering::Less
+                            } else if is_better(best_score, candidate, preference_weight) {
+                                Ordering::Greater
+                            } else {
+                                // Ties go to the better matching `position`.
+                                options.position_rank(person, current_day, start, end).cmp(
+                                    &options.position_rank(&people[best], current_day, start, end),
+                                )
+                            }
+                        }
+                    };
+                    let better = match order {
+                        Ordering::Less => {
+                            tie_break.new_best();
+                            true
                         }
+                        Ordering::Equal => tie_break.replaces_best(),
+                        Ordering::Greater => false,
+                    };
+                    if better {
+                        trace!("New best choice");
+                        best_choice = Some((i, turn_end, preference_group, variance));
                     }
-                    d = d.succ_opt().unwrap();
-                }
-
-                let preference_group = if has_want {
-                    0
-                } else if has_not_want {
-                    2
-                } else {
-                    1
-                };
 
-                let mut next_load = load.clone();
-                next_load[i] += turn_end - current_day;
-                let variance = calculate_load_variance(&next_load);
-                trace!(
-                    "Considering {} for {} -> {} (pref: {}, variance: {})",
-                    person.name,
-                    current_day,
-                    turn_end,
-                    preference_group,
-                    variance
-                );
-
-                if best_choice.is_none() {
-                    best_choice = Some((i, turn_end, preference_group, variance));
-                    continue;
+                    if prune {
+                        if turn_end == end {
+                            break;
+                        }
+                        // Weekend days come in steps, so the variance can
+                        // drop again after a weekend.
+                        let worsening = !balance_weekends
+                            && previous_variance.is_some_and(|previous| variance >= previous);
+                        if worsening && !wants_any(person, turn_end, longest_turn_end) {
+                            trace!("Not lengthening {}'s turn any further", person.name);
+                            break;
+                        }
+                    }
+                    previous_variance = Some(variance);
+                    // Longer turns would be cut at the same month boundary.
+                    if options.align_to_months && turn_end == longest_turn_end {
+                        break;
+                    }
                 }
-
-                let (_, _, current_best_group, current_best_variance) = best_choice.unwrap();
-
-                if preference_group < current_best_group {
-                    trace!("New best choice (better preference group)");
-                    best_choice = Some((i, turn_end, preference_group, variance));
-                } else if preference_group == current_best_group
-                    && variance < current_best_variance
-                {
-                    trace!("New best choice (better variance)");
-                    best_choice = Some((i, turn_end, preference_group, variance));
+            }
+            if best_choice.is_some() {
+                if cooldown < options.cooldown_turns {
+                    warn!(
+                        "Shortening the cooldown to {} turns on {}",
+                        cooldown, current_day
+                    );
                 }
+                break;
             }
         }
 
@@ -144,27 +624,57 @@ pub fn schedule(
                 "Assigning {} to turn {} -> {}",
                 people[assignee].name, current_day, turn_end
             );
-            turns.push(Assignment {
-                person: assignee,
-                start: current_day,
-                end: turn_end,
-            });
-            load[assignee] += turn_end - current_day;
+            push_turn(
+                &mut turns,
+                Assignment {
+                    person: assignee,
+                    start: current_day,
+                    end: turn_end,
+                    rotation: 0,
+                    co_primary: vec![],
+                },
+            );
+            load[assignee] += options.turn_load(current_day, turn_end);
+            days_on_call[assignee] += (turn_end - current_day).num_days();
+            weekend_load[assignee] += weekend_days(current_day, turn_end);
+            options.record_consecutive_days(
+                &mut consecutive_days,
+                last_turn_end[assignee],
+                assignee,
+                current_day,
+                turn_end,
+            );
             current_day = turn_end;
-            last_assignee = Some(assignee);
+            options.record_assignee(&mut recent_assignees, assignee);
+            last_turn_end[assignee] = Some(turn_end);
             trace!("Updated load: {:?}", load);
         } else {
             return Err(ScheduleError::NoOneAvailable(current_day));
         }
     }
 
-    Ok(Schedule { people, turns })
+    debug!("Evaluated {} candidates", evaluations);
+    let algo = Algo::Balanced {
+        min_turn_days,
+        max_turn_days,
+        preference_weight,
+        balance_weekends,
+    };
+    Ok((
+        Schedule {
+            people,
+            turns,
+            algo: Some(algo),
+        },
+        evaluations,
+        candidates,
+    ))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::input::{Person, PreferenceType};
+    use crate::input::{Person, PreferenceType, person};
     use chrono::NaiveDate;
     use std::collections::{HashMap, HashSet};
 
@@ -172,21 +682,18 @@ mod tests {
     fn test_simple_balanced_schedule() {
         let people = vec![
             Person {
-                id: "alice".to_string(),
                 name: "Alice".to_string(),
-                ooo: HashSet::new(),
-                preferences: HashMap::new(),
+                ..person("alice")
             },
             Person {
-                id: "bob".to_string(),
                 name: "Bob".to_string(),
-                ooo: HashSet::new(),
-                preferences: HashMap::new(),
+                ..person("bob")
             },
         ];
         let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
         let end = NaiveDate::from_ymd_opt(2025, 1, 11).unwrap(); // 10 days
-        let schedule = schedule(people, start, end, 3, 7, None).unwrap();
+        let schedule =
+            schedule(people, start, end, 3, 7, None, false, &Options::default()).unwrap();
 
         // Expect Alice: 6 days, Bob: 4 days
         let alice_load = schedule.turns.iter().filter(|t| t.person == 0).map(|t| (t.end - t.start).num_days()).sum::<i64>();
@@ -196,6 +703,74 @@ mod tests {
         assert_eq!(bob_load, 4);
     }
 
+    #[test]
+    fn test_person_weight_scales_the_share_of_days() {
+        let people: Vec<Person> = [("alice", 2.0), ("bob", 1.0)]
+            .iter()
+            .map(|(id, weight)| Person {
+                weight: *weight,
+                ..person(id)
+            })
+            .collect();
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 1, 31).unwrap(); // 30 days
+
+        let schedule =
+            schedule(people, start, end, 1, 3, None, false, &Options::default()).unwrap();
+        let days = |person: usize| {
+            let turns = schedule.turns.iter().filter(|t| t.person == person);
+            turns.map(|t| (t.end - t.start).num_days()).sum::<i64>()
+        };
+        assert_eq!(days(0), 20);
+        assert_eq!(days(1), 10);
+    }
+
+    #[test]
+    fn test_cooldown_turns() {
+        let people: Vec<Person> = ["alice", "bob", "carol", "dave"]
+            .iter()
+            .map(|id| person(id))
+            .collect();
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+        let options = Options {
+            cooldown_turns: 2,
+            optimize_swaps: true,
+            ..Default::default()
+        };
+
+        let schedule = schedule(people, start, end, 1, 3, None, false, &options).unwrap();
+        let assignees: Vec<usize> = schedule.turns.iter().map(|t| t.person).collect();
+        for window in assignees.windows(3) {
+            assert!(window[0] != window[1] && window[0] != window[2]);
+        }
+    }
+
+    #[test]
+    fn test_max_days_caps_the_days_on_call() {
+        let people: Vec<Person> = [("alice", Some(5)), ("bob", None), ("charlie", None)]
+            .iter()
+            .map(|(id, max_days)| Person {
+                max_days: *max_days,
+                ..person(id)
+            })
+            .collect();
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 1, 31).unwrap(); // 30 days
+        let options = Options {
+            optimize_swaps: true,
+            ..Default::default()
+        };
+
+        let schedule = schedule(people, start, end, 1, 3, None, false, &options).unwrap();
+        let days = |person: usize| {
+            let turns = schedule.turns.iter().filter(|t| t.person == person);
+            turns.map(|t| (t.end - t.start).num_days()).sum::<i64>()
+        };
+        assert_eq!(days(0), 5);
+        assert_eq!(days(1) + days(2), 25);
+    }
+
     #[test]
     fn test_balanced_with_preferences() {
         let mut alice_prefs = HashMap::new();
@@ -206,21 +781,555 @@ mod tests {
 
         let people = vec![
             Person {
-                id: "alice".to_string(),
                 name: "Alice".to_string(),
-                ooo: HashSet::new(),
                 preferences: alice_prefs,
+                ..person("alice")
             },
             Person {
-                id: "bob".to_string(),
                 name: "Bob".to_string(),
-                ooo: HashSet::new(),
-                preferences: HashMap::new(),
+                ..person("bob")
             },
         ];
         let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
         let end = NaiveDate::from_ymd_opt(2025, 1, 5).unwrap();
-        let schedule = schedule(people, start, end, 1, 3, None).unwrap();
+        let schedule =
+            schedule(people, start, end, 1, 3, None, false, &Options::default()).unwrap();
         assert_eq!(schedule.turns[0].person, 0); // Alice gets the first turn
     }
-}
\ No newline at end of file
+
+    fn loaded_alice_wants_first_day() -> (Vec<Person>, HashMap<String, TimeDelta>) {
+        let mut alice_prefs = HashMap::new();
+        alice_prefs.insert(
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            PreferenceType::Want,
+        );
+        let people = vec![
+            Person {
+                name: "Alice".to_string(),
+                preferences: alice_prefs,
+                ..person("alice")
+            },
+            Person {
+                name: "Bob".to_string(),
+                ..person("bob")
+            },
+        ];
+        let mut initial_load = HashMap::new();
+        initial_load.insert("alice".to_string(), TimeDelta::days(10));
+        (people, initial_load)
+    }
+
+    fn with_load(initial_load: HashMap<String, TimeDelta>) -> Options {
+        Options {
+            initial_load: Some(initial_load),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_preference_weight_lets_fairness_win() {
+        // Alice already carries 10 days: giving her the turn costs 20 days² of
+        // variance more than giving it to Bob, which outweighs 5 × 1 group.
+        let (people, initial_load) = loaded_alice_wants_first_day();
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 1, 3).unwrap();
+        let schedule = schedule(
+            people,
+            start,
+            end,
+            2,
+            2,
+            Some(5),
+            false,
+            &with_load(initial_load),
+        )
+        .unwrap();
+        assert_eq!(schedule.turns[0].person, 1);
+    }
+
+    #[test]
+    fn test_preference_weight_high_or_unset_keeps_preference() {
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 1, 3).unwrap();
+
+        let (people, initial_load) = loaded_alice_wants_first_day();
+        let weighted = schedule(
+            people,
+            start,
+            end,
+            2,
+            2,
+            Some(50),
+            false,
+            &with_load(initial_load),
+        )
+        .unwrap();
+        assert_eq!(weighted.turns[0].person, 0);
+
+        let (people, initial_load) = loaded_alice_wants_first_day();
+        let strict = schedule(
+            people,
+            start,
+            end,
+            2,
+            2,
+            None,
+            false,
+            &with_load(initial_load),
+        )
+        .unwrap();
+        assert_eq!(strict.turns[0].person, 0);
+    }
+
+    #[test]
+    fn test_pruning_preserves_output_with_fewer_evaluations() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people = || {
+            let mut alice_ooo = HashSet::new();
+            alice_ooo.insert(day(12));
+            let mut bob_prefs = HashMap::new();
+            bob_prefs.insert(day(20), PreferenceType::Want);
+            bob_prefs.insert(day(5), PreferenceType::NotWant);
+            vec![
+                Person {
+                    name: "Alice".to_string(),
+                    ooo: alice_ooo,
+                    ..person("alice")
+                },
+                Person {
+                    name: "Bob".to_string(),
+                    preferences: bob_prefs,
+                    ..person("bob")
+                },
+                Person {
+                    name: "Charlie".to_string(),
+                    ..person("charlie")
+                },
+            ]
+        };
+        let (start, end) = (day(1), day(31));
+        let options = Options::default();
+
+        for weight in [None, Some(3)] {
+            let (pruned, pruned_evaluations, _) =
+                search(people(), start, end, 2, 9, weight, false, &options, true).unwrap();
+            let (full, full_evaluations, _) =
+                search(people(), start, end, 2, 9, weight, false, &options, false).unwrap();
+
+            let as_tuples = |s: &Schedule| {
+                s.turns
+                    .iter()
+                    .map(|t| (t.person, t.start, t.end))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(as_tuples(&pruned), as_tuples(&full));
+            assert!(
+                pruned_evaluations < full_evaluations,
+                "{} >= {}",
+                pruned_evaluations,
+                full_evaluations
+            );
+        }
+    }
+
+    #[test]
+    fn test_trace_has_one_entry_per_evaluated_candidate() {
+        let people = vec![
+            Person {
+                name: "Alice".to_string(),
+                ..person("alice")
+            },
+            Person {
+                name: "Bob".to_string(),
+                ..person("bob")
+            },
+        ];
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+        let options = Options {
+            collect_trace: true,
+            ..Default::default()
+        };
+
+        let (schedule, evaluations, trace) =
+            search(people, start, end, 2, 5, None, false, &options, false).unwrap();
+        assert_eq!(trace.len(), evaluations);
+        // Both people, each with turn lengths 2 to 5.
+        let first_turn: Vec<(usize, i64)> = trace
+            .iter()
+            .filter(|c| c.start == start)
+            .map(|c| (c.person, (c.end - c.start).num_days()))
+            .collect();
+        let expected: Vec<(usize, i64)> = [0, 1]
+            .into_iter()
+            .flat_map(|p| (2..=5).map(move |days| (p, days)))
+            .collect();
+        assert_eq!(first_turn, expected);
+        // Later turns skip the previous assignee.
+        let second_start = schedule.turns[1].start;
+        assert!(
+            trace
+                .iter()
+                .filter(|c| c.start == second_start)
+                .all(|c| c.person != schedule.turns[0].person)
+        );
+
+        let options = Options::default();
+        let (_, _, trace) = search(
+            schedule.people,
+            start,
+            end,
+            2,
+            5,
+            None,
+            false,
+            &options,
+            false,
+        )
+        .unwrap();
+        assert!(trace.is_empty());
+    }
+
+    #[test]
+    fn test_optimize_swaps() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let person = |id: &str, ooo: HashSet<NaiveDate>| Person { ooo, ..person(id) };
+        let total_variance = |schedule: &Schedule| {
+            let mut load = vec![TimeDelta::zero(); schedule.people.len()];
+            for turn in &schedule.turns {
+                load[turn.person] += turn.end - turn.start;
+            }
+            calculate_load_variance(&load)
+        };
+        let options = Options::default();
+
+        // Alice has 10 days and the others 6; Bob can't take her first turn.
+        let turns = [
+            (0, 1, 6),
+            (1, 6, 9),
+            (2, 9, 12),
+            (0, 12, 17),
+            (1, 17, 20),
+            (2, 20, 23),
+        ];
+        let mut uneven = Schedule {
+            people: vec![
+                person("alice", HashSet::new()),
+                person("bob", HashSet::from([day(1)])),
+                person("carol", HashSet::new()),
+            ],
+            turns: turns
+                .into_iter()
+                .map(|(person, start, end)| Assignment {
+                    person,
+                    start: day(start),
+                    end: day(end),
+                    rotation: 0,
+                    co_primary: vec![],
+                })
+                .collect(),
+            algo: None,
+        };
+        let before = total_variance(&uneven);
+        optimize_swaps(&mut uneven, None, false, &options);
+        assert!(total_variance(&uneven) < before);
+        assert_ne!(uneven.turns[0].person, 1);
+        assert!(uneven.turns.windows(2).all(|w| w[0].person != w[1].person));
+
+        let people = vec![
+            person("alice", HashSet::new()),
+            person("bob", (10..=14).map(day).collect()),
+            person("carol", HashSet::new()),
+        ];
+        let mut balanced = schedule(people, day(1), day(31), 2, 6, None, false, &options).unwrap();
+        let before = total_variance(&balanced);
+        optimize_swaps(&mut balanced, None, false, &options);
+        assert!(total_variance(&balanced) <= before);
+
+        // Everyone has 5 days, which is twice Alice's share at half weight.
+        let turns = [
+            (0, 1, 4),
+            (1, 4, 6),
+            (2, 6, 9),
+            (0, 9, 11),
+            (1, 11, 14),
+            (2, 14, 16),
+        ];
+        let mut weighted = Schedule {
+            people: vec![
+                Person {
+                    weight: 0.5,
+                    ..person("alice", HashSet::new())
+                },
+                person("bob", HashSet::new()),
+                person("carol", HashSet::new()),
+            ],
+            turns: turns
+                .into_iter()
+                .map(|(person, start, end)| Assignment {
+                    person,
+                    start: day(start),
+                    end: day(end),
+                    rotation: 0,
+                    co_primary: vec![],
+                })
+                .collect(),
+            algo: None,
+        };
+        optimize_swaps(&mut weighted, None, false, &options);
+        let alice_days: i64 = weighted
+            .turns
+            .iter()
+            .filter(|t| t.person == 0)
+            .map(|t| (t.end - t.start).num_days())
+            .sum();
+        assert!(alice_days < 5);
+    }
+
+    #[test]
+    fn test_incremental_variance_matches_recomputation() {
+        use rand::{Rng, SeedableRng};
+        use rand_chacha::ChaCha8Rng;
+
+        let naive = |load: &[TimeDelta]| {
+            let n = load.len() as f64;
+            let mean = load.iter().map(|d| d.num_seconds() as f64).sum::<f64>() / n;
+            load.iter()
+                .map(|d| (d.num_seconds() as f64 - mean).powi(2))
+                .sum::<f64>()
+                / n
+        };
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        for _ in 0..200 {
+            let load: Vec<TimeDelta> = (0..rng.random_range(1..20))
+                .map(|_| TimeDelta::seconds(rng.random_range(-86_400 * 30..86_400 * 400)))
+                .collect();
+            let sums = LoadSums::new(&load);
+            let i = rng.random_range(0..load.len());
+            let delta = TimeDelta::seconds(rng.random_range(-86_400 * 30..86_400 * 30));
+
+            let mut next_load = load.clone();
+            next_load[i] += delta;
+            let variance = sums.variance_adding(load[i], delta);
+            assert_eq!(variance, calculate_load_variance(&next_load));
+            let expected = naive(&next_load);
+            assert!((variance - expected).abs() <= expected * 1e-9 + 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_strong_want_beats_want() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = [
+            ("alice", PreferenceType::Want),
+            ("bob", PreferenceType::StrongWant),
+        ]
+        .into_iter()
+        .map(|(id, preference)| Person {
+            preferences: HashMap::from([(day(1), preference)]),
+            ..person(id)
+        })
+        .collect();
+
+        let schedule = schedule(
+            people,
+            day(1),
+            day(5),
+            2,
+            2,
+            None,
+            false,
+            &Options::default(),
+        )
+        .unwrap();
+        assert_eq!(schedule.turns[0].person, 1);
+    }
+
+    #[test]
+    fn test_seeded_tie_break() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob", "carol", "dave"]
+            .into_iter()
+            .map(person)
+            .collect();
+        let assignees = |tie_break_seed: Option<u64>| {
+            let options = Options {
+                tie_break_seed,
+                ..Default::default()
+            };
+            let schedule =
+                schedule(people.clone(), day(1), day(9), 1, 1, None, false, &options).unwrap();
+            schedule.turns.iter().map(|t| t.person).collect::<Vec<_>>()
+        };
+
+        // Without a seed, ties go to the first in the rotation.
+        assert_eq!(assignees(None), vec![0, 1, 2, 3, 0, 1, 2, 3]);
+        assert_eq!(assignees(Some(1)), assignees(Some(1)));
+        assert!((2..10).any(|seed| assignees(Some(seed)) != assignees(Some(1))));
+    }
+
+    #[test]
+    fn test_balance_weekends() {
+        let start = NaiveDate::from_ymd_opt(2025, 1, 6).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        let people: Vec<Person> = ["alice", "bob", "carol"].into_iter().map(person).collect();
+        // The weekend spread, and the spread of the total load.
+        let spreads = |balance_weekends: bool| {
+            let schedule = schedule(
+                people.clone(),
+                start,
+                end,
+                2,
+                5,
+                None,
+                balance_weekends,
+                &Options::default(),
+            )
+            .unwrap();
+            let mut weekends = vec![TimeDelta::zero(); people.len()];
+            for turn in &schedule.turns {
+                weekends[turn.person] += weekend_days(turn.start, turn.end);
+            }
+            let (min, max) = (
+                weekends.iter().min().unwrap(),
+                weekends.iter().max().unwrap(),
+            );
+            ((*max - *min).num_days(), schedule.load_spread())
+        };
+
+        let (balanced, balanced_total) = spreads(true);
+        let (unbalanced, unbalanced_total) = spreads(false);
+        assert!(balanced < unbalanced);
+        assert_eq!(balanced_total, unbalanced_total);
+    }
+
+    #[test]
+    fn test_swaps_keep_escalation_only_turns() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob", "senior"].into_iter().map(person).collect();
+        // Alice has 10 days, Bob 4 and the senior, who only covered a gap, 1.
+        let turns = [(0, 1, 6), (1, 6, 8), (2, 8, 9), (0, 9, 14), (1, 14, 16)];
+        let senior_turns = |options: &Options| {
+            let mut schedule = Schedule {
+                people: people.clone(),
+                turns: turns
+                    .into_iter()
+                    .map(|(person, start, end)| Assignment {
+                        person,
+                        start: day(start),
+                        end: day(end),
+                        rotation: 0,
+                        co_primary: vec![],
+                    })
+                    .collect(),
+                algo: None,
+            };
+            optimize_swaps(&mut schedule, None, false, options);
+            let turns = schedule.turns.iter().filter(|t| t.person == 2);
+            turns.map(|t| (t.start, t.end)).collect::<Vec<_>>()
+        };
+
+        // Otherwise, the senior would take one of Alice's turns.
+        assert_ne!(senior_turns(&Options::default()), vec![(day(8), day(9))]);
+        let options = Options {
+            escalation_only: HashSet::from(["senior".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(senior_turns(&options), vec![(day(8), day(9))]);
+    }
+
+    #[test]
+    fn test_swaps_keep_preferences() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people = |preferences: HashMap<NaiveDate, PreferenceType>| -> Vec<Person> {
+            ["alice", "bob", "carol"]
+                .into_iter()
+                .map(|id| Person {
+                    preferences: if id == "alice" {
+                        preferences.clone()
+                    } else {
+                        HashMap::new()
+                    },
+                    ..person(id)
+                })
+                .collect()
+        };
+        // Alice has 10 days, Bob 4 and Carol 1.
+        let turns = [(0, 1, 6), (1, 6, 8), (2, 8, 9), (0, 9, 14), (1, 14, 16)];
+        let alice_turns = |people: Vec<Person>, preference_weight: Option<u8>| {
+            let mut schedule = Schedule {
+                people,
+                turns: turns
+                    .into_iter()
+                    .map(|(person, start, end)| Assignment {
+                        person,
+                        start: day(start),
+                        end: day(end),
+                        rotation: 0,
+                        co_primary: vec![],
+                    })
+                    .collect(),
+                algo: None,
+            };
+            optimize_swaps(&mut schedule, preference_weight, false, &Options::default());
+            let turns = schedule.turns.iter().filter(|t| t.person == 0);
+            turns.map(|t| (t.start, t.end)).collect::<Vec<_>>()
+        };
+        let own_turns = vec![(day(1), day(6)), (day(9), day(14))];
+
+        // Without preferences, Alice gives a turn away.
+        assert_ne!(alice_turns(people(HashMap::new()), None), own_turns);
+        // She wants both of hers, so she keeps them whatever the weight.
+        let wants = HashMap::from([
+            (day(2), PreferenceType::StrongWant),
+            (day(10), PreferenceType::StrongWant),
+        ]);
+        assert_eq!(alice_turns(people(wants.clone()), None), own_turns);
+        assert_eq!(alice_turns(people(wants), Some(1)), own_turns);
+    }
+
+    #[test]
+    fn test_swaps_keep_weekends_balanced() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob", "carol"].into_iter().map(person).collect();
+        // Everyone has one weekend, but Carol only 3 days in total. Evening
+        // that out means giving her a week instead of her weekend.
+        let turns = [
+            (0, 4, 6),
+            (1, 6, 11),
+            (2, 11, 13),
+            (0, 13, 18),
+            (1, 18, 20),
+            (2, 20, 21),
+        ];
+        let weekend_spread = |balance_weekends: bool| {
+            let mut schedule = Schedule {
+                people: people.clone(),
+                turns: turns
+                    .into_iter()
+                    .map(|(person, start, end)| Assignment {
+                        person,
+                        start: day(start),
+                        end: day(end),
+                        rotation: 0,
+                        co_primary: vec![],
+                    })
+                    .collect(),
+                algo: None,
+            };
+            optimize_swaps(&mut schedule, None, balance_weekends, &Options::default());
+            let mut weekends = vec![TimeDelta::zero(); people.len()];
+            for turn in &schedule.turns {
+                weekends[turn.person] += weekend_days(turn.start, turn.end);
+            }
+            let (min, max) = (
+                weekends.iter().min().unwrap(),
+                weekends.iter().max().unwrap(),
+            );
+            (*max - *min).num_days()
+        };
+
+        assert!(weekend_spread(false) > 0);
+        assert_eq!(weekend_spread(true), 0);
+    }
+}
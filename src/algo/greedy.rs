@@ -1,8 +1,11 @@
-use crate::input::{Person, PreferenceType};
+use crate::algo::{Options, push_turn};
+use crate::config::Algo;
+use crate::input::{Person, SOFT_OOO_GROUP};
 use crate::output::{Assignment, Schedule};
-use chrono::{Days, NaiveDate, TimeDelta};
-use log::{debug, info, trace};
-use std::collections::HashMap;
+use chrono::{NaiveDate, TimeDelta};
+use log::{debug, info, trace, warn};
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
 
 use crate::output::ScheduleError;
 
@@ -18,108 +21,280 @@ fn is_ooo_for_turn(person: &Person, start_date: NaiveDate, end_date: NaiveDate)
     false
 }
 
+/// Why a turn went to its assignee, for `--explain`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TurnRationale {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub person: usize,
+    /// Everyone who could take the turn, the assignee included, in rotation
+    /// order.
+    pub candidates: Vec<Considered>,
+    pub reason: Reason,
+}
+
+/// A candidate for a turn, with their effective load before it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Considered {
+    pub person: usize,
+    pub load: TimeDelta,
+    pub preference_group: i32,
+}
+
+/// What set the assignee apart from the runner-up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    Fixed,
+    OnlyCandidate,
+    /// A better preference group, which outweighed any load difference.
+    Preference,
+    Load,
+    /// A better matching `position`, between candidates otherwise tied.
+    Position,
+    /// Tied with the runner-up, and earlier in the rotation order.
+    Order,
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            Reason::Fixed => "fixed turn",
+            Reason::OnlyCandidate => "only candidate",
+            Reason::Preference => "preference",
+            Reason::Load => "lowest load",
+            Reason::Position => "position",
+            Reason::Order => "rotation order",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
 pub fn schedule(
     people: Vec<Person>,
     start: NaiveDate,
     end: NaiveDate,
     turn_length_days: u8,
-    _preference_weight: Option<u8>,
-    initial_load: Option<HashMap<String, TimeDelta>>,
+    preference_weight: Option<u8>,
+    options: &Options,
 ) -> Result<Schedule, ScheduleError> {
+    schedule_with_rationale(
+        people,
+        start,
+        end,
+        turn_length_days,
+        preference_weight,
+        options,
+    )
+    .map(|(schedule, _)| schedule)
+}
+
+/// Like [`schedule`], also returning why each turn went to its assignee when
+/// `options.explain` is set.
+pub fn schedule_with_rationale(
+    people: Vec<Person>,
+    start: NaiveDate,
+    end: NaiveDate,
+    turn_length_days: u8,
+    preference_weight: Option<u8>,
+    options: &Options,
+) -> Result<(Schedule, Vec<TurnRationale>), ScheduleError> {
     let mut turns = vec![];
+    let mut rationale = vec![];
     let mut current_day = start;
-    let mut load: Vec<TimeDelta> = people
-        .iter()
-        .map(|p| {
-            if let Some(ref il) = initial_load {
-                il.get(&p.id).cloned().unwrap_or(TimeDelta::zero())
-            } else {
-                TimeDelta::zero()
-            }
-        })
-        .collect();
-    let mut last_assignee: Option<usize> = None;
+    let mut load: Vec<TimeDelta> = options.initial_load(&people);
+    let mut recent_assignees: VecDeque<usize> = VecDeque::new();
+    let mut last_turn_end: Vec<Option<NaiveDate>> = vec![None; people.len()];
+    let mut days_on_call: Vec<i64> = vec![0; people.len()];
+    let mut consecutive_days: Vec<i64> = vec![0; people.len()];
+    options.record_previous_turns(
+        &people,
+        &mut last_turn_end,
+        &mut consecutive_days,
+        &mut recent_assignees,
+    );
+    let mut tie_break = options.tie_break();
 
     info!("Starting greedy schedule generation");
     trace!("Initial load: {:?}", load);
 
     while current_day < end {
-        let turn_end_date = std::cmp::min(
-            end,
-            current_day
-                .checked_add_days(Days::new(turn_length_days.into()))
-                .unwrap(),
+        current_day = options.skip_uncovered(current_day);
+        if current_day >= end {
+            break;
+        }
+        options.check_turn_count(turns.len())?;
+        if let Some(turn) = options.fixed_turn(&people, current_day, end) {
+            info!(
+                "Keeping the fixed turn of {} {} -> {}",
+                people[turn.person].name, turn.start, turn.end
+            );
+            load[turn.person] += options.turn_load(turn.start, turn.end);
+            options.record_consecutive_days(
+                &mut consecutive_days,
+                last_turn_end[turn.person],
+                turn.person,
+                turn.start,
+                turn.end,
+            );
+            last_turn_end[turn.person] = Some(turn.end);
+            days_on_call[turn.person] += (turn.end - turn.start).num_days();
+            options.record_assignee(&mut recent_assignees, turn.person);
+            current_day = turn.end;
+            if options.explain {
+                rationale.push(TurnRationale {
+                    start: turn.start,
+                    end: turn.end,
+                    person: turn.person,
+                    candidates: vec![],
+                    reason: Reason::Fixed,
+                });
+            }
+            push_turn(&mut turns, turn);
+            continue;
+        }
+        let turn_end_date = options.align_turn_end(
+            current_day,
+            options.fixed_turn_end(current_day, end, turn_length_days),
         );
         debug!("Planning turn from {} to {}", current_day, turn_end_date);
 
-        let mut want_candidates = vec![];
-        let mut neutral_candidates = vec![];
-        let mut not_want_candidates = vec![];
+        // Escalation-only people are only considered when no one else can
+        // take the turn, and the cooldown is only shortened when no one at
+        // all can.
+        let mut candidate = None;
+        let mut reason = Reason::OnlyCandidate;
+        let mut considered = vec![];
+        let attempts = options.cooldowns().flat_map(|c| [(c, false), (c, true)]);
+        for (cooldown, escalation) in attempts {
+            // Each candidate with their preference group, see
+            // `Person::preference_group`.
+            let mut candidates: Vec<(usize, i32)> = vec![];
 
-        for (i, person) in people.iter().enumerate() {
-            if Some(i) == last_assignee {
-                trace!("Skipping {} (last assignee)", person.name);
-                continue;
-            }
+            for (i, person) in people.iter().enumerate() {
+                if options.is_escalation_only(person) != escalation {
+                    continue;
+                }
+                if recent_assignees
+                    .iter()
+                    .rev()
+                    .take(cooldown)
+                    .any(|&r| r == i)
+                {
+                    trace!("Skipping {} (cooldown)", person.name);
+                    continue;
+                }
+                if !options.is_rested(last_turn_end[i], current_day) {
+                    debug!("Skipping {} (resting)", person.name);
+                    continue;
+                }
 
-            if is_ooo_for_turn(person, current_day, turn_end_date) {
-                debug!("Skipping {} (OOO)", person.name);
-                continue;
+                if is_ooo_for_turn(person, current_day, turn_end_date) {
+                    debug!("Skipping {} (OOO)", person.name);
+                    continue;
+                }
+                let turn_days = (turn_end_date - current_day).num_days();
+                if !person.has_room_for(days_on_call[i], turn_days) {
+                    debug!("Skipping {} (max days reached)", person.name);
+                    continue;
+                }
+                if !options.is_within_consecutive_days(
+                    consecutive_days[i],
+                    last_turn_end[i],
+                    current_day,
+                    turn_end_date,
+                ) {
+                    debug!("Skipping {} (max consecutive days reached)", person.name);
+                    continue;
+                }
+
+                let group = person.preference_group(current_day, turn_end_date);
+                trace!("{} has preference group {}", person.name, group);
+                candidates.push((i, group));
             }
+            debug!("Candidates and preference groups: {:?}", candidates);
 
-            let mut has_want = false;
-            let mut has_not_want = false;
-            let mut d = current_day;
-            while d < turn_end_date {
-                if let Some(pref) = person.preferences.get(&d) {
-                    match pref {
-                        PreferenceType::Want => has_want = true,
-                        PreferenceType::NotWant => has_not_want = true,
+            // The least loaded, then the one whose `position` matches best.
+            let rank = |p: usize, shift: TimeDelta| {
+                (
+                    options.effective_load(&people[p], load[p], current_day) + shift,
+                    options.position_rank(&people[p], current_day, start, end),
+                )
+            };
+            let weight = preference_weight.filter(|&weight| weight > 0);
+            // Preferences count as `weight` days less (or more) load rather
+            // than taking precedence over it: each group away from neutral is
+            // worth `weight` days, and ties go to the better group.
+            let shift =
+                |group: i32| TimeDelta::days(i64::from(weight.unwrap_or(0)) * (group - 2) as i64);
+            // Without a weight, the preference group comes first. Either way,
+            // people who are softly OOO are a last resort.
+            let soft = |group: i32| group == SOFT_OOO_GROUP;
+            let key = |&&(p, group): &&(usize, i32)| match weight {
+                Some(_) => (i32::from(soft(group)), rank(p, shift(group)), group),
+                None => (group, rank(p, TimeDelta::zero()), 0),
+            };
+            debug!("Choosing with preference weight {:?}", weight);
+            let best = tie_break.min_by_key(&candidates, key).copied();
+            candidate = best.map(|(p, _)| p);
+            if let Some((chosen, group)) = best.filter(|_| options.explain) {
+                let runner_up = candidates
+                    .iter()
+                    .filter(|&&(p, _)| p != chosen)
+                    .min_by_key(key)
+                    .copied();
+                let load = |p: usize| rank(p, TimeDelta::zero()).0;
+                reason = match runner_up {
+                    None => Reason::OnlyCandidate,
+                    Some((other, other_group)) => {
+                        let (chosen_rank, other_rank) =
+                            (rank(chosen, shift(group)), rank(other, shift(other_group)));
+                        if (weight.is_none() && group != other_group)
+                            || soft(group) != soft(other_group)
+                        {
+                            Reason::Preference
+                        } else if chosen_rank.0 != other_rank.0 {
+                            // With a weight, the preference can make up for
+                            // more load.
+                            if load(chosen) < load(other) {
+                                Reason::Load
+                            } else {
+                                Reason::Preference
+                            }
+                        } else if chosen_rank.1 != other_rank.1 {
+                            Reason::Position
+                        } else if group != other_group {
+                            Reason::Preference
+                        } else {
+                            Reason::Order
+                        }
                     }
-                }
-                d = d.succ_opt().unwrap();
+                };
+                considered = candidates
+                    .iter()
+                    .map(|&(p, group)| Considered {
+                        person: p,
+                        load: load(p),
+                        preference_group: group,
+                    })
+                    .collect();
             }
-
-            if has_want {
-                trace!("{} has Want preference", person.name);
-                want_candidates.push(i);
-            } else if has_not_want {
-                trace!("{} has NotWant preference", person.name);
-                not_want_candidates.push(i);
-            } else {
-                trace!("{} has neutral preference", person.name);
-                neutral_candidates.push(i);
+            if candidate.is_some() {
+                if cooldown < options.cooldown_turns {
+                    warn!(
+                        "Shortening the cooldown to {} turns on {}",
+                        cooldown, current_day
+                    );
+                }
+                break;
             }
         }
-        debug!("Want candidates: {:?}", want_candidates);
-        debug!("Neutral candidates: {:?}", neutral_candidates);
-        debug!("NotWant candidates: {:?}", not_want_candidates);
-
-        let candidate = if !want_candidates.is_empty() {
-            debug!("Choosing from Want candidates");
-            want_candidates
-                .iter()
-                .min_by_key(|&&p| load[p]).copied()
-        } else if !neutral_candidates.is_empty() {
-            debug!("Choosing from Neutral candidates");
-            neutral_candidates
-                .iter()
-                .min_by_key(|&&p| load[p]).copied()
-        } else if !not_want_candidates.is_empty() {
-            debug!("Choosing from NotWant candidates");
-            not_want_candidates
-                .iter()
-                .min_by_key(|&&p| load[p]).copied()
-        } else {
-            None
-        };
 
         if candidate.is_none() {
             return Err(ScheduleError::NoOneAvailable(current_day));
         }
 
         let assignee = candidate.unwrap();
-        last_assignee = Some(assignee);
+        options.record_assignee(&mut recent_assignees, assignee);
         info!(
             "Assigning {} to turn {} -> {}",
             people[assignee].name, current_day, turn_end_date
@@ -127,23 +302,54 @@ pub fn schedule(
 
         let actual_turn_end = turn_end_date;
 
-        turns.push(Assignment {
-            person: assignee,
-            start: current_day,
-            end: actual_turn_end,
-        });
-        load[assignee] += actual_turn_end - current_day;
+        push_turn(
+            &mut turns,
+            Assignment {
+                person: assignee,
+                start: current_day,
+                end: actual_turn_end,
+                rotation: 0,
+                co_primary: vec![],
+            },
+        );
+        if options.explain {
+            rationale.push(TurnRationale {
+                start: current_day,
+                end: actual_turn_end,
+                person: assignee,
+                candidates: considered,
+                reason,
+            });
+        }
+        load[assignee] += options.turn_load(current_day, actual_turn_end);
+        options.record_consecutive_days(
+            &mut consecutive_days,
+            last_turn_end[assignee],
+            assignee,
+            current_day,
+            actual_turn_end,
+        );
+        last_turn_end[assignee] = Some(actual_turn_end);
+        days_on_call[assignee] += (actual_turn_end - current_day).num_days();
         trace!("Updated load: {:?}", load);
         current_day = actual_turn_end;
     }
 
-    Ok(Schedule { people, turns })
+    let schedule = Schedule {
+        people,
+        turns,
+        algo: Some(Algo::Greedy {
+            turn_length_days,
+            preference_weight,
+        }),
+    };
+    Ok((schedule, rationale))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::input::Person;
+    use crate::input::{Person, PreferenceType, person};
     use chrono::NaiveDate;
     use std::collections::{HashMap, HashSet};
 
@@ -151,21 +357,17 @@ mod tests {
     fn test_simple_schedule() {
         let people = vec![
             Person {
-                id: "alice".to_string(),
                 name: "Alice".to_string(),
-                ooo: HashSet::new(),
-                preferences: HashMap::new(),
+                ..person("alice")
             },
             Person {
-                id: "bob".to_string(),
                 name: "Bob".to_string(),
-                ooo: HashSet::new(),
-                preferences: HashMap::new(),
+                ..person("bob")
             },
         ];
         let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
         let end = NaiveDate::from_ymd_opt(2025, 1, 5).unwrap();
-        let schedule = schedule(people, start, end, 2, None, None).unwrap();
+        let schedule = schedule(people, start, end, 2, None, &Options::default()).unwrap();
         assert_eq!(schedule.turns.len(), 2);
         assert_eq!(schedule.turns[0].person, 0);
         assert_eq!(schedule.turns[1].person, 1);
@@ -177,21 +379,18 @@ mod tests {
         ooo.insert(NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
         let people = vec![
             Person {
-                id: "alice".to_string(),
                 name: "Alice".to_string(),
                 ooo,
-                preferences: HashMap::new(),
+                ..person("alice")
             },
             Person {
-                id: "bob".to_string(),
                 name: "Bob".to_string(),
-                ooo: HashSet::new(),
-                preferences: HashMap::new(),
+                ..person("bob")
             },
         ];
         let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
         let end = NaiveDate::from_ymd_opt(2025, 1, 5).unwrap();
-        let schedule = schedule(people, start, end, 2, None, None).unwrap();
+        let schedule = schedule(people, start, end, 2, None, &Options::default()).unwrap();
         assert_eq!(schedule.turns.len(), 2);
         assert_eq!(schedule.turns[0].person, 1); // Bob starts because Alice is OOO
         assert_eq!(schedule.turns[1].person, 0);
@@ -201,21 +400,17 @@ mod tests {
     fn test_load_balancing() {
         let people = vec![
             Person {
-                id: "alice".to_string(),
                 name: "Alice".to_string(),
-                ooo: HashSet::new(),
-                preferences: HashMap::new(),
+                ..person("alice")
             },
             Person {
-                id: "bob".to_string(),
                 name: "Bob".to_string(),
-                ooo: HashSet::new(),
-                preferences: HashMap::new(),
+                ..person("bob")
             },
         ];
         let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
         let end = NaiveDate::from_ymd_opt(2025, 1, 10).unwrap();
-        let schedule = schedule(people, start, end, 3, None, None).unwrap();
+        let schedule = schedule(people, start, end, 3, None, &Options::default()).unwrap();
         // Expected schedule:
         // Alice: 1/1 - 1/4 (3 days)
         // Bob: 1/4 - 1/7 (3 days)
@@ -232,21 +427,19 @@ mod tests {
         ooo.insert(NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
         let people = vec![
             Person {
-                id: "alice".to_string(),
                 name: "Alice".to_string(),
                 ooo: ooo.clone(),
-                preferences: HashMap::new(),
+                ..person("alice")
             },
             Person {
-                id: "bob".to_string(),
                 name: "Bob".to_string(),
                 ooo: ooo.clone(),
-                preferences: HashMap::new(),
+                ..person("bob")
             },
         ];
         let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
         let end = NaiveDate::from_ymd_opt(2025, 1, 5).unwrap();
-        let result = schedule(people, start, end, 2, None, None);
+        let result = schedule(people, start, end, 2, None, &Options::default());
         assert!(matches!(result, Err(ScheduleError::NoOneAvailable(_))));
     }
 
@@ -260,21 +453,18 @@ mod tests {
 
         let people = vec![
             Person {
-                id: "alice".to_string(),
                 name: "Alice".to_string(),
-                ooo: HashSet::new(),
                 preferences: alice_prefs,
+                ..person("alice")
             },
             Person {
-                id: "bob".to_string(),
                 name: "Bob".to_string(),
-                ooo: HashSet::new(),
-                preferences: HashMap::new(),
+                ..person("bob")
             },
         ];
         let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
         let end = NaiveDate::from_ymd_opt(2025, 1, 5).unwrap();
-        let schedule = schedule(people, start, end, 2, None, None).unwrap();
+        let schedule = schedule(people, start, end, 2, None, &Options::default()).unwrap();
         assert_eq!(schedule.turns.len(), 2);
         assert_eq!(schedule.turns[0].person, 0); // Alice is chosen because she wants to be on call
         assert_eq!(schedule.turns[1].person, 1);
@@ -290,27 +480,22 @@ mod tests {
 
         let people = vec![
             Person {
-                id: "alice".to_string(),
                 name: "Alice".to_string(),
-                ooo: HashSet::new(),
-                preferences: HashMap::new(),
+                ..person("alice")
             },
             Person {
-                id: "bob".to_string(),
                 name: "Bob".to_string(),
-                ooo: HashSet::new(),
                 preferences: bob_prefs,
+                ..person("bob")
             },
              Person {
-                id: "charlie".to_string(),
                 name: "Charlie".to_string(),
-                ooo: HashSet::new(),
-                preferences: HashMap::new(),
+                ..person("charlie")
             },
         ];
         let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
         let end = NaiveDate::from_ymd_opt(2025, 1, 5).unwrap();
-        let schedule = schedule(people, start, end, 2, None, None).unwrap();
+        let schedule = schedule(people, start, end, 2, None, &Options::default()).unwrap();
         assert_eq!(schedule.turns.len(), 2);
         // Alice: 1/1 -> 1/3
         // Charlie: 1/3 -> 1/5
@@ -318,4 +503,380 @@ mod tests {
         assert_eq!(schedule.turns[0].person, 0);
         assert_eq!(schedule.turns[1].person, 2);
     }
+
+    #[test]
+    fn test_day_weights_reduce_other_days() {
+        let people: Vec<Person> = ["alice", "bob", "charlie"]
+            .iter()
+            .map(|id| person(id))
+            .collect();
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 2, 12).unwrap(); // 6 weeks
+        let mut day_weights = HashMap::new();
+        let mut day = start;
+        while day < NaiveDate::from_ymd_opt(2025, 1, 8).unwrap() {
+            day_weights.insert(day, 3.0);
+            day = day.succ_opt().unwrap();
+        }
+        let options = Options {
+            day_weights,
+            ..Default::default()
+        };
+
+        let schedule = schedule(people, start, end, 7, None, &options).unwrap();
+        let days = |person: usize| {
+            schedule
+                .turns
+                .iter()
+                .filter(|t| t.person == person)
+                .map(|t| (t.end - t.start).num_days())
+                .sum::<i64>()
+        };
+        // Alice covers the heavy first week, which counts as three.
+        assert_eq!(schedule.turns[0].person, 0);
+        assert_eq!(days(0), 7);
+        assert_eq!(days(1), 21);
+        assert_eq!(days(2), 14);
+    }
+
+    #[test]
+    fn test_yaml_metadata_records_algo() {
+        let people = vec![
+            Person {
+                name: "Alice".to_string(),
+                ..person("alice")
+            },
+            Person {
+                name: "Bob".to_string(),
+                ..person("bob")
+            },
+        ];
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        let schedule = schedule(people, start, end, 7, None, &Options::default()).unwrap();
+
+        let yaml = schedule.to_yaml().unwrap();
+        let parsed: crate::output::YamlSchedule = serde_yaml::from_str(&yaml).unwrap();
+        assert!(matches!(
+            parsed.metadata.unwrap().algo,
+            Algo::Greedy {
+                turn_length_days: 7,
+                preference_weight: None
+            }
+        ));
+        assert_eq!(parsed.schedule.len(), 2);
+    }
+
+    #[test]
+    fn test_preference_weight_trades_off_with_load() {
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 1, 8).unwrap();
+        let people = vec![
+            Person {
+                name: "Alice".to_string(),
+                preferences: HashMap::from([(start, PreferenceType::Want)]),
+                ..person("alice")
+            },
+            Person {
+                name: "Bob".to_string(),
+                ..person("bob")
+            },
+        ];
+        // Alice has 3 more days of load than Bob but wants the turn.
+        let options = Options {
+            initial_load: Some(HashMap::from([("alice".to_string(), TimeDelta::days(3))])),
+            ..Default::default()
+        };
+        let assignee = |preference_weight| {
+            let schedule = schedule(people.clone(), start, end, 7, preference_weight, &options);
+            schedule.unwrap().turns[0].person
+        };
+
+        // Without a weight, or with 0, a Want always wins.
+        assert_eq!(assignee(None), 0);
+        assert_eq!(assignee(Some(0)), 0);
+        // A low weight doesn't make up for the extra load, a high one does.
+        assert_eq!(assignee(Some(1)), 1);
+        assert_eq!(assignee(Some(7)), 0);
+    }
+
+    #[test]
+    fn test_no_load_week_is_covered_without_counting() {
+        let people: Vec<Person> = ["alice", "bob", "charlie"]
+            .iter()
+            .map(|id| person(id))
+            .collect();
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 1, 29).unwrap(); // 4 weeks
+        let options = Options {
+            day_weights: (0..7).map(|d| (start + TimeDelta::days(d), 0.0)).collect(),
+            ..Default::default()
+        };
+
+        let schedule = schedule(people, start, end, 7, None, &options).unwrap();
+        let assignees: Vec<usize> = schedule.turns.iter().map(|t| t.person).collect();
+        // Alice covers the shutdown week, which doesn't count: she is still
+        // among the least loaded people for the third week.
+        assert_eq!(assignees, vec![0, 1, 0, 2]);
+        assert_eq!(options.turn_load(start, schedule.turns[0].end), TimeDelta::zero());
+    }
+
+    #[test]
+    fn test_reduced_weight_catches_up_later() {
+        let people: Vec<Person> = ["alice", "bob", "charlie"]
+            .iter()
+            .map(|id| person(id))
+            .collect();
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 1, 13).unwrap();
+        // Alice is at quarter weight for the first six days.
+        let options = Options {
+            person_weights: HashMap::from([(
+                "alice".to_string(),
+                (0..6).map(|d| (start + TimeDelta::days(d), 0.25)).collect(),
+            )]),
+            ..Default::default()
+        };
+
+        let schedule = schedule(people, start, end, 1, None, &options).unwrap();
+        let days = |person: usize, range: std::ops::Range<usize>| {
+            schedule.turns[range].iter().filter(|t| t.person == person).count()
+        };
+        assert!(days(0, 0..6) < days(1, 0..6));
+        assert!(days(0, 0..6) < days(2, 0..6));
+        assert!(days(0, 6..12) > days(1, 6..12));
+        assert!(days(0, 6..12) > days(2, 6..12));
+    }
+
+    #[test]
+    fn test_person_weight_scales_the_share_of_days() {
+        // Three people, as the same person never takes two turns in a row.
+        let people: Vec<Person> = [("alice", 2.0), ("bob", 2.0), ("charlie", 1.0)]
+            .iter()
+            .map(|(id, weight)| Person {
+                weight: *weight,
+                ..person(id)
+            })
+            .collect();
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 1, 31).unwrap(); // 30 days
+
+        let schedule = schedule(people, start, end, 1, None, &Options::default()).unwrap();
+        let days = |person: usize| schedule.turns.iter().filter(|t| t.person == person).count();
+        assert_eq!(days(0), 12);
+        assert_eq!(days(1), 12);
+        assert_eq!(days(2), 6);
+    }
+
+    #[test]
+    fn test_cooldown_turns() {
+        let people = |ids: &[&str]| -> Vec<Person> { ids.iter().map(|id| person(id)).collect() };
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 1, 21).unwrap();
+        let options = Options {
+            cooldown_turns: 2,
+            ..Default::default()
+        };
+
+        // Everyone sits out at least the two turns after theirs.
+        let team = people(&["alice", "bob", "carol", "dave"]);
+        let four = schedule(team, start, end, 1, None, &options).unwrap();
+        let assignees: Vec<usize> = four.turns.iter().map(|t| t.person).collect();
+        assert_eq!(assignees.len(), 20);
+        for window in assignees.windows(3) {
+            assert!(window[0] != window[1] && window[0] != window[2]);
+        }
+
+        // Two people can't, so they only avoid back-to-back turns.
+        let pair = people(&["alice", "bob"]);
+        let two = schedule(pair, start, end, 1, None, &options).unwrap();
+        for window in two.turns.windows(2) {
+            assert_ne!(window[0].person, window[1].person);
+        }
+    }
+
+    #[test]
+    fn test_max_days_caps_the_days_on_call() {
+        let people = |max_days: [Option<u32>; 3]| -> Vec<Person> {
+            ["alice", "bob", "charlie"]
+                .iter()
+                .zip(max_days)
+                .map(|(id, max_days)| Person {
+                    max_days,
+                    ..person(id)
+                })
+                .collect()
+        };
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 1, 31).unwrap(); // 30 days
+
+        // Once Alice has had her 4 days, Bob and Charlie take turns.
+        let options = Options::default();
+        let capped = schedule(people([Some(4), None, None]), start, end, 1, None, &options);
+        let capped = capped.unwrap();
+        let days = |person: usize| capped.turns.iter().filter(|t| t.person == person).count();
+        assert_eq!(days(0), 4);
+        assert_eq!(days(1), 13);
+        assert_eq!(days(2), 13);
+
+        let all_capped = people([Some(4), Some(4), Some(4)]);
+        let result = schedule(all_capped, start, end, 1, None, &options);
+        let day_13 = NaiveDate::from_ymd_opt(2025, 1, 13).unwrap();
+        assert!(matches!(result, Err(ScheduleError::NoOneAvailable(day)) if day == day_13));
+    }
+
+    #[test]
+    fn test_align_to_months() {
+        let people: Vec<Person> = ["alice", "bob"].iter().map(|id| person(id)).collect();
+        let date = |m, d| NaiveDate::from_ymd_opt(2025, m, d).unwrap();
+        let options = Options {
+            align_to_months: true,
+            ..Default::default()
+        };
+
+        let schedule = schedule(people, date(1, 30), date(2, 12), 7, None, &options).unwrap();
+        let turns: Vec<(NaiveDate, NaiveDate)> =
+            schedule.turns.iter().map(|t| (t.start, t.end)).collect();
+        assert_eq!(
+            turns,
+            vec![
+                (date(1, 30), date(2, 1)),
+                (date(2, 1), date(2, 8)),
+                (date(2, 8), date(2, 12)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escalation_only_fills_gaps() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let vacation: HashSet<NaiveDate> = (15..=21).map(day).collect();
+        let people: Vec<Person> = ["alice", "bob", "senior"]
+            .iter()
+            .map(|id| Person {
+                ooo: if *id == "senior" {
+                    HashSet::new()
+                } else {
+                    vacation.clone()
+                },
+                ..person(id)
+            })
+            .collect();
+        let options = Options {
+            escalation_only: HashSet::from(["senior".to_string()]),
+            ..Default::default()
+        };
+
+        let schedule = schedule(people, day(1), day(29), 7, None, &options).unwrap();
+        let assignees: Vec<usize> = schedule.turns.iter().map(|t| t.person).collect();
+        assert_eq!(assignees, vec![0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn test_strong_want_beats_want() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = [
+            ("alice", PreferenceType::Want),
+            ("bob", PreferenceType::StrongWant),
+        ]
+        .into_iter()
+        .map(|(id, preference)| Person {
+            preferences: HashMap::from([(day(1), preference)]),
+            ..person(id)
+        })
+        .collect();
+
+        let schedule = schedule(people, day(1), day(5), 2, None, &Options::default()).unwrap();
+        assert_eq!(schedule.turns[0].person, 1);
+    }
+
+    #[test]
+    fn test_rationale_of_a_preference_pick() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob", "carol"]
+            .into_iter()
+            .map(|id| Person {
+                preferences: if id == "carol" {
+                    HashMap::from([(day(1), PreferenceType::Want)])
+                } else {
+                    HashMap::new()
+                },
+                ..person(id)
+            })
+            .collect();
+        let options = Options {
+            explain: true,
+            ..Default::default()
+        };
+
+        let (schedule, rationale) =
+            schedule_with_rationale(people, day(1), day(7), 2, None, &options).unwrap();
+        assert_eq!(rationale.len(), schedule.turns.len());
+        assert_eq!(rationale[0].person, 2);
+        assert_eq!(rationale[0].reason, Reason::Preference);
+        let losers: Vec<(usize, i32)> = rationale[0]
+            .candidates
+            .iter()
+            .filter(|c| c.person != 2)
+            .map(|c| (c.person, c.preference_group))
+            .collect();
+        assert_eq!(losers, vec![(0, 2), (1, 2)]);
+        // Then Alice and Bob have no load, and Alice comes first.
+        assert_eq!(rationale[1].reason, Reason::Order);
+        assert_eq!(rationale[2].reason, Reason::Load);
+    }
+
+    #[test]
+    fn test_seeded_tie_break() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob", "carol", "dave"]
+            .into_iter()
+            .map(person)
+            .collect();
+        let assignees = |tie_break_seed: Option<u64>| {
+            let options = Options {
+                tie_break_seed,
+                ..Default::default()
+            };
+            let schedule = schedule(people.clone(), day(1), day(9), 1, None, &options).unwrap();
+            schedule.turns.iter().map(|t| t.person).collect::<Vec<_>>()
+        };
+
+        // Without a seed, ties go to the first in the rotation.
+        assert_eq!(assignees(None), vec![0, 1, 2, 3, 0, 1, 2, 3]);
+        assert_eq!(assignees(Some(1)), assignees(Some(1)));
+        assert!((2..10).any(|seed| assignees(Some(seed)) != assignees(Some(1))));
+    }
+
+    #[test]
+    fn test_soft_ooo_is_a_last_resort() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob", "carol"]
+            .into_iter()
+            .map(|id| Person {
+                ooo: if id == "carol" {
+                    HashSet::new()
+                } else {
+                    HashSet::from([day(5)])
+                },
+                preferences: if id == "carol" {
+                    (1..7).map(|d| (day(d), PreferenceType::SoftOoo)).collect()
+                } else {
+                    HashMap::new()
+                },
+                ..person(id)
+            })
+            .collect();
+        // However loaded Bob is, Carol only takes the turn nobody else can.
+        let options = Options {
+            initial_load: Some(HashMap::from([("bob".to_string(), TimeDelta::days(10))])),
+            ..Default::default()
+        };
+
+        for weight in [None, Some(1)] {
+            let schedule = schedule(people.clone(), day(1), day(7), 2, weight, &options).unwrap();
+            let assignees: Vec<usize> = schedule.turns.iter().map(|t| t.person).collect();
+            assert_eq!(assignees, vec![0, 1, 2]);
+        }
+    }
 }
@@ -1,3 +1,858 @@
-pub(crate) mod roundrobin;
-pub(crate) mod greedy;
-pub(crate) mod balanced;
\ No newline at end of file
+use crate::config::{FixedAssignment, Position};
+use crate::input::Person;
+use crate::output::{Assignment, Schedule, ScheduleError};
+use chrono::{Datelike, Months, NaiveDate, TimeDelta};
+use log::debug;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+pub mod roundrobin;
+pub mod greedy;
+pub mod balanced;
+pub mod ordered;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Adds `turn` to `turns` unless it is empty. Boundary adjustments can shrink
+/// a turn to nothing, and an empty turn would show up in the output and count
+/// as a turn in the fairness stats. Turn lengths are at least one day, so the
+/// algorithms still move forward.
+pub fn push_turn(turns: &mut Vec<Assignment>, turn: Assignment) {
+    if turn.start < turn.end {
+        turns.push(turn);
+    } else {
+        debug!("Dropping empty turn on {}", turn.start);
+    }
+}
+
+/// Generates `rotations` parallel schedules with `generate`, e.g. a primary
+/// and a backup on-call. Each rotation is balanced on its own, and people are
+/// treated as OOO on the days they already cover in a lower rotation. Fixed
+/// and previous turns are only in the primary rotation.
+pub fn with_rotations(
+    rotations: u8,
+    people: Vec<Person>,
+    options: &Options,
+    mut generate: impl FnMut(Vec<Person>, &Options) -> Result<Schedule, ScheduleError>,
+) -> Result<Schedule, ScheduleError> {
+    let mut schedule = generate(people.clone(), options)?;
+    let backup_options = Options {
+        fixed: vec![],
+        previous_turns: vec![],
+        ..options.clone()
+    };
+    for rotation in 1..rotations {
+        let mut busy: HashMap<&str, HashSet<NaiveDate>> = HashMap::new();
+        for turn in &schedule.turns {
+            let days = turn.start.iter_days().take_while(|d| *d < turn.end);
+            busy.entry(&schedule.people[turn.person].id)
+                .or_default()
+                .extend(days);
+        }
+        let mut available = people.clone();
+        for person in &mut available {
+            if let Some(days) = busy.get(person.id.as_str()) {
+                person.ooo.extend(days);
+            }
+        }
+        debug!("Generating rotation {}", rotation);
+        let backup = generate(available, &backup_options)?;
+        // The algorithm may have reordered the people.
+        for turn in backup.turns {
+            let id = &backup.people[turn.person].id;
+            let person = schedule.people.iter().position(|p| &p.id == id).unwrap();
+            schedule.turns.push(Assignment {
+                person,
+                rotation,
+                ..turn
+            });
+        }
+    }
+    schedule.turns.sort_by_key(|t| (t.start, t.rotation));
+    Ok(schedule)
+}
+
+/// Options shared by all the scheduling algorithms.
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// Load carried over from a previous schedule, by person id.
+    pub initial_load: Option<HashMap<String, TimeDelta>>,
+    /// How much a day counts toward load; days not listed count 1.0.
+    pub day_weights: HashMap<NaiveDate, f64>,
+    /// Upper bound on the number of generated turns, to fail fast on
+    /// misconfigured windows instead of exhausting memory.
+    pub max_turns: Option<usize>,
+    /// How much of the usual load each person should carry on specific days,
+    /// by person id; days not listed weigh 1.0.
+    pub person_weights: HashMap<String, HashMap<NaiveDate, f64>>,
+    /// Load waived for people ramping up, by person id, as running totals
+    /// keyed by the day they change.
+    pub ramp_credits: HashMap<String, BTreeMap<NaiveDate, TimeDelta>>,
+    /// Keep every candidate evaluated by the balanced algorithm, for
+    /// debugging its choices.
+    pub collect_trace: bool,
+    /// Record why the greedy algorithm gave each turn to its assignee.
+    pub explain: bool,
+    /// Don't let turns cross the 1st of a month.
+    pub align_to_months: bool,
+    /// Let the balanced algorithm swap already assigned turns when that makes
+    /// the final load more even.
+    pub optimize_swaps: bool,
+    /// Ids of the people only scheduled when no one else is available.
+    pub escalation_only: HashSet<String>,
+    /// Align fixed-length turns to the end of the schedule rather than to its
+    /// start, so that a shorter turn is at the start.
+    pub anchor_end: bool,
+    /// When in the schedule people would rather be on call, by person id.
+    pub positions: HashMap<String, Position>,
+    /// The days someone is off call at least between two of their turns.
+    pub min_rest_days: u8,
+    /// Days on which nobody is on call.
+    pub uncovered: HashSet<NaiveDate>,
+    /// Turns agreed in advance, which the algorithms keep as they are.
+    pub fixed: Vec<FixedAssignment>,
+    /// How many turns someone sits out after theirs; 0 counts as 1, as no
+    /// one takes two turns in a row.
+    pub cooldown_turns: usize,
+    /// Break ties between equally good candidates randomly, with this seed,
+    /// rather than in favor of the first one.
+    pub tie_break_seed: Option<u64>,
+    /// The most days in a row anyone is on call, over back-to-back turns.
+    pub max_consecutive_days: Option<u8>,
+    /// The turns right before the schedule, oldest first (e.g. frozen ones),
+    /// which rest, cooldowns and consecutive days carry over from.
+    pub previous_turns: Vec<FixedAssignment>,
+}
+
+impl Options {
+    /// The load each person starts with, in the order of `people`.
+    pub fn initial_load(&self, people: &[Person]) -> Vec<TimeDelta> {
+        people
+            .iter()
+            .map(|p| {
+                if let Some(ref il) = self.initial_load {
+                    il.get(&p.id).cloned().unwrap_or(TimeDelta::zero())
+                } else {
+                    TimeDelta::zero()
+                }
+            })
+            .collect()
+    }
+
+    /// Whether someone whose last turn ended on `last_turn_end` has been off
+    /// call for `min_rest_days` by `start`.
+    pub fn is_rested(&self, last_turn_end: Option<NaiveDate>, start: NaiveDate) -> bool {
+        last_turn_end.is_none_or(|end| (start - end).num_days() >= self.min_rest_days.into())
+    }
+
+    /// The cooldowns to try, from `cooldown_turns` down to 1, so that small
+    /// teams can still be scheduled with a shorter cooldown.
+    pub fn cooldowns(&self) -> impl Iterator<Item = usize> {
+        (1..=self.cooldown_turns.max(1)).rev()
+    }
+
+    /// Whether someone on call for the `consecutive_days` in a row up to
+    /// `last_turn_end` can take a turn from `start` to `end` without going
+    /// over `max_consecutive_days`. The run only goes on if the turn starts
+    /// right when their last one ended.
+    pub fn is_within_consecutive_days(
+        &self,
+        consecutive_days: i64,
+        last_turn_end: Option<NaiveDate>,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> bool {
+        let Some(max) = self.max_consecutive_days else {
+            return true;
+        };
+        let run = if last_turn_end == Some(start) {
+            consecutive_days
+        } else {
+            0
+        };
+        run + (end - start).num_days() <= i64::from(max)
+    }
+
+    /// Records the turn of `person` from `start` to `end` in
+    /// `consecutive_days`, the days each person has been on call in a row,
+    /// which restart whenever someone else takes a turn. To be called before
+    /// updating the `last_turn_end` of `person`.
+    pub fn record_consecutive_days(
+        &self,
+        consecutive_days: &mut [i64],
+        last_turn_end: Option<NaiveDate>,
+        person: usize,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) {
+        let days = (end - start).num_days();
+        for (i, run) in consecutive_days.iter_mut().enumerate() {
+            *run = if i != person {
+                0
+            } else if last_turn_end == Some(start) {
+                *run + days
+            } else {
+                days
+            };
+        }
+    }
+
+    /// Records `assignee` as the latest in `recent`, which keeps as many
+    /// assignees as the cooldown lasts.
+    pub fn record_assignee(&self, recent: &mut VecDeque<usize>, assignee: usize) {
+        recent.push_back(assignee);
+        if recent.len() > self.cooldown_turns.max(1) {
+            recent.pop_front();
+        }
+    }
+
+    /// Records the `previous_turns` of `people` as if they had been scheduled,
+    /// see [`Options::record_consecutive_days`] and
+    /// [`Options::record_assignee`]. Turns of unknown people are skipped.
+    pub fn record_previous_turns(
+        &self,
+        people: &[Person],
+        last_turn_end: &mut [Option<NaiveDate>],
+        consecutive_days: &mut [i64],
+        recent: &mut VecDeque<usize>,
+    ) {
+        for turn in &self.previous_turns {
+            let Some(person) = people.iter().position(|p| p.id == turn.person) else {
+                continue;
+            };
+            self.record_consecutive_days(
+                consecutive_days,
+                last_turn_end[person],
+                person,
+                turn.from,
+                turn.to,
+            );
+            last_turn_end[person] = Some(turn.to);
+            self.record_assignee(recent, person);
+        }
+    }
+
+    /// Whether `person` is only a fallback for when no one else is available.
+    pub fn is_escalation_only(&self, person: &Person) -> bool {
+        self.escalation_only.contains(&person.id)
+    }
+
+    /// The escalation-only people, least loaded first, to try in turn when no
+    /// one else can take a turn.
+    pub fn escalation_fallback(&self, people: &[Person], load: &[TimeDelta]) -> Vec<usize> {
+        let mut fallback: Vec<usize> = (0..people.len())
+            .filter(|&i| self.is_escalation_only(&people[i]))
+            .collect();
+        fallback.sort_by_key(|&i| load[i]);
+        fallback
+    }
+
+    /// How well a turn starting on `date` matches the `position` preference of
+    /// `person` in a schedule from `start` to `end`: 0 when it does, 2 when
+    /// it is in the other half of the schedule and 1 without a preference.
+    /// Lower is better, to break ties between equally good candidates.
+    pub fn position_rank(
+        &self,
+        person: &Person,
+        date: NaiveDate,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> u8 {
+        let early = (date - start) * 2 < end - start;
+        match self.positions.get(&person.id) {
+            None => 1,
+            Some(Position::Early) if early => 0,
+            Some(Position::Late) if !early => 0,
+            Some(_) => 2,
+        }
+    }
+
+    /// Fails once `turns` generated turns reach the configured maximum.
+    pub fn check_turn_count(&self, turns: usize) -> Result<(), ScheduleError> {
+        match self.max_turns {
+            Some(max_turns) if turns >= max_turns => Err(ScheduleError::TooManyTurns(max_turns)),
+            _ => Ok(()),
+        }
+    }
+
+    /// The end of a turn of `turn_length_days` starting on `start`, before
+    /// `align_turn_end`, in a schedule ending on `end`. With `anchor_end`,
+    /// turns end a multiple of the turn length before `end`, so that only
+    /// the first one can be shorter.
+    pub fn fixed_turn_end(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        turn_length_days: u8,
+    ) -> NaiveDate {
+        let length = i64::from(turn_length_days);
+        let remainder = (end - start).num_days() % length;
+        let days = if self.anchor_end && remainder > 0 {
+            remainder
+        } else {
+            length
+        };
+        end.min(start + TimeDelta::days(days))
+    }
+
+    /// The end of a turn starting on `start` that would end on `end`: with
+    /// `align_to_months`, turns that would cross the 1st of a month end there
+    /// instead, even if they become shorter than the turn length. Turns also
+    /// end on the first `uncovered` day, where a fixed turn starts, or after
+    /// `max_consecutive_days`.
+    pub fn align_turn_end(&self, start: NaiveDate, end: NaiveDate) -> NaiveDate {
+        let end = if self.align_to_months {
+            let next_month = start.with_day(1).unwrap() + Months::new(1);
+            end.min(next_month)
+        } else {
+            end
+        };
+        let end = match self.max_consecutive_days {
+            Some(max) => end.min(start + TimeDelta::days(max.into())),
+            None => end,
+        };
+        start
+            .iter_days()
+            .take_while(|date| *date < end)
+            .find(|date| self.uncovered.contains(date) || self.is_fixed(*date))
+            .unwrap_or(end)
+    }
+
+    /// Whether `date` is part of a fixed turn.
+    fn is_fixed(&self, date: NaiveDate) -> bool {
+        self.fixed.iter().any(|f| f.from <= date && date < f.to)
+    }
+
+    /// The fixed turn covering `date`, from `date` on and cut at `end`, for
+    /// the algorithms to keep as it is.
+    pub fn fixed_turn(
+        &self,
+        people: &[Person],
+        date: NaiveDate,
+        end: NaiveDate,
+    ) -> Option<Assignment> {
+        let fixed = self.fixed.iter().find(|f| f.from <= date && date < f.to)?;
+        let position = |id: &String| people.iter().position(|p| p.id == *id);
+        Some(Assignment {
+            person: position(&fixed.person)?,
+            start: date,
+            end: fixed.to.min(end),
+            rotation: 0,
+            co_primary: fixed.co_primary.iter().filter_map(position).collect(),
+        })
+    }
+
+    /// The first day from `date` on that isn't `uncovered`, where the next
+    /// turn starts.
+    pub fn skip_uncovered(&self, mut date: NaiveDate) -> NaiveDate {
+        while self.uncovered.contains(&date) {
+            date = date.succ_opt().unwrap();
+        }
+        date
+    }
+
+    /// The load accrued by covering `[start, end)`, each day counting for
+    /// its weight.
+    pub fn turn_load(&self, start: NaiveDate, end: NaiveDate) -> TimeDelta {
+        if self.day_weights.is_empty() {
+            return end - start;
+        }
+        let mut seconds = 0.0;
+        let mut current = start;
+        while current < end {
+            seconds += SECONDS_PER_DAY * self.day_weights.get(&current).copied().unwrap_or(1.0);
+            current = current.succ_opt().unwrap();
+        }
+        TimeDelta::seconds(seconds.round() as i64)
+    }
+
+    /// `load` as compared against other people on `date`: divided by the
+    /// person's weight, times their weight on that day if overridden, so that
+    /// someone at half weight looks twice as loaded and catches up once back
+    /// at full weight. The load waived by a ramp so far counts as carried, so
+    /// that it is never caught up on.
+    pub fn effective_load(&self, person: &Person, load: TimeDelta, date: NaiveDate) -> TimeDelta {
+        let credit = self
+            .ramp_credits
+            .get(&person.id)
+            .and_then(|credits| credits.range(..=date).next_back())
+            .map_or(TimeDelta::zero(), |(_, credit)| *credit);
+        let load = load + credit;
+        let weight = self
+            .person_weights
+            .get(&person.id)
+            .and_then(|weights| weights.get(&date))
+            .copied()
+            .unwrap_or(1.0)
+            * person.weight;
+        if weight == 1.0 {
+            return load;
+        }
+        TimeDelta::seconds((load.num_seconds() as f64 / weight).round() as i64)
+    }
+
+    /// How the algorithms break ties, see `tie_break_seed`.
+    pub fn tie_break(&self) -> TieBreak {
+        TieBreak {
+            rng: self.tie_break_seed.map(ChaCha8Rng::seed_from_u64),
+            ties: 1,
+        }
+    }
+}
+
+/// Picks among candidates that are equally good. Without a seed, the first
+/// one wins; with one, each is as likely to win, reproducibly.
+pub struct TieBreak {
+    rng: Option<ChaCha8Rng>,
+    /// How many candidates are tied with the best one, itself included.
+    ties: u32,
+}
+
+impl TieBreak {
+    /// To be called when a candidate is strictly better than the best one.
+    pub fn new_best(&mut self) {
+        self.ties = 1;
+    }
+
+    /// Whether a candidate as good as the best one takes its place. Replacing
+    /// the best with a chance of one in the number of ties so far leaves each
+    /// of them equally likely to be picked in the end.
+    pub fn replaces_best(&mut self) -> bool {
+        self.ties += 1;
+        let ties = self.ties;
+        self.rng
+            .as_mut()
+            .is_some_and(|rng| rng.random_ratio(1, ties))
+    }
+
+    /// The item with the lowest key, like `Iterator::min_by_key`.
+    pub fn min_by_key<T, K: Ord>(
+        &mut self,
+        items: impl IntoIterator<Item = T>,
+        mut key: impl FnMut(&T) -> K,
+    ) -> Option<T> {
+        let mut best: Option<(T, K)> = None;
+        for item in items {
+            let k = key(&item);
+            let replace = match best.as_ref().map(|(_, best_key)| k.cmp(best_key)) {
+                None | Some(Ordering::Less) => {
+                    self.new_best();
+                    true
+                }
+                Some(Ordering::Equal) => self.replaces_best(),
+                Some(Ordering::Greater) => false,
+            };
+            if replace {
+                best = Some((item, k));
+            }
+        }
+        best.map(|(item, _)| item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OrderSource;
+    use crate::input::person;
+
+    #[test]
+    fn test_no_empty_turns_at_boundaries() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob", "carol"]
+            .iter()
+            .map(|id| Person {
+                ooo: if *id == "alice" {
+                    HashSet::from([date(2025, 1, 1), date(2025, 1, 31)])
+                } else {
+                    HashSet::new()
+                },
+                ..person(id)
+            })
+            .collect();
+        let options = Options {
+            day_weights: HashMap::from([(date(2025, 1, 1), 2.0), (date(2024, 12, 31), 0.0)]),
+            person_weights: HashMap::from([(
+                "bob".to_string(),
+                HashMap::from([(date(2025, 1, 1), 0.5)]),
+            )]),
+            align_to_months: true,
+            ..Default::default()
+        };
+        let ranges = [
+            (date(2024, 12, 31), date(2025, 1, 1)),
+            (date(2025, 1, 1), date(2025, 1, 2)),
+            (date(2024, 12, 30), date(2025, 1, 3)),
+            (date(2025, 1, 30), date(2025, 2, 2)),
+        ];
+
+        for (start, end) in ranges {
+            let schedules = [
+                roundrobin::schedule(people.clone(), start, end, 3, None, &options),
+                greedy::schedule(people.clone(), start, end, 3, None, &options),
+                balanced::schedule(people.clone(), start, end, 1, 3, None, false, &options),
+                ordered::schedule(
+                    people.clone(),
+                    start,
+                    end,
+                    OrderSource::Alphabetical,
+                    1,
+                    3,
+                    &options,
+                ),
+            ];
+            for schedule in schedules {
+                let schedule = schedule.unwrap();
+                assert!(
+                    schedule.turns.iter().all(|t| t.start < t.end),
+                    "{}",
+                    schedule
+                );
+                assert_eq!(schedule.turns.first().unwrap().start, start);
+                assert_eq!(schedule.turns.last().unwrap().end, end);
+            }
+        }
+    }
+
+    #[test]
+    fn test_position_preference_breaks_ties() {
+        let date = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob", "carol", "dave"]
+            .iter()
+            .map(|id| person(id))
+            .collect();
+        let options = Options {
+            positions: HashMap::from([
+                ("carol".to_string(), Position::Early),
+                ("alice".to_string(), Position::Late),
+            ]),
+            ..Default::default()
+        };
+        let (start, end) = (date(1), date(29));
+        assert_eq!(options.position_rank(&people[2], date(14), start, end), 0);
+        assert_eq!(options.position_rank(&people[2], date(15), start, end), 2);
+        assert_eq!(options.position_rank(&people[1], date(15), start, end), 1);
+
+        let assignees = |schedule: Result<crate::output::Schedule, ScheduleError>| -> Vec<usize> {
+            schedule.unwrap().turns.iter().map(|t| t.person).collect()
+        };
+        // Everyone has the same load at the start: carol goes first, and
+        // alice waits for the second half.
+        let by_greedy = greedy::schedule(people.clone(), start, end, 7, None, &options);
+        assert_eq!(assignees(by_greedy), vec![2, 1, 0, 3]);
+        let by_balanced = balanced::schedule(people, start, end, 7, 7, None, false, &options);
+        assert_eq!(assignees(by_balanced), vec![2, 1, 0, 3]);
+    }
+
+    #[test]
+    fn test_anchor_end_puts_the_short_turn_first() {
+        let date = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob"].iter().map(|id| person(id)).collect();
+        let lengths = |schedule: Result<crate::output::Schedule, ScheduleError>| -> Vec<i64> {
+            let schedule = schedule.unwrap();
+            schedule
+                .turns
+                .iter()
+                .map(|t| (t.end - t.start).num_days())
+                .collect()
+        };
+        let (start, end) = (date(1), date(11));
+
+        let options = Options::default();
+        let from_start = roundrobin::schedule(people.clone(), start, end, 3, None, &options);
+        assert_eq!(lengths(from_start), vec![3, 3, 3, 1]);
+        let options = Options {
+            anchor_end: true,
+            ..Default::default()
+        };
+        let from_end = roundrobin::schedule(people.clone(), start, end, 3, None, &options);
+        assert_eq!(lengths(from_end), vec![1, 3, 3, 3]);
+        let from_end = greedy::schedule(people.clone(), start, end, 3, None, &options);
+        assert_eq!(lengths(from_end), vec![1, 3, 3, 3]);
+        // Without a remainder, all turns are full.
+        let from_end = greedy::schedule(people, start, date(10), 3, None, &options);
+        assert_eq!(lengths(from_end), vec![3, 3, 3]);
+    }
+
+    #[test]
+    fn test_min_rest_days() {
+        let date = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob", "carol"]
+            .iter()
+            .map(|id| person(id))
+            .collect();
+        let options = Options {
+            min_rest_days: 3,
+            ..Default::default()
+        };
+        let all = |days: u8| {
+            let (start, end) = (date(1), date(21));
+            let order = OrderSource::Alphabetical;
+            [
+                roundrobin::schedule(people.clone(), start, end, days, None, &options),
+                greedy::schedule(people.clone(), start, end, days, None, &options),
+                balanced::schedule(
+                    people.clone(),
+                    start,
+                    end,
+                    days,
+                    days,
+                    None,
+                    false,
+                    &options,
+                ),
+                ordered::schedule(people.clone(), start, end, order, days, days, &options),
+            ]
+        };
+
+        // With 2-day turns, everyone is off for 4 days between turns.
+        for schedule in all(2) {
+            let schedule = schedule.unwrap();
+            for (i, turn) in schedule.turns.iter().enumerate() {
+                let mut later = schedule.turns[i + 1..].iter();
+                let next = later.find(|t| t.person == turn.person);
+                assert!(next.is_none_or(|next| (next.start - turn.end).num_days() >= 3));
+            }
+        }
+        // With 1-day turns, three people can't rest for 3 days.
+        for schedule in all(1) {
+            let error = schedule.unwrap_err();
+            assert!(matches!(error, ScheduleError::NoOneAvailable(day) if day == date(4)));
+        }
+    }
+
+    #[test]
+    fn test_no_coverage_leaves_a_gap() {
+        let date = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob", "carol"]
+            .iter()
+            .map(|id| person(id))
+            .collect();
+        let options = Options {
+            uncovered: [date(10), date(11)].into(),
+            ..Default::default()
+        };
+        let (start, end) = (date(1), date(22));
+        let order = OrderSource::Alphabetical;
+        let schedules = [
+            roundrobin::schedule(people.clone(), start, end, 7, None, &options),
+            greedy::schedule(people.clone(), start, end, 7, None, &options),
+            balanced::schedule(people.clone(), start, end, 7, 7, None, false, &options),
+            ordered::schedule(people.clone(), start, end, order, 7, 7, &options),
+        ];
+
+        // The turn running into the gap ends there, and the next one starts
+        // after it.
+        for schedule in schedules {
+            let turns: Vec<_> = schedule
+                .unwrap()
+                .turns
+                .iter()
+                .map(|t| (t.start, t.end))
+                .collect();
+            assert_eq!(
+                turns,
+                vec![
+                    (date(1), date(8)),
+                    (date(8), date(10)),
+                    (date(12), date(19)),
+                    (date(19), date(22))
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_fixed_turns_are_kept() {
+        let date = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob", "carol"]
+            .iter()
+            .map(|id| person(id))
+            .collect();
+        let options = Options {
+            fixed: vec![FixedAssignment {
+                person: "carol".to_string(),
+                from: date(10),
+                to: date(13),
+                co_primary: vec![],
+            }],
+            ..Default::default()
+        };
+        let (start, end) = (date(1), date(22));
+        let order = OrderSource::Alphabetical;
+        let schedules = [
+            roundrobin::schedule(people.clone(), start, end, 7, None, &options),
+            greedy::schedule(people.clone(), start, end, 7, None, &options),
+            balanced::schedule(people.clone(), start, end, 7, 7, None, false, &options),
+            ordered::schedule(people.clone(), start, end, order, 7, 7, &options),
+        ];
+
+        // The turn running into the fixed one ends there, and the rotation
+        // resumes after it.
+        for schedule in schedules {
+            let schedule = schedule.unwrap();
+            let turns: Vec<_> = schedule.turns.iter().map(|t| (t.start, t.end)).collect();
+            assert_eq!(
+                turns,
+                vec![
+                    (date(1), date(8)),
+                    (date(8), date(10)),
+                    (date(10), date(13)),
+                    (date(13), date(20)),
+                    (date(20), date(22))
+                ]
+            );
+            assert_eq!(schedule.people[schedule.turns[2].person].id, "carol");
+        }
+
+        // Swapping turns to even out the load doesn't move a fixed turn
+        // either, even one that makes someone much busier.
+        let mut people = people;
+        people.push(Person {
+            id: "dave".to_string(),
+            name: "dave".to_string(),
+            ..people[0].clone()
+        });
+        let options = Options {
+            initial_load: Some(HashMap::from([("carol".to_string(), TimeDelta::days(30))])),
+            fixed: vec![FixedAssignment {
+                person: "carol".to_string(),
+                from: date(1),
+                to: date(15),
+                co_primary: vec![],
+            }],
+            optimize_swaps: true,
+            ..Default::default()
+        };
+        let schedule =
+            balanced::schedule(people, date(1), date(29), 3, 7, None, false, &options).unwrap();
+        let first = &schedule.turns[0];
+        assert_eq!(schedule.people[first.person].id, "carol");
+        assert_eq!((first.start, first.end), (date(1), date(15)));
+    }
+
+    #[test]
+    fn test_backup_rotation() {
+        let date = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob", "carol", "dave"]
+            .iter()
+            .map(|id| person(id))
+            .collect();
+        let (start, end) = (date(1), date(29));
+        let options = Options::default();
+
+        let schedule = with_rotations(2, people, &options, |people, options| {
+            greedy::schedule(people, start, end, 7, None, options)
+        })
+        .unwrap();
+        let rotation = |r: u8| -> Vec<&Assignment> {
+            schedule.turns.iter().filter(|t| t.rotation == r).collect()
+        };
+        let (primary, backup) = (rotation(0), rotation(1));
+        assert_eq!(primary.len(), 4);
+        assert_eq!(backup.len(), 4);
+        for (p, b) in primary.iter().zip(&backup) {
+            assert_eq!((p.start, p.end), (b.start, b.end));
+            assert_ne!(p.person, b.person);
+        }
+        // Each rotation is balanced on its own: everyone takes one turn.
+        for turns in [primary, backup] {
+            let mut people: Vec<usize> = turns.iter().map(|t| t.person).collect();
+            people.sort();
+            assert_eq!(people, vec![0, 1, 2, 3]);
+        }
+
+        let yaml = schedule.to_yaml().unwrap();
+        assert_eq!(yaml.matches("rotation: 1").count(), 4);
+        assert!(!yaml.contains("rotation: 0"));
+    }
+
+    #[test]
+    fn test_max_consecutive_days() {
+        let date = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob", "carol"]
+            .iter()
+            .map(|id| person(id))
+            .collect();
+        // Alice's fixed turn leaves her 2 more days in a row, not a whole
+        // turn.
+        let options = Options {
+            max_consecutive_days: Some(5),
+            fixed: vec![FixedAssignment {
+                person: "alice".to_string(),
+                from: date(1),
+                to: date(4),
+                co_primary: vec![],
+            }],
+            ..Default::default()
+        };
+        let (start, end) = (date(1), date(22));
+        let order = OrderSource::Alphabetical;
+        let schedules = [
+            roundrobin::schedule(people.clone(), start, end, 7, None, &options),
+            greedy::schedule(people.clone(), start, end, 7, None, &options),
+            balanced::schedule(people.clone(), start, end, 7, 7, None, false, &options),
+            ordered::schedule(people.clone(), start, end, order, 7, 7, &options),
+        ];
+
+        for schedule in schedules {
+            let schedule = schedule.unwrap();
+            let turns: Vec<_> = schedule
+                .turns
+                .iter()
+                .map(|t| (t.person, t.start, t.end))
+                .collect();
+            // The turns are cut to 5 days and the next one goes to someone
+            // else.
+            assert_eq!(turns[0], (0, date(1), date(4)));
+            assert_ne!(turns[1].0, 0);
+            assert_eq!((turns[1].1, turns[1].2), (date(4), date(9)));
+            for pair in turns.windows(2) {
+                assert!((pair[0].2 - pair[0].1).num_days() <= 5);
+                assert_ne!(pair[0].0, pair[1].0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_previous_turns_carry_over() {
+        let date = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob", "carol"]
+            .iter()
+            .map(|id| person(id))
+            .collect();
+        let options = Options {
+            previous_turns: vec![
+                FixedAssignment {
+                    person: "bob".to_string(),
+                    from: date(1),
+                    to: date(8),
+                    co_primary: vec![],
+                },
+                FixedAssignment {
+                    person: "alice".to_string(),
+                    from: date(8),
+                    to: date(15),
+                    co_primary: vec![],
+                },
+            ],
+            ..Default::default()
+        };
+        let (start, end) = (date(15), date(29));
+        let order = OrderSource::Alphabetical;
+        let schedules = [
+            roundrobin::schedule(people.clone(), start, end, 7, None, &options),
+            greedy::schedule(people.clone(), start, end, 7, None, &options),
+            balanced::schedule(people.clone(), start, end, 7, 7, None, false, &options),
+            ordered::schedule(people.clone(), start, end, order, 7, 7, &options),
+        ];
+
+        // Alice would go first, but she was on call right before the schedule.
+        for schedule in schedules {
+            let schedule = schedule.unwrap();
+            assert_ne!(schedule.people[schedule.turns[0].person].id, "alice");
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use crate::algo::balanced::{calculate_load_variance, is_ooo_for_turn};
+use crate::algo::{Options, push_turn};
+use crate::config::{Algo, OrderSource};
+use crate::input::Person;
+use crate::output::{Assignment, Schedule, ScheduleError};
+use chrono::{Days, NaiveDate, TimeDelta};
+use log::{debug, info, trace};
+use std::collections::VecDeque;
+
+/// Assigns turns to `people` strictly in the given order, like round robin,
+/// choosing the length of each turn like balanced: the one that keeps the
+/// load variance lowest. People OOO at the start of their turn are skipped
+/// until the next round.
+pub fn schedule(
+    people: Vec<Person>,
+    start: NaiveDate,
+    end: NaiveDate,
+    order_source: OrderSource,
+    min_turn_days: u8,
+    max_turn_days: u8,
+    options: &Options,
+) -> Result<Schedule, ScheduleError> {
+    let mut turns = vec![];
+    let mut current_day = start;
+    let mut load: Vec<TimeDelta> = options.initial_load(&people);
+    let mut next = 0;
+    let mut last_turn_end: Vec<Option<NaiveDate>> = vec![None; people.len()];
+    let mut consecutive_days: Vec<i64> = vec![0; people.len()];
+    let mut recent = VecDeque::new();
+    options.record_previous_turns(
+        &people,
+        &mut last_turn_end,
+        &mut consecutive_days,
+        &mut recent,
+    );
+    // The order goes on from whoever was on call last.
+    if let Some(&last) = recent.back() {
+        next = (last + 1) % people.len();
+    }
+
+    info!("Starting ordered balanced schedule generation");
+    trace!("Initial load: {:?}", load);
+
+    while current_day < end {
+        current_day = options.skip_uncovered(current_day);
+        if current_day >= end {
+            break;
+        }
+        options.check_turn_count(turns.len())?;
+        if let Some(turn) = options.fixed_turn(&people, current_day, end) {
+            info!(
+                "Keeping the fixed turn of {} {} -> {}",
+                people[turn.person].name, turn.start, turn.end
+            );
+            load[turn.person] += options.turn_load(turn.start, turn.end);
+            options.record_consecutive_days(
+                &mut consecutive_days,
+                last_turn_end[turn.person],
+                turn.person,
+                turn.start,
+                turn.end,
+            );
+            last_turn_end[turn.person] = Some(turn.end);
+            current_day = turn.end;
+            push_turn(&mut turns, turn);
+            continue;
+        }
+        debug!("Planning turn starting from {}", current_day);
+        let mut best_choice: Option<(usize, NaiveDate, f64)> = None;
+
+        // The first person in order who can cover at least the shortest turn,
+        // falling back on the escalation-only people.
+        let rotation = (0..people.len())
+            .map(|offset| (next + offset) % people.len())
+            .filter(|&i| !options.is_escalation_only(&people[i]));
+        let fallback = options.escalation_fallback(&people, &load);
+        for i in rotation.chain(fallback) {
+            if !options.is_rested(last_turn_end[i], current_day) {
+                debug!("Skipping {} (resting)", people[i].name);
+                continue;
+            }
+            for turn_len in min_turn_days..=max_turn_days {
+                let turn_end = options.align_turn_end(
+                    current_day,
+                    std::cmp::min(
+                        end,
+                        current_day
+                            .checked_add_days(Days::new(turn_len as u64))
+                            .unwrap(),
+                    ),
+                );
+                if is_ooo_for_turn(&people[i], current_day, turn_end) {
+                    break;
+                }
+                if !options.is_within_consecutive_days(
+                    consecutive_days[i],
+                    last_turn_end[i],
+                    current_day,
+                    turn_end,
+                ) {
+                    debug!("Skipping {} (max consecutive days reached)", people[i].name);
+                    break;
+                }
+
+                let mut next_load = load.clone();
+                next_load[i] += options.turn_load(current_day, turn_end);
+                let effective_load: Vec<TimeDelta> = people
+                    .iter()
+                    .zip(&next_load)
+                    .map(|(p, l)| options.effective_load(p, *l, current_day))
+                    .collect();
+                let variance = calculate_load_variance(&effective_load);
+                trace!(
+                    "Considering {} for {} -> {} (variance: {})",
+                    people[i].name, current_day, turn_end, variance
+                );
+                if best_choice.is_none_or(|(_, _, best_variance)| variance < best_variance) {
+                    best_choice = Some((i, turn_end, variance));
+                }
+                if turn_end == end {
+                    break;
+                }
+            }
+            if best_choice.is_some() {
+                break;
+            }
+            debug!("Skipping {} (OOO)", people[i].name);
+        }
+
+        let Some((assignee, turn_end, _)) = best_choice else {
+            return Err(ScheduleError::NoOneAvailable(current_day));
+        };
+        info!(
+            "Assigning {} to turn {} -> {}",
+            people[assignee].name, current_day, turn_end
+        );
+        push_turn(
+            &mut turns,
+            Assignment {
+                person: assignee,
+                start: current_day,
+                end: turn_end,
+                rotation: 0,
+                co_primary: vec![],
+            },
+        );
+        load[assignee] += options.turn_load(current_day, turn_end);
+        options.record_consecutive_days(
+            &mut consecutive_days,
+            last_turn_end[assignee],
+            assignee,
+            current_day,
+            turn_end,
+        );
+        last_turn_end[assignee] = Some(turn_end);
+        trace!("Updated load: {:?}", load);
+        current_day = turn_end;
+        if !options.is_escalation_only(&people[assignee]) {
+            next = (assignee + 1) % people.len();
+        }
+    }
+
+    Ok(Schedule {
+        people,
+        turns,
+        algo: Some(Algo::OrderedBalanced {
+            order_source,
+            min_turn_days,
+            max_turn_days,
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::person;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_strict_order_with_balancing_lengths() {
+        let people: Vec<Person> = ["carol", "alice", "bob"]
+            .iter()
+            .map(|id| person(id))
+            .collect();
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        // Bob comes from a heavy previous schedule.
+        let options = Options {
+            initial_load: Some(HashMap::from([("bob".to_string(), TimeDelta::days(8))])),
+            ..Default::default()
+        };
+
+        let order = OrderSource::Ids(vec!["carol".into(), "alice".into(), "bob".into()]);
+        let schedule = schedule(people, start, end, order, 1, 9, &options).unwrap();
+        for (i, turn) in schedule.turns.iter().enumerate() {
+            assert_eq!(turn.person, i % 3);
+        }
+        let lengths: Vec<i64> = schedule
+            .turns
+            .iter()
+            .map(|t| (t.end - t.start).num_days())
+            .collect();
+        assert!(lengths.iter().any(|&l| l != lengths[0]));
+
+        let mut totals = [0, 0, 8];
+        for turn in &schedule.turns {
+            totals[turn.person] += (turn.end - turn.start).num_days();
+        }
+        let spread = totals.iter().max().unwrap() - totals.iter().min().unwrap();
+        assert!(spread <= 1, "{:?}", totals);
+    }
+}
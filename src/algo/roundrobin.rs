@@ -1,7 +1,10 @@
+use crate::algo::balanced::is_ooo_for_turn;
+use crate::algo::{Options, push_turn};
+use crate::config::Algo;
 use crate::input::Person;
 use crate::output::{Assignment, Schedule};
-use chrono::{Days, NaiveDate, TimeDelta};
-use std::collections::HashMap;
+use chrono::NaiveDate;
+use std::collections::VecDeque;
 
 use crate::output::ScheduleError;
 
@@ -10,14 +13,15 @@ pub fn schedule(
     start: NaiveDate,
     end: NaiveDate,
     turn_length_days: u8,
-    initial_load: Option<HashMap<String, TimeDelta>>,
+    start_person: Option<&str>,
+    options: &Options,
 ) -> Result<Schedule, ScheduleError> {
     let mut turns = vec![];
 
     let mut current_day = start;
     let mut assignee: usize = 0;
 
-    if let Some(il) = initial_load
+    if let Some(il) = &options.initial_load
         && !il.is_empty() {
             // Find the person who worked the most in the previous schedule
             let last_on_call = il.iter().max_by_key(|(_, v)| *v).map(|(k, _)| k);
@@ -27,41 +31,140 @@ pub fn schedule(
                 }
         }
 
+    let mut last_turn_end: Vec<Option<NaiveDate>> = vec![None; people.len()];
+    let mut consecutive_days: Vec<i64> = vec![0; people.len()];
+    let mut recent = VecDeque::new();
+    options.record_previous_turns(
+        &people,
+        &mut last_turn_end,
+        &mut consecutive_days,
+        &mut recent,
+    );
+    // The rotation goes on from whoever was on call last, if known.
+    if let Some(&last) = recent.back() {
+        assignee = (last + 1) % people.len();
+    }
+
+    // A configured start overrides the previous load.
+    if let Some(pos) = start_person.and_then(|id| people.iter().position(|p| p.id == id)) {
+        assignee = pos;
+    }
+
+    // Only needed to pick among the escalation-only people.
+    let mut load = options.initial_load(&people);
+
     while current_day < end {
-        let mut candidate = assignee;
-        while people[candidate].ooo.contains(&current_day) {
-            candidate = (candidate + 1) % people.len();
-            if candidate == assignee {
-                return Err(ScheduleError::NoOneAvailable(current_day));
-            }
+        current_day = options.skip_uncovered(current_day);
+        if current_day >= end {
+            break;
+        }
+        options.check_turn_count(turns.len())?;
+        if let Some(turn) = options.fixed_turn(&people, current_day, end) {
+            load[turn.person] += options.turn_load(turn.start, turn.end);
+            options.record_consecutive_days(
+                &mut consecutive_days,
+                last_turn_end[turn.person],
+                turn.person,
+                turn.start,
+                turn.end,
+            );
+            last_turn_end[turn.person] = Some(turn.end);
+            current_day = turn.end;
+            push_turn(&mut turns, turn);
+            continue;
         }
-        assignee = candidate;
         let start = current_day;
-        let last_day = current_day
-            .checked_add_days(Days::new(turn_length_days.into()))
-            .unwrap();
-        // check if the candidate is available for the whole turn
+        // People can have turns of their own length.
+        let turn_end = |i: usize| {
+            let length = people[i].turn_length_days.unwrap_or(turn_length_days);
+            options.align_turn_end(
+                current_day,
+                options.fixed_turn_end(current_day, end, length),
+            )
+        };
+        let can_take = |&i: &usize| {
+            options.is_rested(last_turn_end[i], current_day)
+                && options.is_within_consecutive_days(
+                    consecutive_days[i],
+                    last_turn_end[i],
+                    current_day,
+                    turn_end(i),
+                )
+        };
+        // Whoever can cover the whole turn, so that an OOO day doesn't cut
+        // it short, or else whoever can at least start it.
+        let pick = |candidates: &[usize]| {
+            let whole_turn = candidates
+                .iter()
+                .find(|&&i| !is_ooo_for_turn(&people[i], start, turn_end(i)));
+            let first_day = || {
+                candidates
+                    .iter()
+                    .find(|&&i| !people[i].ooo.contains(&start))
+            };
+            whole_turn.or_else(first_day).copied()
+        };
+        let rotation: Vec<usize> = (0..people.len())
+            .map(|offset| (assignee + offset) % people.len())
+            .filter(|&i| !options.is_escalation_only(&people[i]))
+            .filter(can_take)
+            .collect();
+        // The escalation-only people only when no regular can take the turn,
+        // not even in part.
+        let fallback = || {
+            let fallback = options.escalation_fallback(&people, &load);
+            pick(&fallback.into_iter().filter(can_take).collect::<Vec<_>>())
+        };
+        let Some(candidate) = pick(&rotation).or_else(fallback) else {
+            return Err(ScheduleError::NoOneAvailable(current_day));
+        };
+        // Otherwise, the turn ends at the candidate's first OOO day.
+        let last_day = turn_end(candidate);
         while current_day < last_day
             && current_day < end
             && !people[candidate].ooo.contains(&current_day)
         {
             current_day = current_day.succ_opt().unwrap();
         }
-        turns.push(Assignment {
-            person: candidate,
+        push_turn(
+            &mut turns,
+            Assignment {
+                person: candidate,
+                start,
+                end: current_day,
+                rotation: 0,
+                co_primary: vec![],
+            },
+        );
+        load[candidate] += options.turn_load(start, current_day);
+        options.record_consecutive_days(
+            &mut consecutive_days,
+            last_turn_end[candidate],
+            candidate,
             start,
-            end: current_day,
-        });
-        assignee = (assignee + 1) % people.len();
+            current_day,
+        );
+        last_turn_end[candidate] = Some(current_day);
+        // Escalation-only people don't take a regular's place in the rotation.
+        if !options.is_escalation_only(&people[candidate]) {
+            assignee = (candidate + 1) % people.len();
+        }
     }
 
-    Ok(Schedule { people, turns })
+    Ok(Schedule {
+        people,
+        turns,
+        algo: Some(Algo::RoundRobin {
+            turn_length_days,
+            start_person: start_person.map(str::to_string),
+        }),
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::input::Person;
+    use crate::input::{Person, person};
     use chrono::NaiveDate;
     use std::collections::{HashMap, HashSet};
 
@@ -69,21 +172,17 @@ mod tests {
     fn test_simple_schedule() {
         let people = vec![
             Person {
-                id: "alice".to_string(),
                 name: "Alice".to_string(),
-                ooo: HashSet::new(),
-                preferences: HashMap::new(),
+                ..person("alice")
             },
             Person {
-                id: "bob".to_string(),
                 name: "Bob".to_string(),
-                ooo: HashSet::new(),
-                preferences: HashMap::new(),
+                ..person("bob")
             },
         ];
         let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
         let end = NaiveDate::from_ymd_opt(2025, 1, 5).unwrap();
-        let schedule = schedule(people, start, end, 2, None).unwrap();
+        let schedule = schedule(people, start, end, 2, None, &Options::default()).unwrap();
         assert_eq!(schedule.turns.len(), 2);
         assert_eq!(schedule.turns[0].person, 0);
         assert_eq!(schedule.turns[1].person, 1);
@@ -95,21 +194,18 @@ mod tests {
         ooo.insert(NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
         let people = vec![
             Person {
-                id: "alice".to_string(),
                 name: "Alice".to_string(),
-                ooo: ooo,
-                preferences: HashMap::new(),
+                ooo,
+                ..person("alice")
             },
             Person {
-                id: "bob".to_string(),
                 name: "Bob".to_string(),
-                ooo: HashSet::new(),
-                preferences: HashMap::new(),
+                ..person("bob")
             },
         ];
         let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
         let end = NaiveDate::from_ymd_opt(2025, 1, 5).unwrap();
-        let schedule = schedule(people, start, end, 2, None).unwrap();
+        let schedule = schedule(people, start, end, 2, None, &Options::default()).unwrap();
         assert_eq!(schedule.turns.len(), 2);
         assert_eq!(schedule.turns[0].person, 1); // Bob starts because Alice is OOO
         assert_eq!(schedule.turns[1].person, 0);
@@ -121,21 +217,176 @@ mod tests {
         ooo.insert(NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
         let people = vec![
             Person {
-                id: "alice".to_string(),
                 name: "Alice".to_string(),
                 ooo: ooo.clone(),
-                preferences: HashMap::new(),
+                ..person("alice")
             },
             Person {
-                id: "bob".to_string(),
                 name: "Bob".to_string(),
                 ooo: ooo.clone(),
-                preferences: HashMap::new(),
+                ..person("bob")
             },
         ];
         let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
         let end = NaiveDate::from_ymd_opt(2025, 1, 5).unwrap();
-        let result = schedule(people, start, end, 2, None);
+        let result = schedule(people, start, end, 2, None, &Options::default());
         assert!(matches!(result, Err(ScheduleError::NoOneAvailable(_))));
     }
+
+    #[test]
+    fn test_max_turns() {
+        let people = vec![
+            Person {
+                name: "Alice".to_string(),
+                ..person("alice")
+            },
+            Person {
+                name: "Bob".to_string(),
+                ..person("bob")
+            },
+        ];
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2075, 1, 1).unwrap();
+        let options = Options {
+            max_turns: Some(1000),
+            ..Default::default()
+        };
+        let result = schedule(people, start, end, 1, None, &options);
+        assert!(matches!(result, Err(ScheduleError::TooManyTurns(1000))));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Schedule exceeds the maximum of 1000 turns; check `from`/`to` and the turn length"
+        );
+    }
+
+    #[test]
+    fn test_escalation_only_keeps_rotation() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob", "senior"]
+            .iter()
+            .map(|id| Person {
+                ooo: if *id == "bob" {
+                    HashSet::from([day(3)])
+                } else if *id == "alice" {
+                    HashSet::from([day(3), day(5)])
+                } else {
+                    HashSet::new()
+                },
+                ..person(id)
+            })
+            .collect();
+        let options = Options {
+            escalation_only: HashSet::from(["senior".to_string()]),
+            ..Default::default()
+        };
+
+        let schedule = schedule(people, day(1), day(7), 1, None, &options).unwrap();
+        let assignees: Vec<usize> = schedule.turns.iter().map(|t| t.person).collect();
+        assert_eq!(assignees, vec![0, 1, 2, 0, 1, 0]);
+    }
+
+    #[test]
+    fn test_escalation_only_after_partial_turns() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people = vec![
+            Person {
+                ooo: HashSet::from([day(3)]),
+                ..person("alice")
+            },
+            person("senior"),
+        ];
+        let options = Options {
+            escalation_only: HashSet::from(["senior".to_string()]),
+            ..Default::default()
+        };
+
+        // Alice can only take the first two days of the turn, but that beats
+        // calling the senior, who could take all of it.
+        let schedule = schedule(people, day(1), day(4), 3, None, &options).unwrap();
+        let turns: Vec<_> = schedule
+            .turns
+            .iter()
+            .map(|t| (t.person, t.start, t.end))
+            .collect();
+        assert_eq!(turns, vec![(0, day(1), day(3)), (1, day(3), day(4))]);
+    }
+
+    #[test]
+    fn test_mid_turn_ooo_passes_the_turn_on() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob", "carol"]
+            .iter()
+            .map(|id| Person {
+                ooo: if *id == "alice" {
+                    HashSet::from([day(2)])
+                } else {
+                    HashSet::new()
+                },
+                ..person(id)
+            })
+            .collect();
+
+        // Alice is OOO on the second day of her turn, so Bob takes it whole
+        // instead of Alice covering the 1st only.
+        let schedule = schedule(people, day(1), day(16), 5, None, &Options::default()).unwrap();
+        let turns: Vec<_> = schedule
+            .turns
+            .iter()
+            .map(|t| (t.person, t.start, t.end))
+            .collect();
+        assert_eq!(
+            turns,
+            vec![
+                (1, day(1), day(6)),
+                (2, day(6), day(11)),
+                (0, day(11), day(16))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_per_person_turn_length() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = [("alice", Some(2)), ("bob", None)]
+            .into_iter()
+            .map(|(id, turn_length_days)| Person {
+                turn_length_days,
+                ..person(id)
+            })
+            .collect();
+
+        let schedule = schedule(people, day(1), day(13), 4, None, &Options::default()).unwrap();
+        let turns: Vec<_> = schedule
+            .turns
+            .iter()
+            .map(|t| (t.person, t.start, t.end))
+            .collect();
+        assert_eq!(
+            turns,
+            vec![
+                (0, day(1), day(3)),
+                (1, day(3), day(7)),
+                (0, day(7), day(9)),
+                (1, day(9), day(13))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_start_person() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob", "carol"].into_iter().map(person).collect();
+        // Without a start, Alice would follow Carol, who worked the most.
+        let options = Options {
+            initial_load: Some(HashMap::from([(
+                "carol".to_string(),
+                chrono::TimeDelta::days(7),
+            )])),
+            ..Default::default()
+        };
+
+        let schedule = schedule(people, day(1), day(7), 2, Some("bob"), &options).unwrap();
+        let assignees: Vec<usize> = schedule.turns.iter().map(|t| t.person).collect();
+        assert_eq!(assignees, vec![1, 2, 0]);
+    }
 }
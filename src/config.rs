@@ -1,9 +1,22 @@
-use chrono::NaiveDate;
+use chrono::{Datelike, NaiveDate, TimeDelta, Weekday};
+use log::warn;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+fn format_errors(errors: &[ConfigError]) -> String {
+    errors
+        .iter()
+        .map(|e| format!("\n  - {}", e))
+        .collect()
+}
+
+fn format_dates(dates: &[NaiveDate]) -> String {
+    let dates: Vec<String> = dates.iter().map(NaiveDate::to_string).collect();
+    dates.join(", ")
+}
+
 #[derive(Error, Debug)]
 pub enum ConfigError {
     #[error("Invalid config file path: {0}")]
@@ -12,40 +25,366 @@ pub enum ConfigError {
     ReadFile(#[from] std::io::Error),
     #[error("Failed to parse config file: {0}")]
     Parse(#[from] serde_yaml::Error),
+    #[error("Failed to parse config file: {0}")]
+    ParseToml(#[from] toml::de::Error),
+    #[error("Failed to read availability file {path:?}: {source}")]
+    ReadAvailabilityFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Failed to parse availability file {path:?}: {source}")]
+    ParseAvailabilityFile {
+        path: PathBuf,
+        source: serde_yaml::Error,
+    },
     #[error("Person name cannot be empty")]
     EmptyPersonName,
+    #[error("There must be at least one person")]
+    NoPeople,
+    #[error("The Greedy and Balanced algorithms need at least two people (or teams) to take turns, as no one takes two turns in a row")]
+    NotEnoughPeople,
     #[error("Invalid date range: `from` date must be before `to` date")]
     InvalidDateRange,
     #[error("Turn length in days must be positive")]
     InvalidTurnLength,
     #[error("min_turn_days must be less than or equal to max_turn_days")]
     InvalidTurnLengthBounds,
+    #[error("Team name cannot be empty")]
+    EmptyTeamName,
+    #[error("Team {team} has no members")]
+    EmptyTeam { team: String },
+    #[error("Team {team} refers to unknown person {person}")]
+    UnknownTeamMember { team: String, person: String },
+    #[error("Group {group} refers to unknown id {id}")]
+    UnknownGroupMember { group: String, id: String },
+    #[error("Person ids {first:?} and {second:?} are the same once normalized")]
+    DuplicatePersonId { first: String, second: String },
+    #[error("Weight of day {0} must be a non-negative number")]
+    InvalidDayWeight(NaiveDate),
+    #[error("Partial Ooo on {date} is invalid for person {person_name}: hours must satisfy `from_hour` < `to_hour` <= 24")]
+    InvalidPartialOoo { person_name: String, date: NaiveDate },
+    #[error("Invalid rotations: there must be at least one")]
+    InvalidRotations,
+    #[error("Invalid cooldown_turns: it must be at least 1")]
+    InvalidCooldown,
+    #[error("Invalid max_consecutive_days: it must be at least 1")]
+    InvalidMaxConsecutiveDays,
+    #[error("Fixed turn refers to unknown id {0}")]
+    UnknownFixedPerson(String),
+    #[error("Invalid fixed turn of {person}: `from` must be before `to`")]
+    InvalidFixedAssignment { person: String },
+    #[error("Fixed turn of {person} from {from} to {to} falls on their OOO days: {}", format_dates(.dates))]
+    FixedAssignmentDuringOoo {
+        person: String,
+        from: NaiveDate,
+        to: NaiveDate,
+        dates: Vec<NaiveDate>,
+    },
+    #[error(
+        "Co-primary turn from {from} to {to} must be split between at least two people, with a day or more each"
+    )]
+    InvalidCoPrimaryTurn { from: NaiveDate, to: NaiveDate },
+    #[error("Fixed turns of {first} and {second} overlap on {date}")]
+    OverlappingFixedAssignments {
+        first: String,
+        second: String,
+        date: NaiveDate,
+    },
+    #[error("Date {date} of person {person_name} is outside the schedule range")]
+    DateOutOfRange {
+        person_name: String,
+        date: NaiveDate,
+    },
+    #[error("Invalid no_load entry: only days and periods with `from` before `to` are allowed")]
+    InvalidNoLoadPeriod,
+    #[error("Invalid no_coverage entry: only days and periods with `from` before `to` are allowed")]
+    InvalidNoCoveragePeriod,
+    #[error("Invalid holidays entry: only days and periods with `from` before `to` are allowed")]
+    InvalidHoliday,
+    #[error("holiday_load_factor must be a non-negative number")]
+    InvalidHolidayLoadFactor,
+    #[error("The rotation order cannot be empty")]
+    EmptyOrder,
+    #[error("The rotation order refers to unknown id {0}")]
+    UnknownOrderId(String),
+    #[error("The round robin start_person refers to unknown id {0}")]
+    UnknownStartPerson(String),
+    #[error("Invalid value for environment variable {var}: {value:?}")]
+    InvalidEnvVar { var: String, value: String },
+    #[error("{} problems found:{}", .0.len(), format_errors(.0))]
+    Multiple(Vec<ConfigError>),
     #[error("Ooo period is invalid for person {person_name}: `from` date must be before `to` date")]
     InvalidOooPeriod { person_name: String },
+    #[error("Preference period is invalid for person {person_name}: `from` date must be before `to` date")]
+    InvalidPreferencePeriod { person_name: String },
+    #[error("Load override is invalid for person {person_name}: `from` date must be before `to` date and `weight` must be a positive number")]
+    InvalidLoadOverride { person_name: String },
+    #[error("Ramp is invalid for person {person_name}: `from` date must be before `to` date and `start_weight` must be greater than 0 and at most 1")]
+    InvalidRamp { person_name: String },
+    #[error("Weight is invalid for person {person_name}: it must be a positive number")]
+    InvalidWeight { person_name: String },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Ooo {
     Day(NaiveDate),
     Period { from: NaiveDate, to: NaiveDate },
+    /// Unavailable for part of a day (`from_hour` to `to_hour`, 0-24). With
+    /// whole-day turns the person is still available, but encumbered: the day
+    /// counts as `NotWant` unless an explicit preference says otherwise.
+    Partial { date: NaiveDate, from_hour: u8, to_hour: u8 },
+    /// Unavailable every week on this day (e.g. someone who never works
+    /// Fridays), over the whole schedule.
+    Weekday(Weekday),
+    /// Unavailable every week on these days.
+    Weekdays(Vec<Weekday>),
+    /// Would rather not be on call on this day, but can be as a last resort:
+    /// unlike the other entries, it doesn't block the person.
+    Soft(NaiveDate),
+    /// A `Soft` entry for every day from `from` to `to`, both included.
+    SoftPeriod { from: NaiveDate, to: NaiveDate },
+}
+
+impl Ooo {
+    /// Whether the entry keeps the person from being on call on `date`;
+    /// partial and soft entries don't.
+    pub fn blocks(&self, date: NaiveDate) -> bool {
+        match self {
+            Ooo::Day(day) => *day == date,
+            Ooo::Period { from, to } => *from <= date && date <= *to,
+            Ooo::Weekday(weekday) => date.weekday() == *weekday,
+            Ooo::Weekdays(weekdays) => weekdays.contains(&date.weekday()),
+            Ooo::Partial { .. } | Ooo::Soft(_) | Ooo::SoftPeriod { .. } => false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Preference {
     Want(NaiveDate),
     NotWant(NaiveDate),
+    /// Outranks a plain `Want` of someone else for the same days.
+    StrongWant(NaiveDate),
+    /// Outranks a plain `NotWant`, so it is only overridden as a last resort.
+    StrongNotWant(NaiveDate),
+    /// A `Want` for every day from `from` to `to`, both included.
+    WantPeriod { from: NaiveDate, to: NaiveDate },
+    /// A `NotWant` for every day from `from` to `to`, both included.
+    NotWantPeriod { from: NaiveDate, to: NaiveDate },
+}
+
+impl Preference {
+    /// The days the preference is about.
+    pub fn days(&self) -> Vec<NaiveDate> {
+        match self {
+            Preference::Want(date)
+            | Preference::NotWant(date)
+            | Preference::StrongWant(date)
+            | Preference::StrongNotWant(date) => vec![*date],
+            Preference::WantPeriod { from, to } | Preference::NotWantPeriod { from, to } => {
+                from.iter_days().take_while(|d| d <= to).collect()
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Person {
-    pub(crate) name: String,
-    pub(crate) ooo: Option<Vec<Ooo>>,
-    pub(crate) preferences: Option<Vec<Preference>>,
+    pub name: String,
+    pub ooo: Option<Vec<Ooo>>,
+    pub preferences: Option<Vec<Preference>>,
+    /// Periods in which the person carries a different share of the load
+    /// (e.g. 0.5 while ramping back from leave).
+    #[serde(default)]
+    pub load_overrides: Option<Vec<LoadOverride>>,
+    /// The person's share of the load relative to the others (default 1.0):
+    /// with 0.5, e.g. for a part-timer, they take half as many days.
+    #[serde(default)]
+    pub weight: Option<f64>,
+    /// The most days the person can be on call in the schedule, e.g. a
+    /// contractual maximum.
+    #[serde(default)]
+    pub max_days: Option<u32>,
+    /// The length of the person's turns with the `RoundRobin` algorithm,
+    /// instead of the algorithm's `turn_length_days`.
+    #[serde(default)]
+    pub turn_length_days: Option<u8>,
+    /// Eases a new hire into the rotation: over the period, they are
+    /// expected to carry a share of the load growing from `start_weight` to
+    /// a full one, and don't have to make up for it afterwards.
+    #[serde(default)]
+    pub ramp: Option<Ramp>,
+    /// A YAML file with more `ooo` and `preferences` of this person, so that
+    /// they can maintain their availability themselves. Relative paths are
+    /// resolved against the directory of the config file.
+    #[serde(default)]
+    pub availability_file: Option<PathBuf>,
+    /// Only put the person on call when no one else is available, e.g. for
+    /// senior engineers acting as a safety net.
+    #[serde(default)]
+    pub escalation_only: bool,
+    /// A weekday the person covers whenever possible (e.g. "Raj always takes
+    /// Wednesdays"). Only applies with turns shorter than a week.
+    #[serde(default)]
+    pub owns_weekday: Option<Weekday>,
+    /// Don't put anyone else on call on `owns_weekday`, unless the person is
+    /// OOO, instead of only preferring them.
+    #[serde(default)]
+    pub owns_weekday_required: bool,
+    /// When in the schedule the person would rather be on call. Only breaks
+    /// ties between equally loaded candidates.
+    #[serde(default)]
+    pub position: Option<Position>,
+    /// How to reach the person, passed through to the exports.
+    #[serde(flatten)]
+    pub contact: Contact,
+}
+
+/// Contact details of a person. Scheduling ignores them; exports include
+/// the ones that are set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contact {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    /// Slack handle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slack: Option<String>,
+}
+
+/// The content of a person's `availability_file`.
+#[derive(Debug, Default, Deserialize)]
+struct Availability {
+    #[serde(default)]
+    ooo: Vec<Ooo>,
+    #[serde(default)]
+    preferences: Vec<Preference>,
+}
+
+/// The person's weight from `from` to `to` (both included): with 0.5, they
+/// are expected to take half the load of their peers over that period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadOverride {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub weight: f64,
+}
+
+/// A person's share of the load growing linearly from `start_weight` on
+/// `from` to a full one on `to`. Unlike a [`LoadOverride`], the turns they
+/// didn't take during the ramp are not made up for later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ramp {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    #[serde(default = "default_ramp_start_weight")]
+    pub start_weight: f64,
+}
+
+fn default_ramp_start_weight() -> f64 {
+    0.25
+}
+
+impl Ramp {
+    /// The weight on `date`, between `from` and `to`.
+    fn weight(&self, date: NaiveDate) -> f64 {
+        let progress =
+            (date - self.from).num_days() as f64 / (self.to - self.from).num_days() as f64;
+        self.start_weight + (1.0 - self.start_weight) * progress
+    }
+}
+
+/// A turn agreed in advance, e.g. for a new hire shadowing someone, which the
+/// algorithms keep as is and schedule around.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixedAssignment {
+    /// The id of the person, or of the team when rotating teams.
+    pub person: String,
+    pub from: NaiveDate,
+    /// The day after the turn.
+    pub to: NaiveDate,
+    /// For a part of a co-primary turn, the other people sharing it.
+    #[serde(skip)]
+    pub co_primary: Vec<String>,
+}
+
+/// A turn window split between several people, e.g. two part-timers, who
+/// each cover a part of it in turn. Unlike a backup rotation, only one of
+/// them is on call at a time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoPrimaryTurn {
+    /// The ids of the people, or of the teams when rotating teams, in the
+    /// order they cover the turn.
+    pub people: Vec<String>,
+    pub from: NaiveDate,
+    /// The day after the turn.
+    pub to: NaiveDate,
+}
+
+impl CoPrimaryTurn {
+    /// The part each person covers, as fixed turns. The days are split
+    /// evenly, the first people taking a day more when they don't divide.
+    pub fn parts(&self) -> Vec<FixedAssignment> {
+        let days = (self.to - self.from).num_days().max(0);
+        let n = self.people.len().max(1) as i64;
+        let mut from = self.from;
+        let mut parts = vec![];
+        for (i, person) in self.people.iter().enumerate() {
+            let length = days / n + i64::from((i as i64) < days % n);
+            let to = from + TimeDelta::days(length);
+            parts.push(FixedAssignment {
+                person: person.clone(),
+                from,
+                to,
+                co_primary: self
+                    .people
+                    .iter()
+                    .filter(|p| *p != person)
+                    .cloned()
+                    .collect(),
+            });
+            from = to;
+        }
+        parts
+    }
+}
+
+/// A group of people rotated as a single unit: the schedule assigns turns to
+/// teams, and each team decides internally who covers its turns.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Team {
+    pub name: String,
+    pub members: Vec<String>,
+}
+
+/// When in the schedule a person would rather be on call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Position {
+    /// In the first half of the schedule, to get it over with.
+    Early,
+    /// In the second half of the schedule.
+    Late,
+}
+
+/// The fixed rotation order of [`Algo::OrderedBalanced`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum OrderSource {
+    /// Everyone, sorted by id.
+    Alphabetical,
+    /// The given ids, in order; people not listed are not scheduled.
+    Ids(Vec<String>),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Algo {
-    RoundRobin { turn_length_days: u8 },
+    RoundRobin {
+        turn_length_days: u8,
+        /// Who takes the first turn, whatever the load of a previous
+        /// schedule.
+        #[serde(default)]
+        start_person: Option<String>,
+    },
     Greedy {
         turn_length_days: u8,
         #[serde(default)]
@@ -54,80 +393,865 @@ pub enum Algo {
     Balanced {
         min_turn_days: u8,
         max_turn_days: u8,
+        #[serde(default)]
+        preference_weight: Option<u8>,
+        /// Also balance the weekend days, which are the least popular.
+        #[serde(default)]
+        balance_weekends: bool,
+    },
+    /// Round robin in a fixed order, with turn lengths chosen like `Balanced`
+    /// to keep the load even.
+    OrderedBalanced {
+        order_source: OrderSource,
+        min_turn_days: u8,
+        max_turn_days: u8,
     },
 }
 
+impl Algo {
+    /// The shortest turn the algorithm may assign.
+    pub fn min_turn_days(&self) -> u8 {
+        match self {
+            Algo::RoundRobin {
+                turn_length_days, ..
+            }
+            | Algo::Greedy {
+                turn_length_days, ..
+            } => *turn_length_days,
+            Algo::Balanced { min_turn_days, .. } | Algo::OrderedBalanced { min_turn_days, .. } => {
+                *min_turn_days
+            }
+        }
+    }
+
+    /// The longest turn the algorithm may assign.
+    pub fn max_turn_days(&self) -> u8 {
+        match self {
+            Algo::RoundRobin {
+                turn_length_days, ..
+            }
+            | Algo::Greedy {
+                turn_length_days, ..
+            } => *turn_length_days,
+            Algo::Balanced { max_turn_days, .. } | Algo::OrderedBalanced { max_turn_days, .. } => {
+                *max_turn_days
+            }
+        }
+    }
+
+    /// The name of the algorithm in prose.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algo::RoundRobin { .. } => "round robin",
+            Algo::Greedy { .. } => "greedy",
+            Algo::Balanced { .. } => "balanced",
+            Algo::OrderedBalanced { .. } => "ordered balanced",
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Schedule {
-    pub(crate) from: NaiveDate,
-    pub(crate) to: NaiveDate,
-    pub(crate) algo: Algo,
+    pub from: NaiveDate,
+    /// The end of the schedule, excluded unless `to_inclusive` is set.
+    pub to: NaiveDate,
+    /// Whether `to` is the last day of the schedule rather than the day
+    /// after it.
+    #[serde(default)]
+    pub to_inclusive: bool,
+    pub algo: Algo,
+    /// How much specific days count toward load (e.g. 2.0 for a deploy
+    /// week); unlisted days count 1.0.
+    #[serde(default)]
+    pub day_weights: Option<HashMap<NaiveDate, f64>>,
+    /// Days that still need coverage but don't count toward load (e.g. a
+    /// company shutdown week).
+    #[serde(default)]
+    pub no_load: Option<Vec<Ooo>>,
+    /// Days on which on-call is suspended (e.g. company holidays): nobody
+    /// covers them, and turns stop before them and resume after.
+    #[serde(default)]
+    pub no_coverage: Option<Vec<Ooo>>,
+    /// Public holidays, which count `holiday_load_factor` times as much toward
+    /// load for whoever covers them.
+    #[serde(default)]
+    pub holidays: Option<Vec<Ooo>>,
+    #[serde(default = "default_holiday_load_factor")]
+    pub holiday_load_factor: f64,
+    /// Seed for a reproducible shuffle of the initial rotation order.
+    #[serde(default)]
+    pub shuffle_seed: Option<u64>,
+    /// Break ties between equally good candidates of the Greedy and Balanced
+    /// algorithms randomly, with a new seed on every run unless one is given
+    /// with `--seed`.
+    #[serde(default)]
+    pub random_tie_break: bool,
+    /// Cut turns that would cross the 1st of a month, so that each turn falls
+    /// within a single month.
+    #[serde(default)]
+    pub align_to_month_boundaries: bool,
+    /// How many days in advance people must know about their turns; turns
+    /// starting sooner than that after today are reported.
+    #[serde(default)]
+    pub min_notice_days: Option<u32>,
+    /// How many days someone is off call at least between two of their
+    /// turns.
+    #[serde(default)]
+    pub min_rest_days: Option<u8>,
+    /// How many people are on call in parallel, e.g. 2 for a primary and a
+    /// backup, never the same person.
+    #[serde(default = "default_rotations")]
+    pub rotations: u8,
+    /// How many turns someone sits out after theirs, when the team is large
+    /// enough.
+    #[serde(default = "default_cooldown_turns")]
+    pub cooldown_turns: u8,
+    /// The most days in a row anyone is on call, over one turn or several
+    /// back to back.
+    #[serde(default)]
+    pub max_consecutive_days: Option<u8>,
+    /// Turns agreed in advance, kept as they are in the primary rotation.
+    #[serde(default)]
+    pub fixed: Option<Vec<FixedAssignment>>,
+    /// Turns split between several people, kept like the `fixed` ones.
+    #[serde(default)]
+    pub co_primary: Option<Vec<CoPrimaryTurn>>,
+}
+
+fn default_rotations() -> u8 {
+    1
+}
+
+fn default_cooldown_turns() -> u8 {
+    1
+}
+
+impl Schedule {
+    /// The `fixed` turns, followed by the parts of the `co_primary` ones.
+    pub fn fixed_turns(&self) -> Vec<FixedAssignment> {
+        let mut turns = self.fixed.clone().unwrap_or_default();
+        turns.extend(
+            self.co_primary
+                .iter()
+                .flatten()
+                .flat_map(CoPrimaryTurn::parts),
+        );
+        turns
+    }
+
+    /// The first day after the schedule.
+    pub fn end(&self) -> NaiveDate {
+        if self.to_inclusive {
+            self.to.succ_opt().unwrap()
+        } else {
+            self.to
+        }
+    }
+}
+
+fn default_holiday_load_factor() -> f64 {
+    2.0
+}
+
+/// The days of `!Day` and `!Period` entries; partial, recurring and soft
+/// entries are ignored.
+fn whole_days(entries: &[Ooo]) -> Vec<NaiveDate> {
+    let mut days = vec![];
+    for entry in entries {
+        match entry {
+            Ooo::Day(date) => days.push(*date),
+            Ooo::Period { from, to } => {
+                let mut current = *from;
+                while current <= *to {
+                    days.push(current);
+                    current = current.succ_opt().unwrap();
+                }
+            }
+            Ooo::Partial { .. }
+            | Ooo::Weekday(_)
+            | Ooo::Weekdays(_)
+            | Ooo::Soft(_)
+            | Ooo::SoftPeriod { .. } => {}
+        }
+    }
+    days
+}
+
+impl Schedule {
+    /// How much each day counts toward load: `day_weights`, multiplied by
+    /// `holiday_load_factor` on holidays, with the `no_load` days counting
+    /// zero.
+    pub fn load_weights(&self) -> HashMap<NaiveDate, f64> {
+        let mut weights = self.day_weights.clone().unwrap_or_default();
+        for date in whole_days(self.holidays.as_deref().unwrap_or_default()) {
+            *weights.entry(date).or_insert(1.0) *= self.holiday_load_factor;
+        }
+        for date in whole_days(self.no_load.as_deref().unwrap_or_default()) {
+            weights.insert(date, 0.0);
+        }
+        weights
+    }
+
+    /// The `no_coverage` days, when nobody is on call.
+    pub fn uncovered_days(&self) -> HashSet<NaiveDate> {
+        whole_days(self.no_coverage.as_deref().unwrap_or_default())
+            .into_iter()
+            .collect()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
-    pub(crate) people: HashMap<String, Person>,
-    pub(crate) schedule: Schedule,
+    pub people: HashMap<String, Person>,
+    #[serde(default)]
+    pub teams: Option<HashMap<String, Team>>,
+    /// Ids of the people (or teams, when rotating teams) in each group, e.g.
+    /// sub-teams, to compare how much each group contributes.
+    #[serde(default)]
+    pub groups: Option<HashMap<String, Vec<String>>>,
+    pub schedule: Schedule,
+}
+
+/// Trims and lowercases a person id, so that ids coming from different
+/// sources (config keys, previous schedules) compare equal.
+pub fn normalize_id(id: &str) -> String {
+    id.trim().to_lowercase()
 }
 
 impl Config {
-    fn validate(&self) -> Result<(), ConfigError> {
-        if self.schedule.from >= self.schedule.to {
-            return Err(ConfigError::InvalidDateRange);
+    /// Normalizes the keys of the people map and the team members with
+    /// [`normalize_id`], warning about every id that changes.
+    pub fn normalize_ids(&mut self) -> Result<(), ConfigError> {
+        let mut people = HashMap::new();
+        for (id, person) in self.people.drain() {
+            let normalized = normalize_id(&id);
+            if normalized != id {
+                warn!("Person id {:?} normalized to {:?}", id, normalized);
+            }
+            if people.contains_key(&normalized) {
+                return Err(ConfigError::DuplicatePersonId {
+                    first: id,
+                    second: normalized,
+                });
+            }
+            people.insert(normalized, person);
+        }
+        self.people = people;
+
+        if let Some(teams) = &mut self.teams {
+            for team in teams.values_mut() {
+                for member in &mut team.members {
+                    *member = normalize_id(member);
+                }
+            }
+        }
+        if let Some(groups) = &mut self.groups {
+            for members in groups.values_mut() {
+                for member in members {
+                    *member = normalize_id(member);
+                }
+            }
+        }
+        for fixed in self.schedule.fixed.iter_mut().flatten() {
+            fixed.person = normalize_id(&fixed.person);
+        }
+        for turn in self.schedule.co_primary.iter_mut().flatten() {
+            for person in &mut turn.people {
+                *person = normalize_id(person);
+            }
+        }
+        Ok(())
+    }
+
+    /// Merges the `availability_file` of each person into their `ooo` and
+    /// `preferences`. All OOO entries are kept; for days with a preference in
+    /// the config, the one in the file is ignored.
+    fn load_availability_files(&mut self, base_dir: &Path) -> Result<(), ConfigError> {
+        for person in self.people.values_mut() {
+            let Some(file) = &person.availability_file else {
+                continue;
+            };
+            let path = base_dir.join(file);
+            let content = std::fs::read_to_string(&path).map_err(|source| {
+                ConfigError::ReadAvailabilityFile {
+                    path: path.clone(),
+                    source,
+                }
+            })?;
+            let availability: Availability = serde_yaml::from_str(&content)
+                .map_err(|source| ConfigError::ParseAvailabilityFile { path, source })?;
+
+            person.ooo.get_or_insert_default().extend(availability.ooo);
+            let preferences = person.preferences.get_or_insert_default();
+            let configured: Vec<NaiveDate> =
+                preferences.iter().flat_map(Preference::days).collect();
+            for preference in availability.preferences {
+                if let Some(date) = preference
+                    .days()
+                    .into_iter()
+                    .find(|d| configured.contains(d))
+                {
+                    warn!(
+                        "{}: ignoring the preference for {} in {:?}, the config already has one",
+                        person.name, date, file
+                    );
+                    continue;
+                }
+                preferences.push(preference);
+            }
+        }
+        Ok(())
+    }
+
+    /// Lists people without any OOO or preference entry, sorted by id. Such
+    /// people are scheduled as always available, but missing data is more
+    /// often a failed data load than genuine full availability.
+    pub fn availability_diagnostics(&self) -> Vec<String> {
+        let mut ids: Vec<&String> = self
+            .people
+            .iter()
+            .filter(|(_, p)| {
+                p.ooo.as_ref().is_none_or(|o| o.is_empty())
+                    && p.preferences.as_ref().is_none_or(|p| p.is_empty())
+            })
+            .map(|(id, _)| id)
+            .collect();
+        ids.sort();
+        ids.into_iter()
+            .map(|id| format!("{}: no availability data (assuming available)", id))
+            .collect()
+    }
+
+    /// The people sorted by id, so that they are always in the same order
+    /// (and ties between them resolved the same way), unlike in `people`.
+    pub fn people_by_id(&self) -> Vec<(&String, &Person)> {
+        let mut people: Vec<(&String, &Person)> = self.people.iter().collect();
+        people.sort_by_key(|(id, _)| *id);
+        people
+    }
+
+    /// A one-line description of the config, to confirm it is valid.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} people, schedule from {} to {}, algo {}",
+            self.people.len(),
+            self.schedule.from,
+            self.schedule.end(),
+            self.schedule.algo.name()
+        )
+    }
+
+    /// The people who own a weekday: their id, the weekday and whether the
+    /// ownership is required.
+    pub fn weekday_owners(&self) -> Vec<(String, Weekday, bool)> {
+        self.people
+            .iter()
+            .filter_map(|(id, person)| {
+                let weekday = person.owns_weekday?;
+                Some((id.clone(), weekday, person.owns_weekday_required))
+            })
+            .collect()
+    }
+
+    /// The `position` preference of the people who have one, by person id.
+    pub fn positions(&self) -> HashMap<String, Position> {
+        self.people
+            .iter()
+            .filter_map(|(id, person)| Some((id.clone(), person.position?)))
+            .collect()
+    }
+
+    /// The ids of the people who are `escalation_only`.
+    pub fn escalation_only(&self) -> HashSet<String> {
+        self.people
+            .iter()
+            .filter(|(_, person)| person.escalation_only)
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// The `load_overrides` of every person expanded to single days, by
+    /// person id. When overrides overlap, the last one listed wins.
+    pub fn person_weights(&self) -> HashMap<String, HashMap<NaiveDate, f64>> {
+        let mut weights = HashMap::new();
+        for (id, person) in &self.people {
+            let Some(overrides) = &person.load_overrides else {
+                continue;
+            };
+            let mut days = HashMap::new();
+            for o in overrides {
+                let mut current = o.from;
+                while current <= o.to {
+                    days.insert(current, o.weight);
+                    current = current.succ_opt().unwrap();
+                }
+            }
+            weights.insert(id.clone(), days);
+        }
+        weights
+    }
+
+    /// The load waived for each person with a `ramp`, by person id: from
+    /// each day of the ramp on, the part of a fair share of that day (one
+    /// day split among everyone) they weren't expected to carry. Keyed by the
+    /// day the running total changes.
+    pub fn ramp_credits(&self) -> HashMap<String, BTreeMap<NaiveDate, TimeDelta>> {
+        let fair_share = 86_400.0 / self.people.len().max(1) as f64;
+        let mut credits = HashMap::new();
+        for (id, person) in &self.people {
+            let Some(ramp) = &person.ramp else {
+                continue;
+            };
+            let mut totals = BTreeMap::new();
+            let mut seconds = 0.0;
+            let mut current = ramp.from;
+            while current <= ramp.to {
+                seconds += fair_share * (1.0 - ramp.weight(current));
+                totals.insert(current, TimeDelta::seconds(seconds.round() as i64));
+                current = current.succ_opt().unwrap();
+            }
+            credits.insert(id.clone(), totals);
+        }
+        credits
+    }
+
+    /// Overrides the schedule range and algorithm from the `TURNS_FROM`,
+    /// `TURNS_TO` and `TURNS_ALGO` variables found by `lookup`, then
+    /// validates the result again. As with [`Config::override_range`], OOO and
+    /// preferences may fall outside the new range. Dates are `YYYY-MM-DD`; the
+    /// algorithm uses the same YAML syntax as the config file (e.g.
+    /// `!RoundRobin { turn_length_days: 7 }`).
+    pub fn apply_env(
+        &mut self,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<(), ConfigError> {
+        let invalid = |var: &str, value: String| ConfigError::InvalidEnvVar {
+            var: var.to_string(),
+            value,
+        };
+        if let Some(value) = lookup("TURNS_FROM") {
+            self.schedule.from = value.parse().map_err(|_| invalid("TURNS_FROM", value))?;
+        }
+        if let Some(value) = lookup("TURNS_TO") {
+            self.schedule.to = value.parse().map_err(|_| invalid("TURNS_TO", value))?;
+        }
+        if let Some(value) = lookup("TURNS_ALGO") {
+            self.schedule.algo =
+                serde_yaml::from_str(&value).map_err(|_| invalid("TURNS_ALGO", value))?;
+        }
+        Self::report(self.validation_errors(false))
+    }
+
+    /// Overrides the start and end of the schedule when given, e.g. to reuse a
+    /// config for another month, then validates the result again. As with a
+    /// rolling window, OOO and preferences may fall outside the new range.
+    pub fn override_range(
+        &mut self,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<(), ConfigError> {
+        if let Some(from) = from {
+            self.schedule.from = from;
+        }
+        if let Some(to) = to {
+            self.schedule.to = to;
+        }
+        Self::report(self.validation_errors(false))
+    }
+
+    /// Replaces the schedule range, e.g. with a rolling window, then validates
+    /// the result again. `to` is excluded regardless of `to_inclusive`.
+    pub fn set_range(&mut self, from: NaiveDate, to: NaiveDate) -> Result<(), ConfigError> {
+        self.schedule.from = from;
+        self.schedule.to = to;
+        self.schedule.to_inclusive = false;
+        // A window is expected to leave out some of the OOO and preferences.
+        Self::report(self.validation_errors(false))
+    }
+
+    /// Validates the config, reporting every problem at once: a single
+    /// problem is returned as is, several as [`ConfigError::Multiple`].
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        Self::report(self.validation_errors(true))
+    }
+
+    /// Validates the config like [`Config::validate`], except for the dates
+    /// of OOO entries and preferences, which may fall outside the schedule
+    /// once availability files are merged in.
+    pub fn validate_merged(&self) -> Result<(), ConfigError> {
+        Self::report(self.validation_errors(false))
+    }
+
+    fn report(mut errors: Vec<ConfigError>) -> Result<(), ConfigError> {
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            _ => Err(ConfigError::Multiple(errors)),
+        }
+    }
+
+    /// The problems of the config. With `check_dates`, the dates of OOO
+    /// entries and preferences must fall within the schedule, as they are
+    /// otherwise silently ignored (typically a typo in the year).
+    fn validation_errors(&self, check_dates: bool) -> Vec<ConfigError> {
+        let mut errors = vec![];
+
+        if self.schedule.from >= self.schedule.end() {
+            errors.push(ConfigError::InvalidDateRange);
+        }
+
+        // With teams, the rotation is between teams.
+        let rotation_size = match &self.teams {
+            Some(teams) => teams.len(),
+            None => self.people.len(),
+        };
+        if self.people.is_empty() {
+            errors.push(ConfigError::NoPeople);
+        } else if rotation_size < 2
+            && matches!(
+                self.schedule.algo,
+                Algo::Greedy { .. } | Algo::Balanced { .. }
+            )
+        {
+            errors.push(ConfigError::NotEnoughPeople);
         }
 
         match self.schedule.algo {
-            Algo::RoundRobin { turn_length_days } | Algo::Greedy { turn_length_days, .. } => {
+            Algo::RoundRobin {
+                turn_length_days, ..
+            }
+            | Algo::Greedy {
+                turn_length_days, ..
+            } => {
                 if turn_length_days == 0 {
-                    return Err(ConfigError::InvalidTurnLength);
+                    errors.push(ConfigError::InvalidTurnLength);
                 }
             }
             Algo::Balanced {
                 min_turn_days,
                 max_turn_days,
+                ..
+            }
+            | Algo::OrderedBalanced {
+                min_turn_days,
+                max_turn_days,
+                ..
             } => {
                 if min_turn_days == 0 || max_turn_days == 0 {
-                    return Err(ConfigError::InvalidTurnLength);
+                    errors.push(ConfigError::InvalidTurnLength);
+                } else if min_turn_days > max_turn_days {
+                    errors.push(ConfigError::InvalidTurnLengthBounds);
+                }
+            }
+        }
+
+        if let Algo::OrderedBalanced {
+            order_source: OrderSource::Ids(ids),
+            ..
+        } = &self.schedule.algo
+        {
+            if ids.is_empty() {
+                errors.push(ConfigError::EmptyOrder);
+            }
+            // With teams, the rotation is between teams.
+            for id in ids {
+                let known = match &self.teams {
+                    Some(teams) => teams.contains_key(id),
+                    None => self.people.contains_key(id),
+                };
+                if !known {
+                    errors.push(ConfigError::UnknownOrderId(id.clone()));
                 }
-                if min_turn_days > max_turn_days {
-                    return Err(ConfigError::InvalidTurnLengthBounds);
+            }
+        }
+
+        if let Algo::RoundRobin {
+            start_person: Some(id),
+            ..
+        } = &self.schedule.algo
+        {
+            // With teams, the rotation is between teams.
+            let known = match &self.teams {
+                Some(teams) => teams.contains_key(id),
+                None => self.people.contains_key(id),
+            };
+            if !known {
+                errors.push(ConfigError::UnknownStartPerson(id.clone()));
+            }
+        }
+
+        if let Some(day_weights) = &self.schedule.day_weights {
+            for (date, weight) in day_weights {
+                if !weight.is_finite() || *weight < 0.0 {
+                    errors.push(ConfigError::InvalidDayWeight(*date));
                 }
             }
         }
 
+        for entry in self.schedule.no_load.iter().flatten() {
+            match entry {
+                Ooo::Day(_) => {}
+                Ooo::Period { from, to } if from < to => {}
+                _ => errors.push(ConfigError::InvalidNoLoadPeriod),
+            }
+        }
+
+        for entry in self.schedule.no_coverage.iter().flatten() {
+            match entry {
+                Ooo::Day(_) => {}
+                Ooo::Period { from, to } if from < to => {}
+                _ => errors.push(ConfigError::InvalidNoCoveragePeriod),
+            }
+        }
+
+        for entry in self.schedule.holidays.iter().flatten() {
+            match entry {
+                Ooo::Day(_) => {}
+                Ooo::Period { from, to } if from < to => {}
+                _ => errors.push(ConfigError::InvalidHoliday),
+            }
+        }
+        let factor = self.schedule.holiday_load_factor;
+        if !factor.is_finite() || factor < 0.0 {
+            errors.push(ConfigError::InvalidHolidayLoadFactor);
+        }
+        if self.schedule.rotations == 0 {
+            errors.push(ConfigError::InvalidRotations);
+        }
+        if self.schedule.cooldown_turns == 0 {
+            errors.push(ConfigError::InvalidCooldown);
+        }
+        if self.schedule.max_consecutive_days == Some(0) {
+            errors.push(ConfigError::InvalidMaxConsecutiveDays);
+        }
+        for turn in self.schedule.co_primary.iter().flatten() {
+            let days = (turn.to - turn.from).num_days();
+            if turn.people.len() < 2 || days < turn.people.len() as i64 {
+                errors.push(ConfigError::InvalidCoPrimaryTurn {
+                    from: turn.from,
+                    to: turn.to,
+                });
+            }
+        }
+        let fixed_turns = self.schedule.fixed_turns();
+        for fixed in &fixed_turns {
+            // With teams, the rotation is between teams.
+            let known = match &self.teams {
+                Some(teams) => teams.contains_key(&fixed.person),
+                None => self.people.contains_key(&fixed.person),
+            };
+            if !known {
+                errors.push(ConfigError::UnknownFixedPerson(fixed.person.clone()));
+            }
+            if fixed.from >= fixed.to {
+                errors.push(ConfigError::InvalidFixedAssignment {
+                    person: fixed.person.clone(),
+                });
+            }
+            // Fixed turns are kept as they are, even on OOO days.
+            let ooo = match (&self.teams, self.people.get(&fixed.person)) {
+                (None, Some(person)) => person.ooo.as_deref().unwrap_or_default(),
+                _ => &[],
+            };
+            let dates: Vec<NaiveDate> = fixed
+                .from
+                .iter_days()
+                .take_while(|date| *date < fixed.to)
+                .filter(|date| ooo.iter().any(|o| o.blocks(*date)))
+                .collect();
+            if !dates.is_empty() {
+                errors.push(ConfigError::FixedAssignmentDuringOoo {
+                    person: fixed.person.clone(),
+                    from: fixed.from,
+                    to: fixed.to,
+                    dates,
+                });
+            }
+        }
+        let mut fixed: Vec<&FixedAssignment> = fixed_turns.iter().collect();
+        fixed.sort_by_key(|f| f.from);
+        // The fixed turn ending last so far.
+        let mut latest: Option<&FixedAssignment> = None;
+        for f in fixed {
+            if let Some(previous) = latest
+                && f.from < previous.to
+            {
+                errors.push(ConfigError::OverlappingFixedAssignments {
+                    first: previous.person.clone(),
+                    second: f.person.clone(),
+                    date: f.from,
+                });
+            }
+            if latest.is_none_or(|previous| f.to > previous.to) {
+                latest = Some(f);
+            }
+        }
+
         for person in self.people.values() {
             if person.name.is_empty() {
-                return Err(ConfigError::EmptyPersonName);
+                errors.push(ConfigError::EmptyPersonName);
             }
             if let Some(ooos) = &person.ooo {
                 for ooo in ooos {
-                    if let Ooo::Period { from, to } = ooo
+                    if let Ooo::Period { from, to } | Ooo::SoftPeriod { from, to } = ooo
                         && from >= to {
-                            return Err(ConfigError::InvalidOooPeriod {
+                            errors.push(ConfigError::InvalidOooPeriod {
                                 person_name: person.name.clone(),
                             });
                         }
+                    if let Ooo::Partial { date, from_hour, to_hour } = ooo
+                        && (from_hour >= to_hour || *to_hour > 24)
+                    {
+                        errors.push(ConfigError::InvalidPartialOoo {
+                            person_name: person.name.clone(),
+                            date: *date,
+                        });
+                    }
+                }
+            }
+            for preference in person.preferences.iter().flatten() {
+                if let Preference::WantPeriod { from, to } | Preference::NotWantPeriod { from, to } =
+                    preference
+                    && from >= to
+                {
+                    errors.push(ConfigError::InvalidPreferencePeriod {
+                        person_name: person.name.clone(),
+                    });
+                }
+            }
+            if check_dates {
+                let range = self.schedule.from..self.schedule.end();
+                let ooo_dates = person.ooo.iter().flatten().flat_map(|ooo| match ooo {
+                    Ooo::Day(date) | Ooo::Partial { date, .. } | Ooo::Soft(date) => vec![*date],
+                    Ooo::Period { from, to } | Ooo::SoftPeriod { from, to } => vec![*from, *to],
+                    Ooo::Weekday(_) | Ooo::Weekdays(_) => vec![],
+                });
+                let preference_dates = person.preferences.iter().flatten().flat_map(|p| match p {
+                    Preference::Want(date)
+                    | Preference::NotWant(date)
+                    | Preference::StrongWant(date)
+                    | Preference::StrongNotWant(date) => vec![*date],
+                    Preference::WantPeriod { from, to }
+                    | Preference::NotWantPeriod { from, to } => {
+                        vec![*from, *to]
+                    }
+                });
+                for date in ooo_dates.chain(preference_dates) {
+                    if !range.contains(&date) {
+                        errors.push(ConfigError::DateOutOfRange {
+                            person_name: person.name.clone(),
+                            date,
+                        });
+                    }
+                }
+            }
+            if let Some(ramp) = &person.ramp
+                && (ramp.from >= ramp.to
+                    || !ramp.start_weight.is_finite()
+                    || ramp.start_weight <= 0.0
+                    || ramp.start_weight > 1.0)
+            {
+                errors.push(ConfigError::InvalidRamp {
+                    person_name: person.name.clone(),
+                });
+            }
+            if let Some(weight) = person.weight
+                && (!weight.is_finite() || weight <= 0.0)
+            {
+                errors.push(ConfigError::InvalidWeight {
+                    person_name: person.name.clone(),
+                });
+            }
+            if person.turn_length_days == Some(0) {
+                errors.push(ConfigError::InvalidTurnLength);
+            }
+            for o in person.load_overrides.iter().flatten() {
+                if o.from >= o.to || !o.weight.is_finite() || o.weight <= 0.0 {
+                    errors.push(ConfigError::InvalidLoadOverride {
+                        person_name: person.name.clone(),
+                    });
                 }
             }
         }
 
-        Ok(())
+        if let Some(teams) = &self.teams {
+            for (id, team) in teams {
+                if team.name.is_empty() {
+                    errors.push(ConfigError::EmptyTeamName);
+                }
+                if team.members.is_empty() {
+                    errors.push(ConfigError::EmptyTeam { team: id.clone() });
+                }
+                for member in &team.members {
+                    if !self.people.contains_key(member) {
+                        errors.push(ConfigError::UnknownTeamMember {
+                            team: id.clone(),
+                            person: member.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        if let Some(groups) = &self.groups {
+            for (group, members) in groups {
+                // With teams, the rotation is between teams.
+                for id in members {
+                    let known = match &self.teams {
+                        Some(teams) => teams.contains_key(id),
+                        None => self.people.contains_key(id),
+                    };
+                    if !known {
+                        errors.push(ConfigError::UnknownGroupMember {
+                            group: group.clone(),
+                            id: id.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        errors
     }
 }
 
-pub fn parse(config_file: &Path) -> Result<Config, ConfigError> {
+/// Parses the config file at `config_file`, as TOML if its extension is
+/// `.toml` and as YAML otherwise.
+pub fn parse_path(config_file: &Path) -> Result<Config, ConfigError> {
     if !config_file.exists() || !config_file.is_file() {
         return Err(ConfigError::InvalidPath(config_file.to_path_buf()));
     }
     let content = std::fs::read_to_string(config_file)?;
-    let config: Config = serde_yaml::from_str(&content)?;
+    // YAML unless the extension says otherwise.
+    let config: Config = if config_file.extension().is_some_and(|e| e == "toml") {
+        toml::from_str(&content)?
+    } else {
+        serde_yaml::from_str(&content)?
+    };
+    finish(config, config_file.parent().unwrap_or(Path::new(".")))
+}
+
+/// Parses a config that isn't read from a file, e.g. from stdin. Availability
+/// files are relative to the current directory.
+pub fn parse_str(content: &str) -> Result<Config, ConfigError> {
+    finish(serde_yaml::from_str(content)?, Path::new("."))
+}
+
+fn finish(mut config: Config, base_dir: &Path) -> Result<Config, ConfigError> {
+    // Availability files are kept up over time and may well cover other
+    // months, so only the dates of the config itself must be in range.
     config.validate()?;
+    config.load_availability_files(base_dir)?;
+    config.validate_merged()?;
     Ok(config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::algo;
+    use crate::input::Person;
+    use chrono::Datelike;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -145,8 +1269,11 @@ people:
     name: Alice
     preferences:
       - !Want 2025-01-10
+      - !StrongNotWant 2025-01-20
   bob:
     name: Bob
+    max_days: 10
+    turn_length_days: 14
 schedule:
   from: 2025-01-01
   to: 2025-01-31
@@ -154,8 +1281,15 @@ schedule:
     turn_length_days: 7
 "#;
         let file = write_config_to_tempfile(config);
-        let result = parse(file.path());
+        let result = parse_path(file.path());
         assert!(result.is_ok());
+        let config = result.unwrap();
+        assert_eq!(config.people["bob"].max_days, Some(10));
+        assert_eq!(config.people["bob"].turn_length_days, Some(14));
+        assert!(matches!(
+            config.people["alice"].preferences.as_deref(),
+            Some([Preference::Want(_), Preference::StrongNotWant(_)])
+        ));
     }
 
     #[test]
@@ -172,77 +1306,1321 @@ schedule:
     turn_length_days: 7
 "#;
         let file = write_config_to_tempfile(config);
-        let result = parse(file.path());
+        let result = parse_path(file.path());
         assert!(matches!(result, Err(ConfigError::EmptyPersonName)));
     }
 
     #[test]
-    fn test_parse_invalid_date_range() {
+    fn test_parse_no_people() {
         let config = r#"
-people:
-  alice:
-    name: Alice
-    preferences: []
+people: {}
 schedule:
-  from: 2025-01-31
-  to: 2025-01-01
+  from: 2025-01-01
+  to: 2025-01-31
   algo: !RoundRobin
     turn_length_days: 7
 "#;
         let file = write_config_to_tempfile(config);
-        let result = parse(file.path());
-        assert!(matches!(result, Err(ConfigError::InvalidDateRange)));
+        let result = parse_path(file.path());
+        assert!(matches!(result, Err(ConfigError::NoPeople)));
     }
 
     #[test]
-    fn test_parse_invalid_turn_length() {
-        let config = r#"
+    fn test_parse_single_person() {
+        let config = |algo: &str| {
+            format!(
+                r#"
 people:
   alice:
     name: Alice
-    preferences: []
 schedule:
   from: 2025-01-01
   to: 2025-01-31
-  algo: !RoundRobin
-    turn_length_days: 0
-"#;
-        let file = write_config_to_tempfile(config);
-        let result = parse(file.path());
-        assert!(matches!(result, Err(ConfigError::InvalidTurnLength)));
+  algo: {}
+"#,
+                algo
+            )
+        };
+
+        // Round robin gives every turn to the only person.
+        let file = write_config_to_tempfile(&config("!RoundRobin { turn_length_days: 7 }"));
+        assert!(parse_path(file.path()).is_ok());
+
+        for algo in [
+            "!Greedy { turn_length_days: 7 }",
+            "!Balanced { min_turn_days: 3, max_turn_days: 7 }",
+        ] {
+            let file = write_config_to_tempfile(&config(algo));
+            let result = parse_path(file.path());
+            assert!(matches!(result, Err(ConfigError::NotEnoughPeople)));
+        }
     }
 
     #[test]
-    fn test_parse_invalid_ooo_period() {
+    fn test_parse_unknown_start_person() {
         let config = r#"
 people:
   alice:
     name: Alice
-    ooo:
-      - !Period { from: 2025-01-10, to: 2025-01-05 }
-    preferences: []
 schedule:
   from: 2025-01-01
   to: 2025-01-31
   algo: !RoundRobin
     turn_length_days: 7
+    start_person: bob
 "#;
         let file = write_config_to_tempfile(config);
-        let result = parse(file.path());
-        assert!(matches!(result, Err(ConfigError::InvalidOooPeriod { .. })));
+        let result = parse_path(file.path());
+        assert!(matches!(&result, Err(ConfigError::UnknownStartPerson(id)) if id == "bob"));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "The round robin start_person refers to unknown id bob"
+        );
     }
 
     #[test]
-    fn test_parse_non_existent_file() {
-        let path = PathBuf::from("non_existent_file.yaml");
-        let result = parse(&path);
+    fn test_parse_invalid_date_range() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+    preferences: []
+schedule:
+  from: 2025-01-31
+  to: 2025-01-01
+  algo: !RoundRobin
+    turn_length_days: 7
+"#;
+        let file = write_config_to_tempfile(config);
+        let result = parse_path(file.path());
+        assert!(matches!(result, Err(ConfigError::InvalidDateRange)));
+    }
+
+    #[test]
+    fn test_parse_invalid_turn_length() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+    preferences: []
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 0
+"#;
+        let file = write_config_to_tempfile(config);
+        let result = parse_path(file.path());
+        assert!(matches!(result, Err(ConfigError::InvalidTurnLength)));
+    }
+
+    #[test]
+    fn test_parse_balanced() {
+        let config = |min_turn_days: u8, max_turn_days: u8| {
+            format!(
+                r#"
+people:
+  alice:
+    name: Alice
+  bob:
+    name: Bob
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !Balanced
+    min_turn_days: {}
+    max_turn_days: {}
+"#,
+                min_turn_days, max_turn_days
+            )
+        };
+
+        let file = write_config_to_tempfile(&config(3, 10));
+        let parsed = parse_path(file.path()).unwrap();
+        assert!(matches!(
+            parsed.schedule.algo,
+            Algo::Balanced {
+                min_turn_days: 3,
+                max_turn_days: 10,
+                preference_weight: None,
+                balance_weekends: false,
+            }
+        ));
+
+        let file = write_config_to_tempfile(&config(0, 10));
+        let result = parse_path(file.path());
+        assert!(matches!(result, Err(ConfigError::InvalidTurnLength)));
+
+        let file = write_config_to_tempfile(&config(5, 3));
+        let result = parse_path(file.path());
+        assert!(matches!(result, Err(ConfigError::InvalidTurnLengthBounds)));
+    }
+
+    #[test]
+    fn test_parse_invalid_ooo_period() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+    ooo:
+      - !Period { from: 2025-01-10, to: 2025-01-05 }
+    preferences: []
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+"#;
+        let file = write_config_to_tempfile(config);
+        let result = parse_path(file.path());
+        assert!(matches!(result, Err(ConfigError::InvalidOooPeriod { .. })));
+    }
+
+    #[test]
+    fn test_parse_invalid_preference_period() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+    preferences:
+      - !WantPeriod { from: 2025-01-10, to: 2025-01-05 }
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+"#;
+        let file = write_config_to_tempfile(config);
+        let result = parse_path(file.path());
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidPreferencePeriod { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_teams() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+  bob:
+    name: Bob
+teams:
+  backend:
+    name: Backend
+    members: [alice]
+  frontend:
+    name: Frontend
+    members: [bob]
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+"#;
+        let file = write_config_to_tempfile(config);
+        let config = parse_path(file.path()).unwrap();
+        assert_eq!(config.teams.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_contact() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+    email: alice@example.com
+    ooo:
+      - !Day 2025-01-02
+  bob:
+    name: Bob
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+"#;
+        let file = write_config_to_tempfile(config);
+        let config = parse_path(file.path()).unwrap();
+        let alice = &config.people["alice"];
+        assert_eq!(alice.contact.email.as_deref(), Some("alice@example.com"));
+        assert_eq!(alice.contact.phone, None);
+        assert_eq!(alice.ooo.as_ref().unwrap().len(), 1);
+        assert_eq!(config.people["bob"].contact, Contact::default());
+    }
+
+    #[test]
+    fn test_weekday_ooo_blocks_every_matching_day() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+    ooo:
+      - !Weekday Friday
+  bob:
+    name: Bob
+    ooo:
+      - !Weekdays [Sat, Sun]
+schedule:
+  from: 2025-01-01
+  to: 2025-02-01
+  algo: !RoundRobin
+    turn_length_days: 7
+"#;
+        let file = write_config_to_tempfile(config);
+        let config = parse_path(file.path()).unwrap();
+        let (from, to) = (config.schedule.from, config.schedule.end());
+        let person = |id: &str| Person::from_config(id, &config.people[id], from, to);
+        let jan = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+
+        let fridays: HashSet<NaiveDate> = [3, 10, 17, 24, 31].map(jan).into();
+        assert_eq!(person("alice").ooo, fridays);
+        let weekends = person("bob").ooo;
+        assert_eq!(weekends.len(), 8);
+        let weekend = [Weekday::Sat, Weekday::Sun];
+        assert!(weekends.iter().all(|d| weekend.contains(&d.weekday())));
+    }
+
+    #[test]
+    fn test_parse_unknown_group_member() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+groups:
+  backend: [alice, mallory]
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+"#;
+        let file = write_config_to_tempfile(config);
+        let result = parse_path(file.path());
+        assert!(matches!(
+            result,
+            Err(ConfigError::UnknownGroupMember { ref group, ref id })
+                if group == "backend" && id == "mallory"
+        ));
+    }
+
+    #[test]
+    fn test_parse_unknown_team_member() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+teams:
+  backend:
+    name: Backend
+    members: [alice, mallory]
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+"#;
+        let file = write_config_to_tempfile(config);
+        let result = parse_path(file.path());
+        assert!(matches!(
+            result,
+            Err(ConfigError::UnknownTeamMember { ref person, .. }) if person == "mallory"
+        ));
+    }
+
+    #[test]
+    fn test_parse_day_weights() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+  day_weights:
+    2025-01-06: 2.5
+    2025-01-07: -1
+"#;
+        let file = write_config_to_tempfile(config);
+        let result = parse_path(file.path());
+        let date = NaiveDate::from_ymd_opt(2025, 1, 7).unwrap();
+        assert!(matches!(result, Err(ConfigError::InvalidDayWeight(d)) if d == date));
+    }
+
+    #[test]
+    fn test_no_load_days_weigh_nothing() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+  day_weights:
+    2025-01-02: 2.0
+    2025-01-20: 2.0
+  no_load:
+    - !Period { from: 2025-01-01, to: 2025-01-07 }
+"#;
+        let file = write_config_to_tempfile(config);
+        let config = parse_path(file.path()).unwrap();
+        let weights = config.schedule.load_weights();
+
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        assert_eq!(weights.len(), 8);
+        assert_eq!(weights[&day(2)], 0.0);
+        assert_eq!(weights[&day(7)], 0.0);
+        assert_eq!(weights[&day(20)], 2.0);
+    }
+
+    #[test]
+    fn test_holidays_count_extra() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+    preferences:
+      - !Want 2025-12-25
+  bob:
+    name: Bob
+  carol:
+    name: Carol
+schedule:
+  from: 2025-12-22
+  to: 2026-01-03
+  algo: !Greedy
+    turn_length_days: 1
+  day_weights:
+    2026-04-06: 1.5
+  holidays:
+    - !Day 2025-12-25
+    - !Period { from: 2026-04-05, to: 2026-04-06 }
+  holiday_load_factor: 3.0
+"#;
+        let file = write_config_to_tempfile(config);
+        let config = parse_path(file.path()).unwrap();
+        let weights = config.schedule.load_weights();
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 12, d).unwrap();
+        let easter = |d: u32| NaiveDate::from_ymd_opt(2026, 4, d).unwrap();
+        assert_eq!(weights[&day(25)], 3.0);
+        assert_eq!(weights[&easter(5)], 3.0);
+        assert_eq!(weights[&easter(6)], 4.5);
+
+        // Alice asked for Christmas and covers it, so she gets fewer turns.
+        let (from, to) = (config.schedule.from, config.schedule.end());
+        let mut people: Vec<crate::input::Person> = config
+            .people
+            .iter()
+            .map(|(id, p)| crate::input::Person::from_config(id, p, from, to))
+            .collect();
+        people.sort_by(|a, b| a.id.cmp(&b.id));
+        let options = crate::algo::Options {
+            day_weights: weights,
+            ..Default::default()
+        };
+        let schedule = crate::algo::greedy::schedule(
+            people,
+            config.schedule.from,
+            config.schedule.to,
+            1,
+            None,
+            &options,
+        )
+        .unwrap();
+        let christmas = schedule.turns.iter().find(|t| t.start == day(25)).unwrap();
+        assert_eq!(christmas.person, 0);
+        let turns = |p| schedule.turns.iter().filter(|t| t.person == p).count();
+        assert!(turns(0) < turns(1));
+        assert!(turns(0) < turns(2));
+    }
+
+    #[test]
+    fn test_parse_no_coverage() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+schedule:
+  from: 2025-12-01
+  to: 2026-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+  no_coverage:
+    - !Period { from: 2025-12-24, to: 2026-01-01 }
+    - !Day 2026-01-06
+"#;
+        let file = write_config_to_tempfile(config);
+        let config = parse_path(file.path()).unwrap();
+        let uncovered = config.schedule.uncovered_days();
+        assert_eq!(uncovered.len(), 10);
+        assert!(uncovered.contains(&NaiveDate::from_ymd_opt(2025, 12, 24).unwrap()));
+        assert!(uncovered.contains(&NaiveDate::from_ymd_opt(2026, 1, 1).unwrap()));
+        assert!(uncovered.contains(&NaiveDate::from_ymd_opt(2026, 1, 6).unwrap()));
+
+        let config = r#"
+people:
+  alice:
+    name: Alice
+schedule:
+  from: 2025-12-01
+  to: 2026-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+  no_coverage:
+    - !Period { from: 2026-01-01, to: 2025-12-24 }
+"#;
+        let file = write_config_to_tempfile(config);
+        let result = parse_path(file.path());
+        assert!(matches!(result, Err(ConfigError::InvalidNoCoveragePeriod)));
+    }
+
+    #[test]
+    fn test_availability_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("alice.yaml"),
+            r#"
+ooo:
+  - !Period { from: 2025-01-01, to: 2025-01-10 }
+preferences:
+  - !Want 2025-01-20
+  - !NotWant 2025-01-25
+"#,
+        )
+        .unwrap();
+        let config_path = dir.path().join("turns.yaml");
+        std::fs::write(
+            &config_path,
+            r#"
+people:
+  alice:
+    name: Alice
+    availability_file: alice.yaml
+    preferences:
+      - !Want 2025-01-25
+  bob:
+    name: Bob
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !Greedy
+    turn_length_days: 5
+"#,
+        )
+        .unwrap();
+
+        let config = parse_path(&config_path).unwrap();
+        let alice = &config.people["alice"];
+        assert_eq!(alice.ooo.as_ref().unwrap().len(), 1);
+        // The preference in the config wins over the one in the file.
+        assert_eq!(alice.preferences.as_ref().unwrap().len(), 2);
+
+        let (from, to) = (config.schedule.from, config.schedule.end());
+        let mut people: Vec<crate::input::Person> = config
+            .people
+            .iter()
+            .map(|(id, p)| crate::input::Person::from_config(id, p, from, to))
+            .collect();
+        people.sort_by(|a, b| a.id.cmp(&b.id));
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let schedule = crate::algo::roundrobin::schedule(
+            people,
+            config.schedule.from,
+            config.schedule.to,
+            5,
+            None,
+            &crate::algo::Options::default(),
+        )
+        .unwrap();
+        for turn in schedule.turns.iter().filter(|t| t.person == 0) {
+            assert!(turn.start > day(10), "{:?}", turn);
+        }
+
+        std::fs::remove_file(dir.path().join("alice.yaml")).unwrap();
+        assert!(matches!(
+            parse_path(&config_path),
+            Err(ConfigError::ReadAvailabilityFile { .. })
+        ));
+    }
+
+    #[test]
+    fn test_availability_file_beyond_range() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("alice.yaml"),
+            r#"
+ooo:
+  - !Day 2025-01-06
+  - !Period { from: 2025-06-02, to: 2025-06-20 }
+preferences:
+  - !NotWant 2024-12-24
+"#,
+        )
+        .unwrap();
+        let config = |ooo: &str| {
+            format!(
+                r#"
+people:
+  alice:
+    name: Alice
+    availability_file: alice.yaml
+    ooo: [!Day {ooo}]
+  bob:
+    name: Bob
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !Greedy
+    turn_length_days: 5
+"#
+            )
+        };
+        let config_path = dir.path().join("turns.yaml");
+
+        // The file covers other months too, which is fine.
+        std::fs::write(&config_path, config("2025-01-20")).unwrap();
+        let parsed = parse_path(&config_path).unwrap();
+        assert_eq!(parsed.people["alice"].ooo.as_ref().unwrap().len(), 3);
+        assert!(crate::generate(parsed).is_ok());
+        // A date out of the range in the config itself is still a typo.
+        std::fs::write(&config_path, config("2024-01-20")).unwrap();
+        assert!(matches!(
+            parse_path(&config_path),
+            Err(ConfigError::DateOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_apply_env() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+    ooo:
+      - !Day 2025-01-06
+  bob:
+    name: Bob
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+"#;
+        let file = write_config_to_tempfile(config);
+        let mut config = parse_path(file.path()).unwrap();
+        let env = HashMap::from([
+            ("TURNS_FROM", "2025-01-15"),
+            ("TURNS_ALGO", "!Greedy { turn_length_days: 3 }"),
+        ]);
+        config
+            .apply_env(|var| env.get(var).map(|v| v.to_string()))
+            .unwrap();
+
+        // Alice's OOO on Jan 6 is now before the range, which is fine.
+        assert_eq!(config.schedule.from, NaiveDate::from_ymd_opt(2025, 1, 15).unwrap());
+        assert_eq!(config.schedule.to, NaiveDate::from_ymd_opt(2025, 1, 31).unwrap());
+        assert!(matches!(
+            config.schedule.algo,
+            Algo::Greedy {
+                turn_length_days: 3,
+                ..
+            }
+        ));
+
+        let result = config.apply_env(|var| (var == "TURNS_TO").then(|| "2025-01-02".to_string()));
+        assert!(matches!(result, Err(ConfigError::InvalidDateRange)));
+
+        let result = config.apply_env(|var| (var == "TURNS_FROM").then(|| "soon".to_string()));
+        assert!(matches!(result, Err(ConfigError::InvalidEnvVar { .. })));
+    }
+
+    #[test]
+    fn test_normalize_ids() {
+        let config = r#"
+people:
+  "Alice ":
+    name: Alice
+  BOB:
+    name: Bob
+teams:
+  all:
+    name: All
+    members: ["Alice ", BOB]
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+"#;
+        let file = write_config_to_tempfile(config);
+        let mut config = parse_path(file.path()).unwrap();
+        config.normalize_ids().unwrap();
+
+        let mut ids: Vec<&String> = config.people.keys().collect();
+        ids.sort();
+        assert_eq!(ids, vec!["alice", "bob"]);
+        assert_eq!(config.teams.unwrap()["all"].members, vec!["alice", "bob"]);
+    }
+
+    #[test]
+    fn test_normalize_ids_collision() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+  " Alice":
+    name: Alice again
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+"#;
+        let file = write_config_to_tempfile(config);
+        let mut config = parse_path(file.path()).unwrap();
+        let result = config.normalize_ids();
+        assert!(matches!(result, Err(ConfigError::DuplicatePersonId { .. })));
+    }
+
+    #[test]
+    fn test_people_by_id_is_stable() {
+        let ids: Vec<String> = (0..20).map(|i| format!("person{:02}", i)).collect();
+        let mut config = String::from("people:\n");
+        for id in ids.iter().rev() {
+            config.push_str(&format!("  {}:\n    name: {}\n", id, id));
+        }
+        config.push_str(
+            r#"schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+"#,
+        );
+        let file = write_config_to_tempfile(&config);
+        let order = || -> Vec<String> {
+            let config = parse_path(file.path()).unwrap();
+            let people = config.people_by_id();
+            people.iter().map(|(id, _)| id.to_string()).collect()
+        };
+        assert_eq!(order(), ids);
+        assert_eq!(order(), order());
+    }
+
+    #[test]
+    fn test_summary() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+  bob:
+    name: Bob
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  to_inclusive: true
+  algo: !Greedy
+    turn_length_days: 7
+"#;
+        let file = write_config_to_tempfile(config);
+        let config = parse_path(file.path()).unwrap();
+        assert_eq!(
+            config.summary(),
+            "2 people, schedule from 2025-01-01 to 2025-02-01, algo greedy"
+        );
+    }
+
+    #[test]
+    fn test_preference_dates_must_be_within_the_schedule() {
+        let config = |want: &str| {
+            format!(
+                r#"
+people:
+  alice:
+    name: Alice
+    preferences:
+      - !Want {want}
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+"#
+            )
+        };
+
+        let file = write_config_to_tempfile(&config("2025-01-10"));
+        assert!(parse_path(file.path()).is_ok());
+
+        let file = write_config_to_tempfile(&config("2026-01-10"));
+        let result = parse_path(file.path());
+        let typo = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+        assert!(matches!(
+            result,
+            Err(ConfigError::DateOutOfRange { date, .. }) if date == typo
+        ));
+
+        // `to` is excluded.
+        let file = write_config_to_tempfile(&config("2025-01-31"));
+        assert!(matches!(
+            parse_path(file.path()),
+            Err(ConfigError::DateOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_rotations() {
+        let config = |rotations: &str| {
+            format!(
+                r#"
+people:
+  alice:
+    name: Alice
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+{rotations}
+"#
+            )
+        };
+        let file = write_config_to_tempfile(&config(""));
+        assert_eq!(parse_path(file.path()).unwrap().schedule.rotations, 1);
+        let file = write_config_to_tempfile(&config("  rotations: 2"));
+        assert_eq!(parse_path(file.path()).unwrap().schedule.rotations, 2);
+        let file = write_config_to_tempfile(&config("  rotations: 0"));
+        assert!(matches!(
+            parse_path(file.path()),
+            Err(ConfigError::InvalidRotations)
+        ));
+    }
+
+    #[test]
+    fn test_parse_cooldown_turns() {
+        let config = |cooldown: &str| {
+            format!(
+                r#"
+people:
+  alice:
+    name: Alice
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+{cooldown}
+"#
+            )
+        };
+        let file = write_config_to_tempfile(&config(""));
+        assert_eq!(parse_path(file.path()).unwrap().schedule.cooldown_turns, 1);
+        let file = write_config_to_tempfile(&config("  cooldown_turns: 2"));
+        assert_eq!(parse_path(file.path()).unwrap().schedule.cooldown_turns, 2);
+        let file = write_config_to_tempfile(&config("  cooldown_turns: 0"));
+        assert!(matches!(
+            parse_path(file.path()),
+            Err(ConfigError::InvalidCooldown)
+        ));
+    }
+
+    #[test]
+    fn test_parse_max_consecutive_days() {
+        let config = |max: &str| {
+            format!(
+                r#"
+people:
+  alice:
+    name: Alice
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+{max}
+"#
+            )
+        };
+        let file = write_config_to_tempfile(&config(""));
+        let parsed = parse_path(file.path()).unwrap();
+        assert_eq!(parsed.schedule.max_consecutive_days, None);
+        let file = write_config_to_tempfile(&config("  max_consecutive_days: 5"));
+        let parsed = parse_path(file.path()).unwrap();
+        assert_eq!(parsed.schedule.max_consecutive_days, Some(5));
+        let file = write_config_to_tempfile(&config("  max_consecutive_days: 0"));
+        assert!(matches!(
+            parse_path(file.path()),
+            Err(ConfigError::InvalidMaxConsecutiveDays)
+        ));
+    }
+
+    #[test]
+    fn test_parse_fixed() {
+        let config = |fixed: &str| {
+            format!(
+                r#"
+people:
+  alice:
+    name: Alice
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+  fixed:
+    - {fixed}
+"#
+            )
+        };
+        let file = write_config_to_tempfile(&config(
+            "{ person: alice, from: 2025-01-10, to: 2025-01-13 }",
+        ));
+        let fixed = parse_path(file.path()).unwrap().schedule.fixed.unwrap();
+        assert_eq!(fixed[0].person, "alice");
+        assert_eq!(fixed[0].to, NaiveDate::from_ymd_opt(2025, 1, 13).unwrap());
+
+        let file =
+            write_config_to_tempfile(&config("{ person: bob, from: 2025-01-10, to: 2025-01-13 }"));
+        assert!(matches!(
+            parse_path(file.path()),
+            Err(ConfigError::UnknownFixedPerson(id)) if id == "bob"
+        ));
+        let file = write_config_to_tempfile(&config(
+            "{ person: alice, from: 2025-01-13, to: 2025-01-13 }",
+        ));
+        assert!(matches!(
+            parse_path(file.path()),
+            Err(ConfigError::InvalidFixedAssignment { .. })
+        ));
+    }
+
+    #[test]
+    fn test_co_primary_parts() {
+        let config = |people: &str| {
+            format!(
+                r#"
+people:
+  alice:
+    name: Alice
+    ooo:
+      - !Day 2025-01-13
+  bob:
+    name: Bob
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+  co_primary:
+    - {{ people: {people}, from: 2025-01-08, to: 2025-01-15 }}
+"#
+            )
+        };
+
+        let date = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        // The odd day goes to Alice, which keeps her OOO day in Bob's part.
+        let file = write_config_to_tempfile(&config("[alice, bob]"));
+        let parts = parse_path(file.path()).unwrap().schedule.fixed_turns();
+        let parts: Vec<(&str, NaiveDate, NaiveDate, &[String])> = parts
+            .iter()
+            .map(|f| (f.person.as_str(), f.from, f.to, f.co_primary.as_slice()))
+            .collect();
+        assert_eq!(
+            parts,
+            [
+                ("alice", date(8), date(12), &["bob".to_string()][..]),
+                ("bob", date(12), date(15), &["alice".to_string()][..]),
+            ]
+        );
+
+        let file = write_config_to_tempfile(&config("[bob, alice]"));
+        assert!(matches!(
+            parse_path(file.path()),
+            Err(ConfigError::FixedAssignmentDuringOoo { person, .. }) if person == "alice"
+        ));
+        let file = write_config_to_tempfile(&config("[bob]"));
+        assert!(matches!(
+            parse_path(file.path()),
+            Err(ConfigError::InvalidCoPrimaryTurn { .. })
+        ));
+    }
+
+    #[test]
+    fn test_fixed_assignment_conflicts() {
+        let config = |fixed: &str| {
+            format!(
+                r#"
+people:
+  alice:
+    name: Alice
+    ooo:
+      - !Day 2025-01-11
+  bob:
+    name: Bob
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+  fixed: {fixed}
+"#
+            )
+        };
+
+        // Friday to Sunday, over Alice's Saturday off.
+        let file = write_config_to_tempfile(&config(
+            "[{ person: alice, from: 2025-01-10, to: 2025-01-13 }]",
+        ));
+        let error = parse_path(file.path()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Fixed turn of alice from 2025-01-10 to 2025-01-13 falls on their OOO days: 2025-01-11"
+        );
+        let file = write_config_to_tempfile(&config(
+            "[{ person: bob, from: 2025-01-10, to: 2025-01-13 }]",
+        ));
+        assert!(parse_path(file.path()).is_ok());
+
+        let file = write_config_to_tempfile(&config(
+            "[{ person: bob, from: 2025-01-01, to: 2025-01-08 }, \
+             { person: alice, from: 2025-01-07, to: 2025-01-10 }]",
+        ));
+        assert!(matches!(
+            parse_path(file.path()),
+            Err(ConfigError::OverlappingFixedAssignments { first, second, .. })
+                if first == "bob" && second == "alice"
+        ));
+    }
+
+    #[test]
+    fn test_parse_invalid_weight() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+    weight: -0.5
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+"#;
+        let file = write_config_to_tempfile(config);
+        let result = parse_path(file.path());
+        assert!(matches!(result, Err(ConfigError::InvalidWeight { .. })));
+    }
+
+    #[test]
+    fn test_parse_invalid_load_override() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+    load_overrides:
+      - from: 2025-01-01
+        to: 2025-01-15
+        weight: 0.0
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+"#;
+        let file = write_config_to_tempfile(config);
+        let result = parse_path(file.path());
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidLoadOverride { .. })
+        ));
+    }
+
+    #[test]
+    fn test_ramp_eases_in_new_hire() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+    ramp:
+      from: 2025-01-01
+      to: 2025-01-24
+  bob:
+    name: Bob
+  charlie:
+    name: Charlie
+  dave:
+    name: Dave
+schedule:
+  from: 2025-01-01
+  to: 2025-02-18
+  algo: !Greedy
+    turn_length_days: 1
+"#;
+        let file = write_config_to_tempfile(config);
+        let cfg = parse_path(file.path()).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let credits = cfg.ramp_credits();
+        // A fair share of a day is 6 hours, of which Alice carries a quarter.
+        assert_eq!(credits["alice"][&day(1)], TimeDelta::hours(6) * 3 / 4);
+        assert_eq!(credits["alice"][&day(24)], credits["alice"][&day(23)]);
+        assert!(!credits.contains_key("bob"));
+
+        let (from, to) = (cfg.schedule.from, cfg.schedule.end());
+        let mut people: Vec<Person> = cfg
+            .people
+            .iter()
+            .map(|(id, p)| Person::from_config(id, p, from, to))
+            .collect();
+        people.sort_by(|a, b| a.id.cmp(&b.id));
+        let options = algo::Options {
+            ramp_credits: credits,
+            ..Default::default()
+        };
+        let end = NaiveDate::from_ymd_opt(2025, 2, 18).unwrap();
+        let schedule = algo::greedy::schedule(people, day(1), end, 1, None, &options).unwrap();
+        let turns = |person: usize, range: std::ops::Range<usize>| {
+            schedule.turns[range]
+                .iter()
+                .filter(|t| t.person == person)
+                .count()
+        };
+        assert!(turns(0, 0..24) < turns(1, 0..24));
+        assert!(turns(0, 0..24) < turns(2, 0..24));
+        assert!(turns(0, 24..48) >= turns(1, 24..48));
+        assert!(turns(0, 24..48) >= turns(2, 24..48));
+
+        let invalid = config.replace("to: 2025-01-24", "to: 2025-01-24\n      start_weight: 0");
+        let file = write_config_to_tempfile(&invalid);
+        assert!(matches!(
+            parse_path(file.path()),
+            Err(ConfigError::InvalidRamp { .. })
+        ));
+    }
+
+    #[test]
+    fn test_to_inclusive_covers_the_last_day() {
+        let yaml = r#"
+people:
+  alice:
+    name: Alice
+  bob:
+    name: Bob
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  to_inclusive: true
+  algo: !RoundRobin
+    turn_length_days: 7
+"#;
+        let file = write_config_to_tempfile(yaml);
+        let config = parse_path(file.path()).unwrap();
+        let jan = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let feb_1 = NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+        assert_eq!(config.schedule.end(), feb_1);
+
+        let (from, to) = (config.schedule.from, config.schedule.end());
+        let people: Vec<Person> = config
+            .people
+            .iter()
+            .map(|(id, p)| Person::from_config(id, p, from, to))
+            .collect();
+        let schedule = algo::roundrobin::schedule(
+            people,
+            config.schedule.from,
+            config.schedule.end(),
+            7,
+            None,
+            &algo::Options::default(),
+        )
+        .unwrap();
+        let last = schedule.turns.last().unwrap();
+        assert!(last.start <= jan(31) && jan(31) < last.end);
+
+        // The default is still exclusive.
+        let file = write_config_to_tempfile(&yaml.replace("  to_inclusive: true\n", ""));
+        let config = parse_path(file.path()).unwrap();
+        assert_eq!(config.schedule.end(), jan(31));
+    }
+
+    #[test]
+    fn test_parse_invalid_partial_ooo() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+    ooo:
+      - !Partial { date: 2025-01-10, from_hour: 15, to_hour: 13 }
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+"#;
+        let file = write_config_to_tempfile(config);
+        let result = parse_path(file.path());
+        assert!(matches!(result, Err(ConfigError::InvalidPartialOoo { .. })));
+    }
+
+    #[test]
+    fn test_availability_diagnostics() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+    ooo: []
+  bob:
+    name: Bob
+    preferences:
+      - !Want 2025-01-10
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+"#;
+        let file = write_config_to_tempfile(config);
+        let config = parse_path(file.path()).unwrap();
+        assert_eq!(
+            config.availability_diagnostics(),
+            vec!["alice: no availability data (assuming available)"]
+        );
+    }
+
+    #[test]
+    fn test_parse_collects_all_errors() {
+        let config = r#"
+people:
+  alice:
+    name: ""
+  bob:
+    name: Bob
+    ooo:
+      - !Period { from: 2025-01-10, to: 2025-01-05 }
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 0
+"#;
+        let file = write_config_to_tempfile(config);
+        let result = parse_path(file.path());
+        let Err(ConfigError::Multiple(errors)) = result else {
+            panic!("expected several errors, got {:?}", result);
+        };
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(errors[0], ConfigError::InvalidTurnLength));
+        assert!(errors.iter().any(|e| matches!(e, ConfigError::EmptyPersonName)));
+        assert!(errors.iter().any(|e| matches!(e, ConfigError::InvalidOooPeriod { .. })));
+
+        let message = ConfigError::Multiple(errors).to_string();
+        assert!(message.starts_with("3 problems found:\n  - Turn length in days must be positive"));
+    }
+
+    #[test]
+    fn test_parse_non_existent_file() {
+        let path = PathBuf::from("non_existent_file.yaml");
+        let result = parse_path(&path);
         assert!(matches!(result, Err(ConfigError::InvalidPath(_))));
     }
 
     #[test]
     fn test_parse_directory_path() {
         let dir = tempfile::tempdir().unwrap();
-        let result = parse(dir.path());
+        let result = parse_path(dir.path());
         assert!(matches!(result, Err(ConfigError::InvalidPath(_))));
     }
+
+    #[test]
+    fn test_parse_str() {
+        let config = parse_str(
+            r#"
+people:
+  alice:
+    name: Alice
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.people["alice"].name, "Alice");
+
+        let result = parse_str(
+            r#"
+people:
+  alice:
+    name: Alice
+schedule:
+  from: 2025-01-31
+  to: 2025-01-01
+  algo: !RoundRobin
+    turn_length_days: 7
+"#,
+        );
+        assert!(matches!(result, Err(ConfigError::InvalidDateRange)));
+    }
+
+    #[test]
+    fn test_parse_toml_matches_yaml() {
+        let yaml = write_config_to_tempfile(
+            r#"
+people:
+  alice:
+    name: Alice
+    ooo:
+      - !Day 2025-01-03
+      - !Weekday Sat
+    preferences:
+      - !Want 2025-01-10
+      - !NotWantPeriod
+        from: 2025-01-20
+        to: 2025-01-24
+  bob:
+    name: Bob
+    max_days: 10
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !Balanced
+    min_turn_days: 3
+    max_turn_days: 7
+"#,
+        );
+        // Variants are tables keyed by their name, and dates are strings.
+        let toml = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+        std::fs::write(
+            toml.path(),
+            r#"
+[people.alice]
+name = "Alice"
+ooo = [{ Day = "2025-01-03" }, { Weekday = "Sat" }]
+preferences = [
+  { Want = "2025-01-10" },
+  { NotWantPeriod = { from = "2025-01-20", to = "2025-01-24" } },
+]
+
+[people.bob]
+name = "Bob"
+max_days = 10
+
+[schedule]
+from = "2025-01-01"
+to = "2025-01-31"
+algo = { Balanced = { min_turn_days = 3, max_turn_days = 7 } }
+"#,
+        )
+        .unwrap();
+
+        let as_json = |config: Config| serde_json::to_value(config).unwrap();
+        assert_eq!(
+            as_json(parse_path(toml.path()).unwrap()),
+            as_json(parse_path(yaml.path()).unwrap())
+        );
+    }
 }
@@ -1,34 +1,89 @@
 use crate::config;
-use crate::config::{Ooo, Preference};
-use chrono::NaiveDate;
+use crate::config::{Ooo, OrderSource, Preference};
+use chrono::{Datelike, NaiveDate, Weekday};
 use log::info;
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
 use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
 use std::hash::Hash;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
-pub(crate) enum PreferenceType {
+pub enum PreferenceType {
     Want,
     NotWant,
+    StrongWant,
+    StrongNotWant,
+    /// From a soft OOO entry: only a last resort.
+    SoftOoo,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub(crate) struct Person {
-    pub(crate) id: String,
-    pub(crate) name: String,
-    pub(crate) ooo: HashSet<NaiveDate>,
-    pub(crate) preferences: HashMap<NaiveDate, PreferenceType>,
+/// The preference group of turns on days someone is softly OOO, see
+/// `Person::preference_group`.
+pub const SOFT_OOO_GROUP: i32 = 5;
+
+impl PreferenceType {
+    pub fn is_want(&self) -> bool {
+        matches!(self, PreferenceType::Want | PreferenceType::StrongWant)
+    }
+
+    pub fn is_not_want(&self) -> bool {
+        matches!(
+            self,
+            PreferenceType::NotWant | PreferenceType::StrongNotWant | PreferenceType::SoftOoo
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Person {
+    pub id: String,
+    pub name: String,
+    pub ooo: HashSet<NaiveDate>,
+    pub preferences: HashMap<NaiveDate, PreferenceType>,
+    pub contact: config::Contact,
+    /// The person's share of the load relative to the others, e.g. 0.5 for
+    /// a part-timer.
+    pub weight: f64,
+    /// The most days the person can be on call in the schedule, if capped.
+    pub max_days: Option<u32>,
+    /// The length of the person's round robin turns, if not the usual one.
+    pub turn_length_days: Option<u8>,
 }
 
+/// A person named after their id, without OOO, preferences or overrides, for
+/// tests to adjust with struct update syntax.
+#[cfg(test)]
+pub(crate) fn person(id: &str) -> Person {
+    Person {
+        id: id.to_string(),
+        name: id.to_string(),
+        ooo: HashSet::new(),
+        preferences: HashMap::new(),
+        contact: Default::default(),
+        weight: 1.0,
+        max_days: None,
+        turn_length_days: None,
+    }
+}
+
+// Weights are validated to be finite, so equality is total.
+impl Eq for Person {}
+
 impl Hash for Person {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.id.hash(state);
     }
 }
 
-impl From<(&String, &config::Person)> for Person {
-    fn from(value: (&String, &config::Person)) -> Self {
-        let (id, p) = value;
+impl Person {
+    /// Converts a person of the config, expanding their OOO entries and
+    /// preferences into days. Recurring weekdays are expanded over `[from,
+    /// to)`, the range of the schedule.
+    pub fn from_config(id: &str, p: &config::Person, from: NaiveDate, to: NaiveDate) -> Self {
         let mut ooo = HashSet::new();
+        let mut preferences = HashMap::new();
 
         if let Some(ooo_vec) = &p.ooo {
             for ooo_entry in ooo_vec {
@@ -45,11 +100,43 @@ impl From<(&String, &config::Person)> for Person {
                             current = current.succ_opt().unwrap();
                         }
                     }
+                    Ooo::Partial {
+                        date,
+                        from_hour,
+                        to_hour,
+                    } => {
+                        // Explicit preferences below take precedence.
+                        info!(
+                            "{} is partially Ooo on {} ({}-{}h)",
+                            p.name, date, from_hour, to_hour
+                        );
+                        preferences.insert(*date, PreferenceType::NotWant);
+                    }
+                    Ooo::Weekday(weekday) => {
+                        info!("{} is Ooo every {}", p.name, weekday);
+                        ooo.extend(days_on(&[*weekday], from, to));
+                    }
+                    Ooo::Weekdays(weekdays) => {
+                        info!("{} is Ooo every {:?}", p.name, weekdays);
+                        ooo.extend(days_on(weekdays, from, to));
+                    }
+                    // Like partial entries, a preference rather than a block.
+                    Ooo::Soft(date) => {
+                        info!("{} is softly Ooo on {}", p.name, date);
+                        preferences.insert(*date, PreferenceType::SoftOoo);
+                    }
+                    Ooo::SoftPeriod { from, to } => {
+                        let mut current = *from;
+                        while current <= *to {
+                            info!("{} is softly Ooo on {}", p.name, current);
+                            preferences.insert(current, PreferenceType::SoftOoo);
+                            current = current.succ_opt().unwrap();
+                        }
+                    }
                 }
             }
         }
 
-        let mut preferences = HashMap::new();
         if let Some(pref_vec) = &p.preferences {
             for pref_entry in pref_vec {
                 match pref_entry {
@@ -61,15 +148,738 @@ impl From<(&String, &config::Person)> for Person {
                         info!("{} does not want to be on call on {}", p.name, date);
                         preferences.insert(*date, PreferenceType::NotWant);
                     }
+                    Preference::StrongWant(date) => {
+                        info!("{} really wants to be on call on {}", p.name, date);
+                        preferences.insert(*date, PreferenceType::StrongWant);
+                    }
+                    Preference::StrongNotWant(date) => {
+                        info!("{} really does not want to be on call on {}", p.name, date);
+                        preferences.insert(*date, PreferenceType::StrongNotWant);
+                    }
+                    Preference::WantPeriod { from, to } => {
+                        let mut current = *from;
+                        while current <= *to {
+                            info!("{} wants to be on call on {}", p.name, current);
+                            preferences.insert(current, PreferenceType::Want);
+                            current = current.succ_opt().unwrap();
+                        }
+                    }
+                    Preference::NotWantPeriod { from, to } => {
+                        let mut current = *from;
+                        while current <= *to {
+                            info!("{} does not want to be on call on {}", p.name, current);
+                            preferences.insert(current, PreferenceType::NotWant);
+                            current = current.succ_opt().unwrap();
+                        }
+                    }
                 }
             }
         }
 
         Person {
-            id: id.clone(),
+            id: id.to_string(),
             name: p.name.clone(),
             ooo,
             preferences,
+            contact: p.contact.clone(),
+            weight: p.weight.unwrap_or(1.0),
+            max_days: p.max_days,
+            turn_length_days: p.turn_length_days,
+        }
+    }
+
+    /// Whether the person can take a turn of `turn_days` without going over
+    /// `max_days`, with `days` on call so far.
+    pub fn has_room_for(&self, days: i64, turn_days: i64) -> bool {
+        self.max_days
+            .is_none_or(|max_days| days + turn_days <= max_days.into())
+    }
+
+    /// How much the person wants the days in `[start, end)`, from 0 (a strong
+    /// `Want`) through 2 (no preference) to 4 (a strong `NotWant`). Wanting
+    /// any of the days outweighs not wanting others, and being softly OOO on
+    /// any of them outweighs everything: that's `SOFT_OOO_GROUP`.
+    pub fn preference_group(&self, start: NaiveDate, end: NaiveDate) -> i32 {
+        let preferences: Vec<&PreferenceType> = start
+            .iter_days()
+            .take_while(|d| *d < end)
+            .filter_map(|d| self.preferences.get(&d))
+            .collect();
+        let has = |preference: PreferenceType| preferences.contains(&&preference);
+        if has(PreferenceType::SoftOoo) {
+            SOFT_OOO_GROUP
+        } else if has(PreferenceType::StrongWant) {
+            0
+        } else if has(PreferenceType::Want) {
+            1
+        } else if has(PreferenceType::StrongNotWant) {
+            4
+        } else if has(PreferenceType::NotWant) {
+            3
+        } else {
+            2
+        }
+    }
+}
+
+/// The days in `[from, to)` falling on one of `weekdays`.
+fn days_on(weekdays: &[Weekday], from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+    from.iter_days()
+        .take_while(|&date| date < to)
+        .filter(|date| weekdays.contains(&date.weekday()))
+        .collect()
+}
+
+/// Semantic checks that are only possible once OOO periods are expanded:
+/// people who are OOO for the whole schedule, and people who never have
+/// `min_turn_days` available days in a row (nor an available stretch running
+/// to the end of the schedule), so they can never be picked.
+pub fn availability_warnings(
+    people: &[Person],
+    from: NaiveDate,
+    to: NaiveDate,
+    min_turn_days: u8,
+) -> Vec<String> {
+    let mut warnings = vec![];
+    for person in people {
+        let mut longest_run = 0;
+        let mut run = 0;
+        let mut current = from;
+        while current < to {
+            if person.ooo.contains(&current) {
+                run = 0;
+            } else {
+                run += 1;
+                longest_run = longest_run.max(run);
+            }
+            current = current.succ_opt().unwrap();
+        }
+
+        if longest_run == 0 {
+            warnings.push(format!("{} is OOO for the whole schedule", person.name));
+        } else if longest_run < min_turn_days.into() && run == 0 {
+            warnings.push(format!(
+                "{} is never available for {} days in a row and can't be scheduled",
+                person.name, min_turn_days
+            ));
+        }
+    }
+    warnings
+}
+
+/// Static checks of the preferences as a whole, independent of the
+/// algorithm: `Want` days wanted by more people than there are turns to go
+/// around, assuming `min_turn_days` long turns that line up with the wanted
+/// days, and days nobody wants, where someone has to be on call anyway.
+pub fn preference_conflicts(
+    people: &[Person],
+    from: NaiveDate,
+    to: NaiveDate,
+    min_turn_days: u8,
+) -> Vec<String> {
+    let wanters = |date: NaiveDate| -> Vec<&str> {
+        let mut names: Vec<&str> = people
+            .iter()
+            .filter(|p| !p.ooo.contains(&date))
+            .filter(|p| {
+                p.preferences
+                    .get(&date)
+                    .is_some_and(PreferenceType::is_want)
+            })
+            .map(|p| p.name.as_str())
+            .collect();
+        names.sort();
+        names
+    };
+    let unwanted = |date: NaiveDate| {
+        let available: Vec<&Person> = people.iter().filter(|p| !p.ooo.contains(&date)).collect();
+        !available.is_empty()
+            && available.iter().all(|p| {
+                p.preferences
+                    .get(&date)
+                    .is_some_and(PreferenceType::is_not_want)
+            })
+    };
+
+    let mut warnings = vec![];
+    let mut current = from;
+    while current < to {
+        // The run of days wanted by the same people, or by no one.
+        let names = wanters(current);
+        let is_unwanted = unwanted(current);
+        let mut run_end = current.succ_opt().unwrap();
+        while run_end < to && wanters(run_end) == names && unwanted(run_end) == is_unwanted {
+            run_end = run_end.succ_opt().unwrap();
+        }
+        let last_day = run_end.pred_opt().unwrap();
+
+        let days = (run_end - current).num_days() as usize;
+        let slots = days.div_ceil(usize::from(min_turn_days).max(1));
+        if names.len() > slots {
+            warnings.push(format!(
+                "{} all want to be on call from {} to {}: {} of {} wants cannot be honored",
+                names.join(", "),
+                current,
+                last_day,
+                names.len() - slots,
+                names.len()
+            ));
+        }
+        if is_unwanted {
+            warnings.push(format!(
+                "Everyone available prefers not to be on call from {} to {}",
+                current, last_day
+            ));
+        }
+        current = run_end;
+    }
+    warnings
+}
+
+/// Someone's availability on a day, as shown in a [`Heatmap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Availability {
+    Available,
+    Ooo,
+    Want,
+    NotWant,
+}
+
+impl Availability {
+    fn of(person: &Person, date: NaiveDate) -> Self {
+        if person.ooo.contains(&date) {
+            return Availability::Ooo;
+        }
+        match person.preferences.get(&date) {
+            Some(preference) if preference.is_want() => Availability::Want,
+            Some(_) => Availability::NotWant,
+            None => Availability::Available,
+        }
+    }
+
+    fn glyph(self) -> char {
+        match self {
+            Availability::Available => '.',
+            Availability::Ooo => 'x',
+            Availability::Want => '+',
+            Availability::NotWant => '-',
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Availability::Available => "available",
+            Availability::Ooo => "ooo",
+            Availability::Want => "want",
+            Availability::NotWant => "not_want",
+        }
+    }
+}
+
+/// The availability of everyone on every day from `from` to `to` (excluded),
+/// before any scheduling, to spot days with few people available. Displays
+/// as a grid with a glyph per day, weeks separated by a space.
+pub struct Heatmap<'a> {
+    people: Vec<&'a Person>,
+    from: NaiveDate,
+    to: NaiveDate,
+}
+
+impl<'a> Heatmap<'a> {
+    /// People are listed by id.
+    pub fn new(people: &'a [Person], from: NaiveDate, to: NaiveDate) -> Self {
+        let mut people: Vec<&Person> = people.iter().collect();
+        people.sort_by(|a, b| a.id.cmp(&b.id));
+        Heatmap { people, from, to }
+    }
+
+    fn days(&self) -> impl Iterator<Item = NaiveDate> + '_ {
+        self.from.iter_days().take_while(|d| *d < self.to)
+    }
+
+    /// The availability of each person, by id, day by day.
+    pub fn rows(&self) -> Vec<(&str, Vec<Availability>)> {
+        self.people
+            .iter()
+            .map(|p| {
+                let row = self.days().map(|d| Availability::of(p, d)).collect();
+                (p.id.as_str(), row)
+            })
+            .collect()
+    }
+
+    /// One row per person and one column per day, with the availability
+    /// names as cells.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("person");
+        for day in self.days() {
+            csv.push_str(&format!(",{}", day));
+        }
+        csv.push('\n');
+        for (id, row) in self.rows() {
+            csv.push_str(id);
+            for availability in row {
+                csv.push(',');
+                csv.push_str(availability.name());
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+impl Display for Heatmap<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "From {} to {} (. available, x OOO, + want, - not want)",
+            self.from, self.to
+        )?;
+        let width = self.people.iter().map(|p| p.id.len()).max().unwrap_or(0);
+        let separated = |cells: &mut dyn Iterator<Item = char>| -> String {
+            let mut line = String::new();
+            for (day, cell) in self.days().zip(cells) {
+                if day != self.from && day.weekday() == Weekday::Mon {
+                    line.push(' ');
+                }
+                line.push(cell);
+            }
+            line
+        };
+        for (id, row) in self.rows() {
+            let line = separated(&mut row.into_iter().map(Availability::glyph));
+            writeln!(f, "{:width$}  {}", id, line)?;
+        }
+        // How many people are available each day, 9 at most.
+        let mut counts = self.days().map(|day| {
+            let available = self.people.iter().filter(|p| !p.ooo.contains(&day)).count();
+            char::from_digit(available.min(9) as u32, 10).unwrap()
+        });
+        writeln!(f, "{:width$}  {}", "", separated(&mut counts))
+    }
+}
+
+/// Shuffles the rotation order reproducibly: people are first sorted by id,
+/// so the result only depends on the seed and the set of people.
+pub fn shuffle(people: &mut [Person], seed: u64) {
+    people.sort_by(|a, b| a.id.cmp(&b.id));
+    people.shuffle(&mut ChaCha8Rng::seed_from_u64(seed));
+    info!(
+        "Rotation order: {:?}",
+        people.iter().map(|p| &p.id).collect::<Vec<_>>()
+    );
+}
+
+/// Puts people in the fixed rotation order given by `source`, dropping those
+/// not part of it.
+pub fn order(people: &mut Vec<Person>, source: &OrderSource) {
+    match source {
+        OrderSource::Alphabetical => people.sort_by(|a, b| a.id.cmp(&b.id)),
+        OrderSource::Ids(ids) => {
+            people.retain(|p| ids.contains(&p.id));
+            people.sort_by_key(|p| ids.iter().position(|id| *id == p.id));
         }
     }
 }
+
+/// Hands every `weekday` from `from` to `to` (both included) to the people
+/// who own it, given as `(id, weekday, required)`. Owners get a `Want`
+/// preference for the day and a `NotWant` one for the day before, so that
+/// they aren't on call right before and skipped to avoid back-to-back turns;
+/// explicit preferences take precedence. When the ownership is required,
+/// everyone else is also made OOO that day. Days on which all the owners are
+/// OOO are left alone.
+pub fn apply_weekday_owners(
+    people: &mut [Person],
+    owners: &[(String, Weekday, bool)],
+    from: NaiveDate,
+    to: NaiveDate,
+) {
+    for date in from.iter_days().take_while(|d| *d <= to) {
+        let available: Vec<&(String, Weekday, bool)> = owners
+            .iter()
+            .filter(|(id, weekday, _)| {
+                *weekday == date.weekday()
+                    && people.iter().any(|p| p.id == *id && !p.ooo.contains(&date))
+            })
+            .collect();
+        if available.is_empty() {
+            continue;
+        }
+        let required = available.iter().any(|(_, _, required)| *required);
+        for person in people.iter_mut() {
+            if available.iter().any(|(id, _, _)| *id == person.id) {
+                person
+                    .preferences
+                    .entry(date)
+                    .or_insert(PreferenceType::Want);
+                if let Some(before) = date.pred_opt().filter(|d| *d >= from) {
+                    person
+                        .preferences
+                        .entry(before)
+                        .or_insert(PreferenceType::NotWant);
+                }
+            } else if required {
+                info!("{} is Ooo on {}, owned by someone else", person.name, date);
+                person.ooo.insert(date);
+            }
+        }
+    }
+}
+
+impl Person {
+    /// Builds the rotation participant standing for a team. A team is only
+    /// unavailable on the days all of its members are OOO; members'
+    /// preferences are left to the team's internal rotation.
+    pub fn from_team(id: &str, team: &config::Team, people: &[Person]) -> Self {
+        let members: Vec<&Person> = people
+            .iter()
+            .filter(|p| team.members.contains(&p.id))
+            .collect();
+        let ooo = match members.split_first() {
+            Some((first, rest)) => first
+                .ooo
+                .iter()
+                .filter(|date| rest.iter().all(|m| m.ooo.contains(date)))
+                .copied()
+                .collect(),
+            None => HashSet::new(),
+        };
+        info!("Team {} is unavailable on {} days", team.name, ooo.len());
+
+        Person {
+            id: id.to_string(),
+            name: team.name.clone(),
+            ooo,
+            preferences: HashMap::new(),
+            contact: Default::default(),
+            weight: 1.0,
+            max_days: None,
+            turn_length_days: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo;
+    use chrono::TimeDelta;
+
+    fn person(id: &str, ooo: &[NaiveDate]) -> Person {
+        Person {
+            ooo: ooo.iter().copied().collect(),
+            ..super::person(id)
+        }
+    }
+
+    fn team(name: &str, members: &[&str]) -> config::Team {
+        config::Team {
+            name: name.to_string(),
+            members: members.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_ooo_days_and_periods_are_expanded() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let bob = config::Person {
+            name: "Bob".to_string(),
+            ooo: Some(vec![
+                Ooo::Day(day(2)),
+                Ooo::Period {
+                    from: day(10),
+                    to: day(12),
+                },
+                Ooo::Soft(day(20)),
+                Ooo::SoftPeriod {
+                    from: day(14),
+                    to: day(15),
+                },
+            ]),
+            preferences: Some(vec![Preference::NotWant(day(5))]),
+            load_overrides: None,
+            ramp: None,
+            availability_file: None,
+            escalation_only: false,
+            owns_weekday: None,
+            owns_weekday_required: false,
+            position: None,
+            contact: config::Contact::default(),
+            weight: None,
+            max_days: None,
+            turn_length_days: None,
+        };
+        let bob = Person::from_config("bob", &bob, day(1), day(31));
+        assert_eq!(bob.id, "bob");
+        assert_eq!(bob.name, "Bob");
+        assert_eq!(bob.ooo, HashSet::from([day(2), day(10), day(11), day(12)]));
+        assert_eq!(bob.preferences[&day(5)], PreferenceType::NotWant);
+        for d in [14, 15, 20] {
+            assert_eq!(bob.preferences[&day(d)], PreferenceType::SoftOoo);
+        }
+        assert_eq!(bob.preference_group(day(13), day(15)), SOFT_OOO_GROUP);
+    }
+
+    #[test]
+    fn test_preference_periods() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let alice = config::Person {
+            name: "Alice".to_string(),
+            ooo: None,
+            preferences: Some(vec![
+                Preference::WantPeriod {
+                    from: day(6),
+                    to: day(19),
+                },
+                Preference::NotWantPeriod {
+                    from: day(24),
+                    to: day(25),
+                },
+            ]),
+            load_overrides: None,
+            ramp: None,
+            availability_file: None,
+            escalation_only: false,
+            owns_weekday: None,
+            owns_weekday_required: false,
+            position: None,
+            contact: config::Contact::default(),
+            weight: None,
+            max_days: None,
+            turn_length_days: None,
+        };
+        let alice = Person::from_config("alice", &alice, day(1), day(31));
+        for d in 6..=19 {
+            assert_eq!(alice.preferences[&day(d)], PreferenceType::Want);
+        }
+        assert_eq!(alice.preferences[&day(24)], PreferenceType::NotWant);
+        assert_eq!(alice.preferences[&day(25)], PreferenceType::NotWant);
+        assert_eq!(alice.preferences.len(), 16);
+    }
+
+    #[test]
+    fn test_partial_ooo_deprioritizes_without_excluding() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let alice = config::Person {
+            name: "Alice".to_string(),
+            ooo: Some(
+                (1..=4)
+                    .map(|d| Ooo::Partial {
+                        date: day(d),
+                        from_hour: 13,
+                        to_hour: 17,
+                    })
+                    .collect(),
+            ),
+            preferences: Some(vec![Preference::Want(day(4))]),
+            load_overrides: None,
+            ramp: None,
+            availability_file: None,
+            escalation_only: false,
+            owns_weekday: None,
+            owns_weekday_required: false,
+            position: None,
+            contact: config::Contact::default(),
+            weight: None,
+            max_days: None,
+            turn_length_days: None,
+        };
+        let alice = Person::from_config("alice", &alice, day(1), day(31));
+        assert!(alice.ooo.is_empty());
+        assert_eq!(alice.preferences[&day(1)], PreferenceType::NotWant);
+        assert_eq!(alice.preferences[&day(4)], PreferenceType::Want);
+
+        let people = vec![alice, person("bob", &[])];
+        let schedule =
+            algo::greedy::schedule(people, day(1), day(5), 2, None, &algo::Options::default())
+                .unwrap();
+        assert_eq!(schedule.turns[0].person, 1);
+        assert_eq!(schedule.turns[1].person, 0);
+    }
+
+    #[test]
+    fn test_weekday_owner_gets_the_weekday() {
+        // 2025-01-01 is a Wednesday.
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let owners = [("carol".to_string(), Weekday::Wed, false)];
+        let mut people = vec![
+            person("alice", &[]),
+            person("bob", &[]),
+            person("carol", &[day(15)]),
+        ];
+        apply_weekday_owners(&mut people, &owners, day(1), day(28));
+        assert_eq!(people[2].preferences[&day(8)], PreferenceType::Want);
+        assert_eq!(people[2].preferences[&day(7)], PreferenceType::NotWant);
+        assert!(!people[2].preferences.contains_key(&day(9)));
+
+        let schedule =
+            algo::greedy::schedule(people, day(1), day(29), 1, None, &algo::Options::default())
+                .unwrap();
+        let wednesdays: Vec<usize> = schedule
+            .turns
+            .iter()
+            .filter(|t| t.start.weekday() == Weekday::Wed)
+            .map(|t| t.person)
+            .collect();
+        // Carol is OOO on the 15th, someone else covers it.
+        assert_eq!(wednesdays[0], 2);
+        assert_eq!(wednesdays[1], 2);
+        assert_ne!(wednesdays[2], 2);
+        assert_eq!(wednesdays[3], 2);
+
+        let owners = [("carol".to_string(), Weekday::Wed, true)];
+        let mut people = vec![person("alice", &[]), person("carol", &[day(15)])];
+        apply_weekday_owners(&mut people, &owners, day(1), day(28));
+        assert!(people[0].ooo.contains(&day(8)));
+        assert!(!people[0].ooo.contains(&day(15)));
+        assert!(!people[0].ooo.contains(&day(9)));
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let mut bob = person("bob", &[day(3), day(4), day(5)]);
+        bob.preferences.insert(day(7), PreferenceType::Want);
+        bob.preferences.insert(day(8), PreferenceType::NotWant);
+        let people = vec![bob, person("alice", &[])];
+
+        let heatmap = Heatmap::new(&people, day(1), day(9));
+        let rows = heatmap.rows();
+        assert_eq!(rows[0].0, "alice");
+        assert!(rows[0].1.iter().all(|a| *a == Availability::Available));
+        assert_eq!(rows[1].0, "bob");
+        assert_eq!(rows[1].1[1], Availability::Available);
+        assert_eq!(&rows[1].1[2..5], &[Availability::Ooo; 3]);
+        assert_eq!(rows[1].1[5], Availability::Available);
+        assert_eq!(rows[1].1[6], Availability::Want);
+        assert_eq!(rows[1].1[7], Availability::NotWant);
+
+        // 2025-01-06 is a Monday.
+        assert_eq!(
+            heatmap.to_string(),
+            "From 2025-01-01 to 2025-01-09 (. available, x OOO, + want, - not want)\n\
+             alice  ..... ...\n\
+             bob    ..xxx .+-\n\
+             \x20      22111 222\n"
+        );
+        let csv = heatmap.to_csv();
+        assert!(csv.starts_with("person,2025-01-01,"));
+        assert!(csv.contains("\nbob,available,available,ooo,ooo,ooo,available,want,not_want\n"));
+    }
+
+    #[test]
+    fn test_availability_warnings() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let every_other_day: Vec<NaiveDate> = (1..=10).step_by(2).map(day).collect();
+        let people = vec![
+            person("alice", &(1..=10).map(day).collect::<Vec<_>>()),
+            person("bob", &every_other_day),
+            person("carol", &[day(5)]),
+        ];
+
+        let warnings = availability_warnings(&people, day(1), day(10), 2);
+        assert_eq!(
+            warnings,
+            vec![
+                "alice is OOO for the whole schedule",
+                "bob is never available for 2 days in a row and can't be scheduled",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_preference_conflicts() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let with_preferences = |id: &str, dates: &[u32], preference: PreferenceType| Person {
+            preferences: dates
+                .iter()
+                .map(|d| (day(*d), preference.clone()))
+                .collect(),
+            ..person(id, &[])
+        };
+        let week: Vec<u32> = (6..=12).collect();
+        let people = vec![
+            with_preferences("alice", &week, PreferenceType::Want),
+            with_preferences("bob", &week, PreferenceType::Want),
+            with_preferences("carol", &week, PreferenceType::Want),
+        ];
+
+        let warnings = preference_conflicts(&people, day(1), day(20), 7);
+        assert_eq!(
+            warnings,
+            vec![
+                "alice, bob, carol all want to be on call from 2025-01-06 to 2025-01-12: \
+                 2 of 3 wants cannot be honored"
+            ]
+        );
+        assert!(preference_conflicts(&people[..1], day(1), day(20), 7).is_empty());
+
+        let people = vec![
+            with_preferences("alice", &[14, 15], PreferenceType::NotWant),
+            Person {
+                ooo: [day(14)].into(),
+                ..with_preferences("bob", &[15], PreferenceType::NotWant)
+            },
+        ];
+        assert_eq!(
+            preference_conflicts(&people, day(1), day(20), 7),
+            vec!["Everyone available prefers not to be on call from 2025-01-14 to 2025-01-15"]
+        );
+    }
+
+    #[test]
+    fn test_shuffle_is_stable_for_a_seed() {
+        let ids = ["alice", "bob", "charlie", "dave", "erin"];
+        let order = |seed: u64, reversed: bool| {
+            let mut people: Vec<Person> = ids.iter().map(|id| person(id, &[])).collect();
+            if reversed {
+                people.reverse();
+            }
+            shuffle(&mut people, seed);
+            people.into_iter().map(|p| p.id).collect::<Vec<_>>()
+        };
+
+        assert_eq!(order(42, false), vec!["charlie", "bob", "alice", "erin", "dave"]);
+        assert_eq!(order(42, true), order(42, false));
+        assert_ne!(order(7, false), order(42, false));
+    }
+
+    #[test]
+    fn test_team_is_unavailable_only_when_all_members_are() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people = vec![
+            person("alice", &[day(1), day(2)]),
+            person("bob", &[day(2), day(3)]),
+        ];
+
+        let backend = Person::from_team("backend", &team("Backend", &["alice", "bob"]), &people);
+        assert_eq!(backend.ooo, HashSet::from([day(2)]));
+    }
+
+    #[test]
+    fn test_two_team_rotation_balances_team_days() {
+        let people = vec![
+            person("alice", &[]),
+            person("bob", &[]),
+            person("carol", &[]),
+        ];
+        let teams = vec![
+            Person::from_team("backend", &team("Backend", &["alice", "bob"]), &people),
+            Person::from_team("frontend", &team("Frontend", &["carol"]), &people),
+        ];
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 1, 29).unwrap();
+
+        let schedule = algo::greedy::schedule(teams, start, end, 7, None, &algo::Options::default()).unwrap();
+        let days = |team: usize| {
+            schedule
+                .turns
+                .iter()
+                .filter(|t| t.person == team)
+                .fold(TimeDelta::zero(), |acc, t| acc + (t.end - t.start))
+        };
+        assert_eq!(days(0), TimeDelta::days(14));
+        assert_eq!(days(1), TimeDelta::days(14));
+    }
+}
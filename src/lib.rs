@@ -0,0 +1,415 @@
+pub mod algo;
+pub mod config;
+pub mod input;
+pub mod output;
+
+pub use crate::config::{Config, ConfigError};
+pub use crate::output::{Assignment, Schedule, ScheduleError};
+
+use crate::input::Person;
+use chrono::NaiveDate;
+use log::{info, warn};
+use std::num::NonZeroUsize;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SchedulerError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Schedule(#[from] ScheduleError),
+}
+
+/// Generates the schedule described by `config`, the way the `turns` command
+/// does without any flags.
+pub fn generate(config: Config) -> Result<Schedule, SchedulerError> {
+    config.validate_merged()?;
+    let people = rotation(&config, config.schedule.shuffle_seed);
+    let options = options(&config);
+    let (start, end) = (config.schedule.from, config.schedule.end());
+    let rotations = config.schedule.rotations;
+    let schedule = algo::with_rotations(rotations, people, &options, |people, options| {
+        run_algo(&config.schedule.algo, people, start, end, options).map(|g| g.schedule)
+    })?;
+    Ok(schedule)
+}
+
+/// The people in `config`, sorted by id.
+pub fn people(config: &Config) -> Vec<Person> {
+    let (from, to) = (config.schedule.from, config.schedule.end());
+    config
+        .people_by_id()
+        .into_iter()
+        .map(|(id, p)| Person::from_config(id, p, from, to))
+        .collect()
+}
+
+/// The people (or teams, when rotating teams) taking turns, in rotation
+/// order: sorted by id, shuffled with `seed` if any, then ordered for the
+/// `OrderedBalanced` algorithm.
+pub fn rotation(config: &Config, seed: Option<u64>) -> Vec<Person> {
+    let mut people = people(config);
+    let weekday_owners = config.weekday_owners();
+    if !weekday_owners.is_empty() {
+        // Every turn of a week or longer covers every weekday.
+        if config.schedule.algo.max_turn_days() < 7 {
+            input::apply_weekday_owners(
+                &mut people,
+                &weekday_owners,
+                config.schedule.from,
+                config.schedule.to,
+            );
+        } else {
+            warn!("Ignoring `owns_weekday`, which only applies to turns shorter than a week");
+        }
+    }
+    // With teams, the rotation is between teams rather than individuals.
+    let mut people: Vec<Person> = match &config.teams {
+        Some(teams) => {
+            let mut teams: Vec<Person> = teams
+                .iter()
+                .map(|(id, team)| Person::from_team(id, team, &people))
+                .collect();
+            teams.sort_by(|a, b| a.id.cmp(&b.id));
+            teams
+        }
+        None => people,
+    };
+    if let Some(seed) = seed {
+        info!("Shuffling the rotation order with seed {}", seed);
+        input::shuffle(&mut people, seed);
+    }
+    if let config::Algo::OrderedBalanced { order_source, .. } = &config.schedule.algo {
+        input::order(&mut people, order_source);
+    }
+    people
+}
+
+/// The scheduling options set in `config`; the others are left to their
+/// defaults.
+pub fn options(config: &Config) -> algo::Options {
+    algo::Options {
+        day_weights: config.schedule.load_weights(),
+        person_weights: config.person_weights(),
+        ramp_credits: config.ramp_credits(),
+        align_to_months: config.schedule.align_to_month_boundaries,
+        escalation_only: config.escalation_only(),
+        positions: config.positions(),
+        min_rest_days: config.schedule.min_rest_days.unwrap_or(0),
+        uncovered: config.schedule.uncovered_days(),
+        fixed: config.schedule.fixed_turns(),
+        cooldown_turns: config.schedule.cooldown_turns.into(),
+        max_consecutive_days: config.schedule.max_consecutive_days,
+        ..Default::default()
+    }
+}
+
+/// A generated schedule, with what the algorithm recorded about its choices
+/// when the options ask for it.
+#[derive(Debug)]
+pub struct Generated {
+    pub schedule: Schedule,
+    /// Every candidate evaluated by the balanced algorithm, with
+    /// `collect_trace`.
+    pub trace: Option<Vec<algo::balanced::Candidate>>,
+    /// Why each turn went to its assignee, with `explain` and the greedy
+    /// algorithm.
+    pub rationale: Option<Vec<algo::greedy::TurnRationale>>,
+}
+
+impl From<Schedule> for Generated {
+    fn from(schedule: Schedule) -> Self {
+        Generated {
+            schedule,
+            trace: None,
+            rationale: None,
+        }
+    }
+}
+
+/// Runs the configured scheduling algorithm.
+pub fn run_algo(
+    algo: &config::Algo,
+    people: Vec<Person>,
+    start: NaiveDate,
+    end: NaiveDate,
+    options: &algo::Options,
+) -> Result<Generated, ScheduleError> {
+    match *algo {
+        config::Algo::RoundRobin {
+            turn_length_days,
+            ref start_person,
+        } => algo::roundrobin::schedule(
+            people,
+            start,
+            end,
+            turn_length_days,
+            start_person.as_deref(),
+            options,
+        )
+        .map(Generated::from),
+        config::Algo::Greedy {
+            turn_length_days,
+            preference_weight,
+        } if options.explain => algo::greedy::schedule_with_rationale(
+            people,
+            start,
+            end,
+            turn_length_days,
+            preference_weight,
+            options,
+        )
+        .map(|(schedule, rationale)| Generated {
+            schedule,
+            trace: None,
+            rationale: Some(rationale),
+        }),
+        config::Algo::Greedy {
+            turn_length_days,
+            preference_weight,
+        } => algo::greedy::schedule(
+            people,
+            start,
+            end,
+            turn_length_days,
+            preference_weight,
+            options,
+        )
+        .map(Generated::from),
+        config::Algo::Balanced {
+            min_turn_days,
+            max_turn_days,
+            preference_weight,
+            balance_weekends,
+        } if options.collect_trace => algo::balanced::schedule_with_trace(
+            people,
+            start,
+            end,
+            min_turn_days,
+            max_turn_days,
+            preference_weight,
+            balance_weekends,
+            options,
+        )
+        .map(|(schedule, trace)| Generated {
+            schedule,
+            trace: Some(trace),
+            rationale: None,
+        }),
+        config::Algo::Balanced {
+            min_turn_days,
+            max_turn_days,
+            preference_weight,
+            balance_weekends,
+        } => algo::balanced::schedule(
+            people,
+            start,
+            end,
+            min_turn_days,
+            max_turn_days,
+            preference_weight,
+            balance_weekends,
+            options,
+        )
+        .map(Generated::from),
+        config::Algo::OrderedBalanced {
+            ref order_source,
+            min_turn_days,
+            max_turn_days,
+        } => algo::ordered::schedule(
+            people,
+            start,
+            end,
+            order_source.clone(),
+            min_turn_days,
+            max_turn_days,
+            options,
+        )
+        .map(Generated::from),
+    }
+}
+
+/// Generates `n` schedules, the first one with `people` in the given order and
+/// the others after shuffling them with `seed + 1`, `seed + 2`, etc., and
+/// returns the fairest: the one with the lowest load spread, then the lowest
+/// Gini coefficient, with what its run recorded. If none can be generated,
+/// the first error is returned.
+pub fn best_of_candidates(
+    n: NonZeroUsize,
+    algo: &config::Algo,
+    people: Vec<Person>,
+    start: NaiveDate,
+    end: NaiveDate,
+    options: &algo::Options,
+    seed: u64,
+) -> Result<Generated, ScheduleError> {
+    let score = |g: &Generated| (g.schedule.load_spread(), g.schedule.report_card().gini);
+    let mut best: Option<Generated> = None;
+    let mut first_error = None;
+    for i in 0..n.get() {
+        let mut candidate = people.clone();
+        if i > 0 {
+            input::shuffle(&mut candidate, seed.wrapping_add(i as u64));
+            if let config::Algo::OrderedBalanced { order_source, .. } = algo {
+                input::order(&mut candidate, order_source);
+            }
+        }
+        match run_algo(algo, candidate, start, end, options) {
+            Ok(generated) => {
+                if best.as_ref().is_none_or(|b| score(&generated) < score(b)) {
+                    best = Some(generated);
+                }
+            }
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    // At least one candidate ran, so there is a schedule or an error.
+    best.ok_or_else(|| first_error.unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::person;
+
+    #[test]
+    fn test_best_of_candidates() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        // Carol being OOO on the 11th makes the rotation order matter.
+        let people: Vec<Person> = ["alice", "bob", "carol"]
+            .iter()
+            .map(|id| Person {
+                ooo: if *id == "carol" {
+                    [day(11)].into()
+                } else {
+                    Default::default()
+                },
+                ..person(id)
+            })
+            .collect();
+        let algo = config::Algo::RoundRobin {
+            turn_length_days: 5,
+            start_person: None,
+        };
+        let options = algo::Options::default();
+        let single = run_algo(&algo, people.clone(), day(1), day(24), &options).unwrap();
+        let best = |seed: u64| {
+            let n = NonZeroUsize::new(8).unwrap();
+            best_of_candidates(n, &algo, people.clone(), day(1), day(24), &options, seed)
+                .unwrap()
+                .schedule
+        };
+
+        let turns = |schedule: &Schedule| {
+            schedule
+                .turns
+                .iter()
+                .map(|t| (schedule.people[t.person].id.clone(), t.start, t.end))
+                .collect::<Vec<_>>()
+        };
+
+        assert!(best(42).load_spread() < single.schedule.load_spread());
+        assert_eq!(turns(&best(42)), turns(&best(42)));
+    }
+
+    #[test]
+    fn test_run_algo_returns_trace() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob"].into_iter().map(person).collect();
+        let algo = config::Algo::Balanced {
+            min_turn_days: 2,
+            max_turn_days: 3,
+            preference_weight: None,
+            balance_weekends: false,
+        };
+        let run = |collect_trace: bool| {
+            let options = algo::Options {
+                collect_trace,
+                ..Default::default()
+            };
+            run_algo(&algo, people.clone(), day(1), day(11), &options).unwrap()
+        };
+
+        assert!(run(false).trace.is_none());
+        // Both people with both turn lengths for the first turn.
+        let trace = run(true).trace.unwrap();
+        assert_eq!(trace.iter().filter(|c| c.start == day(1)).count(), 4);
+    }
+
+    #[test]
+    fn test_run_algo_returns_rationale() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let people: Vec<Person> = ["alice", "bob"].into_iter().map(person).collect();
+        let algo = config::Algo::Greedy {
+            turn_length_days: 3,
+            preference_weight: None,
+        };
+        let run = |explain: bool| {
+            let options = algo::Options {
+                explain,
+                ..Default::default()
+            };
+            run_algo(&algo, people.clone(), day(1), day(10), &options).unwrap()
+        };
+
+        assert!(run(false).rationale.is_none());
+        let generated = run(true);
+        let rationale = generated.rationale.unwrap();
+        assert_eq!(rationale.len(), generated.schedule.turns.len());
+        assert_eq!(rationale[0].candidates.len(), 2);
+    }
+
+    #[test]
+    fn test_co_primary_turn() {
+        let config = config::parse_str(
+            r#"
+people:
+  alice:
+    name: Alice
+  bob:
+    name: Bob
+  carol:
+    name: Carol
+schedule:
+  from: 2025-01-01
+  to: 2025-01-29
+  algo: !Greedy
+    turn_length_days: 14
+  co_primary:
+    - { people: [alice, bob], from: 2025-01-15, to: 2025-01-29 }
+"#,
+        )
+        .unwrap();
+        let schedule = generate(config).unwrap();
+
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let shared: Vec<(&str, NaiveDate, NaiveDate)> = schedule
+            .turns
+            .iter()
+            .filter(|t| !t.co_primary.is_empty())
+            .map(|t| (schedule.people[t.person].id.as_str(), t.start, t.end))
+            .collect();
+        assert_eq!(
+            shared,
+            [("alice", day(15), day(22)), ("bob", day(22), day(29))]
+        );
+        // Each is credited half of the 14 days.
+        let days = |id: &str| {
+            shared
+                .iter()
+                .filter(|(person, _, _)| *person == id)
+                .map(|(_, start, end)| (*end - *start).num_days())
+                .sum::<i64>()
+        };
+        assert_eq!((days("alice"), days("bob")), (7, 7));
+
+        let ics = schedule.to_ics("On call");
+        assert!(ics.contains("SUMMARY:Alice (co-primary with Bob)"));
+        assert!(ics.contains("SUMMARY:Bob (co-primary with Alice)"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 3);
+        let yaml = schedule.to_yaml().unwrap();
+        assert!(yaml.contains("co_primary:\n  - bob"), "{}", yaml);
+    }
+}
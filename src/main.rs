@@ -1,56 +1,708 @@
-mod algo;
-mod config;
-mod input;
-mod output;
-
-use clap::{Parser, arg};
-use std::path::PathBuf;
-use crate::input::Person;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::{Path, PathBuf};
 use env_logger::Builder;
-use log::LevelFilter;
+use log::{LevelFilter, info, warn};
 use std::collections::HashMap;
-use chrono::TimeDelta;
-use crate::output::YamlSchedule;
+use std::num::NonZeroUsize;
+use chrono::{Days, NaiveDate, NaiveDateTime, TimeDelta};
 use std::fs;
+use turns::output::{YamlAssignment, YamlSchedule};
+use turns::{algo, config, input, output};
+
+/// How to group the turns when printing the schedule
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum GroupBy {
+    /// One section per person, listing all of their turns
+    Person,
+}
+
+/// Format of the availability heatmap
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum HeatmapFormat {
+    /// A grid with one character per day
+    Text,
+    /// One row per person and one column per day
+    Csv,
+}
+
+/// Format of the assignment ledger
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum LedgerFormat {
+    /// One dated line per change
+    Text,
+    /// JSON array of the changes
+    Json,
+}
+
+/// Serialization format of the schedule
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// YAML, readable back with `--previous`
+    Yaml,
+    /// JSON array of FullCalendar all-day events
+    Fullcalendar,
+    /// Plain text listing the ISO weeks each turn spans
+    Weeks,
+    /// Load and fairness metrics in the Prometheus text format
+    Prometheus,
+    /// iCalendar feed with an all-day event per turn
+    Ics,
+    /// One row per turn, for spreadsheets
+    Csv,
+    /// Markdown table of the turns, followed by the load of each person
+    Markdown,
+}
 
 /// Schedule people for on-call rotations
 #[derive(Parser, Debug)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
-    /// Configuration file
-    #[arg(short, long, default_value = "turns.yaml")]
+    /// Configuration file, or `-` to read it from stdin
+    #[arg(short, long, default_value = "turns.yaml", global = true)]
     config: PathBuf,
 
+    /// Verbose output (0=warn, 1=info, 2=debug, 3=trace)
+    #[arg(short, long, default_value = "0", global = true)]
+    verbose: u8,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    // Without a subcommand, `generate` is implied.
+    #[command(flatten)]
+    generate: GenerateArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generate a schedule (the default)
+    Generate(Box<GenerateArgs>),
+    /// Only parse and validate the config, and print a summary of it
+    Validate,
+    /// Print the statistics of a previously generated schedule
+    Stats {
+        /// Schedule file, as written with `--output`
+        #[arg(long)]
+        schedule: PathBuf,
+    },
+    /// Print who is on call and who is next in a schedule as JSON
+    Status {
+        /// Schedule file, as written with `--output`
+        schedule: PathBuf,
+        /// The time to check (YYYY-MM-DDTHH:MM:SS, default: now)
+        #[arg(long)]
+        at: Option<NaiveDateTime>,
+    },
+    /// Print the turns added, removed or changed from one schedule to the
+    /// next as dated entries, for an audit log
+    Ledger {
+        /// The schedule before the change
+        old: PathBuf,
+        /// The schedule after the change
+        new: PathBuf,
+        /// The date the entries are recorded on (YYYY-MM-DD, default: today)
+        #[arg(long)]
+        date: Option<NaiveDate>,
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: LedgerFormat,
+    },
+    /// Check whether generating with the config and the given flags gives
+    /// the same schedule every time, and name what to pin otherwise
+    Doctor {
+        /// Configuration file (default: `--config`)
+        #[arg(value_name = "CONFIG")]
+        config_file: Option<PathBuf>,
+        #[command(flatten)]
+        generate: Box<GenerateArgs>,
+    },
+}
+
+#[derive(Args, Debug)]
+struct GenerateArgs {
     /// Output file
     #[arg(short, long)]
     output: Option<PathBuf>,
 
+    /// Create the directory of the output file if it doesn't exist
+    #[arg(long, requires = "output")]
+    mkdir: bool,
+
+    /// Also write the days on call and number of turns of each person to
+    /// this file, as JSON with `--format fullcalendar` and as YAML otherwise
+    #[arg(long)]
+    output_load: Option<PathBuf>,
+
+    /// Print what `--output` or `--append` would write to stdout, with a
+    /// summary on stderr, without writing the file
+    #[arg(long)]
+    dry_run: bool,
+
     /// Output from a previous schedule, to calculate initial load
     #[arg(long)]
     previous: Option<PathBuf>,
 
-    /// Verbose output (0=warn, 1=info, 2=debug, 3=trace)
-    #[arg(short, long, default_value = "0")]
-    verbose: u8,
+    /// How much of the load from the previous schedule carries over, from
+    /// 0.0 (none) to 1.0 (all of it)
+    #[arg(long, default_value = "1.0", value_parser = parse_load_decay)]
+    load_decay: f64,
+
+    /// Schedule file to extend: new turns start after its last covered day,
+    /// using it as history, and are appended to the same file
+    #[arg(long, conflicts_with_all = ["output", "previous", "merge_edits"])]
+    append: Option<PathBuf>,
+
+    /// Keep the manual edits made to ORIGINAL in EDITED before `--cutoff`,
+    /// using the newly generated turns from then on
+    #[arg(long, num_args = 2, value_names = ["ORIGINAL", "EDITED"], requires = "cutoff")]
+    merge_edits: Option<Vec<PathBuf>>,
+
+    /// First day of the regenerated part of the schedule (YYYY-MM-DD)
+    #[arg(long, requires = "merge_edits")]
+    cutoff: Option<NaiveDate>,
+
+    /// Print the turns that were added, removed or changed compared to this
+    /// previously generated schedule to stderr
+    #[arg(long, value_name = "PREVIOUS")]
+    diff: Option<PathBuf>,
+
+    /// Merge back-to-back turns of the same person into one before output
+    #[arg(long)]
+    coalesce: bool,
+
+    /// Output format (default: YAML for files, plain text on the console)
+    #[arg(long, value_enum)]
+    format: Option<Format>,
+
+    /// Name of the calendar with `--format ics`
+    #[arg(long, default_value = "On call")]
+    calendar_name: String,
+
+    /// Split the YAML output into the `published` turns before this date
+    /// (YYYY-MM-DD) and the `tentative` ones from it on
+    #[arg(long, conflicts_with = "append")]
+    tentative_after: Option<NaiveDate>,
+
+    /// Trim and lowercase person ids in the config and the previous schedule
+    #[arg(long)]
+    normalize_ids: bool,
+
+    /// Warn about preferences that can't all be honored together, like more
+    /// people wanting the same days than there are turns
+    #[arg(long)]
+    lint: bool,
+
+    /// Only validate the config and print a summary of it, without
+    /// generating a schedule
+    #[arg(long)]
+    check: bool,
+
+    /// Treat people without OOO or preferences as available without warning
+    #[arg(long)]
+    assume_available: bool,
+
+    /// Start the schedule on the given day (YYYY-MM-DD) instead of the
+    /// configured `from`
+    #[arg(long, conflicts_with = "rolling")]
+    from: Option<NaiveDate>,
+
+    /// End the schedule on the given day (YYYY-MM-DD) instead of the
+    /// configured `to`, included if `to_inclusive` is set
+    #[arg(long, conflicts_with = "rolling")]
+    to: Option<NaiveDate>,
+
+    /// Schedule the given number of days (e.g. `30d`) from today instead of
+    /// the configured range, counting only the `--previous` turns before today
+    #[arg(long, value_parser = parse_days)]
+    rolling: Option<u64>,
+
+    /// Keep the `--previous` turns starting in the given number of days (e.g.
+    /// `7d`) from today as they are, only regenerating the later ones
+    #[arg(long, value_parser = parse_days, requires = "previous")]
+    freeze: Option<u64>,
+
+    /// Keep the `--previous` turns before the given day (YYYY-MM-DD) as they
+    /// are, splitting the one that straddles it, and only regenerate the
+    /// schedule from that day on
+    #[arg(long, requires = "previous", conflicts_with = "freeze")]
+    freeze_before: Option<NaiveDate>,
+
+    /// The day `--rolling`, `--freeze` and `min_notice_days` count from
+    /// (YYYY-MM-DD, default: the current date)
+    #[arg(long)]
+    today: Option<NaiveDate>,
+
+    /// Without a `shuffle_seed` in the config, shuffle the rotation order with
+    /// a seed derived from the parsed config, which only changes with it
+    #[arg(long)]
+    seed_from_config_hash: bool,
+
+    /// Break ties between equally good candidates of the Greedy and Balanced
+    /// algorithms randomly with this seed, instead of in favor of the first
+    /// person in the rotation (or with a new seed with `random_tie_break`)
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Generate this many schedules, shuffling the rotation order of all but
+    /// the first, and keep the fairest one
+    #[arg(long, default_value = "1")]
+    candidates: NonZeroUsize,
+
+    /// Fail instead of warning when turns don't give `min_notice_days` of
+    /// notice
+    #[arg(long)]
+    strict: bool,
+
+    /// Fail instead of warning when nobody is on call on some days outside
+    /// of the `no_coverage` periods
+    #[arg(long)]
+    strict_coverage: bool,
+
+    /// Align turns to the end of the schedule instead of its start, so that a
+    /// shorter turn is at the start (RoundRobin and Greedy only)
+    #[arg(long)]
+    anchor_end: bool,
+
+    /// Abort when the schedule would have more turns than this
+    #[arg(long, default_value = "10000")]
+    max_turns: usize,
+
+    /// Group the printed schedule (default: chronological)
+    #[arg(long, value_enum)]
+    group_by: Option<GroupBy>,
+
+    /// Print turn lengths in weeks when possible (e.g. "2 weeks") instead of
+    /// always in days
+    #[arg(long)]
+    human_durations: bool,
+
+    /// Print every candidate turn the Balanced algorithm evaluates to stderr
+    #[arg(long)]
+    trace_candidates: bool,
+
+    /// Print why the Greedy algorithm gave each turn to its assignee to
+    /// stderr: the rule that decided it and the candidates with their load
+    #[arg(long)]
+    explain: bool,
+
+    /// After the Balanced algorithm, swap pairs of turns between people
+    /// while that makes the load more even
+    #[arg(long)]
+    optimize_swaps: bool,
+
+    /// Print a fairness grade (A-F) and statistics of the schedule to stderr
+    #[arg(long)]
+    stats: bool,
+
+    /// Print a one-sentence description of the schedule to stderr, e.g. for
+    /// wikis
+    #[arg(long)]
+    summary: bool,
+
+    /// Print everyone's availability over the schedule range, instead of
+    /// generating a schedule
+    #[arg(long, value_enum)]
+    heatmap: Option<HeatmapFormat>,
 }
 
-fn calculate_initial_load(previous_schedule_path: &PathBuf) -> Result<HashMap<String, TimeDelta>, String> {
+/// A seed derived from the config as parsed, with every override applied, so
+/// that comments and formatting don't change it. See [`output::stable_hash`].
+fn config_seed(cfg: &config::Config) -> Result<u64, serde_json::Error> {
+    // Going through a `Value` sorts the keys of the maps.
+    let canonical = serde_json::to_value(cfg)?.to_string();
+    Ok(output::stable_hash(canonical.as_bytes()))
+}
+
+/// What makes generating with `cfg` and `args` give a different schedule
+/// from one run to the next, or after unrelated edits to the config, and how
+/// to pin it. Empty when the schedule is reproducible.
+fn unpinned_randomness(cfg: &config::Config, args: &GenerateArgs) -> Vec<String> {
+    let mut sources = vec![];
+    if cfg.schedule.random_tie_break && args.seed.is_none() {
+        sources.push(
+            "Ties are broken randomly with a new seed on every run (`random_tie_break`); pin it with `--seed`"
+                .to_string(),
+        );
+    }
+    if cfg.schedule.shuffle_seed.is_none() && args.seed_from_config_hash {
+        let shuffled = if args.candidates.get() > 1 {
+            "The rotation order of every candidate"
+        } else {
+            "The rotation order"
+        };
+        sources.push(format!(
+            "{} is shuffled with a seed hashed from the config (`--seed-from-config-hash`), so any edit to the config reshuffles it; pin it with `shuffle_seed`",
+            shuffled
+        ));
+    }
+    if args.today.is_none() && (args.rolling.is_some() || args.freeze.is_some()) {
+        sources.push(
+            "`--rolling` and `--freeze` count from the current date; pin it with `--today`"
+                .to_string(),
+        );
+    }
+    sources
+}
+
+/// Parses a number of days, optionally suffixed with `d` (e.g. `30d`).
+fn parse_days(s: &str) -> Result<u64, String> {
+    s.strip_suffix('d')
+        .unwrap_or(s)
+        .parse()
+        .map_err(|_| format!("invalid number of days: {:?}", s))
+}
+
+/// Parses a load decay factor, between 0 and 1.
+fn parse_load_decay(s: &str) -> Result<f64, String> {
+    s.parse()
+        .ok()
+        .filter(|decay| (0.0..=1.0).contains(decay))
+        .ok_or_else(|| format!("invalid load decay: {:?}, expected 0.0 to 1.0", s))
+}
+
+/// The load of each person in the previous schedule, times `decay`. With
+/// `until`, only the days before it count, and the recorded debt is ignored
+/// since it also covers the later turns.
+fn calculate_initial_load(
+    previous_schedule_path: &PathBuf,
+    normalize_ids: bool,
+    until: Option<NaiveDate>,
+    decay: f64,
+) -> Result<HashMap<String, TimeDelta>, String> {
     let content = fs::read_to_string(previous_schedule_path)
         .map_err(|e| format!("Failed to read previous schedule file: {}", e))?;
     let previous_schedule: YamlSchedule = serde_yaml::from_str(&content)
         .map_err(|e| format!("Failed to parse previous schedule file: {}", e))?;
+    if let Some(metadata) = &previous_schedule.metadata {
+        info!("Previous schedule was generated with {:?}", metadata.algo);
+    }
+    let normalize = |id: &str| {
+        if !normalize_ids {
+            return id.to_string();
+        }
+        let normalized = config::normalize_id(id);
+        if normalized != id {
+            warn!(
+                "Person id {:?} in the previous schedule normalized to {:?}",
+                id, normalized
+            );
+        }
+        normalized
+    };
+
+    let decayed = |load: HashMap<String, TimeDelta>| {
+        load.into_iter()
+            .map(|(id, days)| {
+                let seconds = (days.num_seconds() as f64 * decay).round() as i64;
+                (id, TimeDelta::seconds(seconds))
+            })
+            .collect()
+    };
 
     let mut initial_load = HashMap::new();
+    // The debt accounts for the whole history relative to the fair share,
+    // including the people who got no turn at all.
+    let debt = previous_schedule.metadata.and_then(|m| m.debt);
+    if let Some(debt) = debt.filter(|_| until.is_none()) {
+        for (id, days) in debt {
+            let seconds = (days * 86_400.0).round() as i64;
+            *initial_load
+                .entry(normalize(&id))
+                .or_insert(TimeDelta::zero()) += TimeDelta::seconds(seconds);
+        }
+        return Ok(decayed(initial_load));
+    }
     for assignment in previous_schedule.schedule {
-        let duration = assignment.end - assignment.start;
-        *initial_load.entry(assignment.person.to_string()).or_insert(TimeDelta::zero()) += duration;
+        let end = until.map_or(assignment.end, |until| assignment.end.min(until));
+        if end <= assignment.start {
+            continue;
+        }
+        let duration = end - assignment.start;
+        *initial_load
+            .entry(normalize(assignment.person))
+            .or_insert(TimeDelta::zero()) += duration;
+    }
+    Ok(decayed(initial_load))
+}
+
+/// The `previous` turns that `--freeze` and `--freeze-before` keep as they
+/// are: those starting before `freeze_end`, except the ones over before
+/// `from`.
+fn frozen_turns<'a>(
+    previous: Vec<YamlAssignment<'a>>,
+    from: NaiveDate,
+    freeze_end: NaiveDate,
+) -> Vec<YamlAssignment<'a>> {
+    previous
+        .into_iter()
+        .filter(|a| a.start < freeze_end && a.end > from)
+        .collect()
+}
+
+/// The primary `frozen` turns cut at `frozen_until`, oldest first, for the
+/// new turns to carry rest, cooldowns and consecutive days over from.
+fn previous_turns(
+    frozen: &[YamlAssignment],
+    frozen_until: NaiveDate,
+) -> Vec<config::FixedAssignment> {
+    let mut turns: Vec<config::FixedAssignment> = frozen
+        .iter()
+        .filter(|a| a.rotation == 0 && a.start < frozen_until)
+        .map(|a| config::FixedAssignment {
+            person: a.person.to_string(),
+            from: a.start,
+            to: a.end.min(frozen_until),
+            co_primary: vec![],
+        })
+        .collect();
+    turns.sort_by_key(|t| t.from);
+    turns
+}
+
+/// Merges the manual edits made to a published schedule into `schedule`, see
+/// [`output::Schedule::merge_edits`].
+fn merge_edits(
+    schedule: output::Schedule,
+    original_path: &PathBuf,
+    edited_path: &PathBuf,
+    cutoff: NaiveDate,
+) -> Result<output::Schedule, String> {
+    let original = fs::read_to_string(original_path)
+        .map_err(|e| format!("Failed to read original schedule file: {}", e))?;
+    let original: YamlSchedule = serde_yaml::from_str(&original)
+        .map_err(|e| format!("Failed to parse original schedule file: {}", e))?;
+    let edited = fs::read_to_string(edited_path)
+        .map_err(|e| format!("Failed to read edited schedule file: {}", e))?;
+    let edited: YamlSchedule = serde_yaml::from_str(&edited)
+        .map_err(|e| format!("Failed to parse edited schedule file: {}", e))?;
+
+    let (merged, conflicts) = schedule.merge_edits(&original.schedule, &edited.schedule, cutoff);
+    for conflict in conflicts {
+        warn!("{}", conflict);
+    }
+    Ok(merged)
+}
+
+/// The changes from the schedule at `previous_path` to `schedule`, one per
+/// line, see [`output::Schedule::diff`].
+fn diff(schedule: &output::Schedule, previous_path: &PathBuf) -> Result<Vec<String>, String> {
+    let content = fs::read_to_string(previous_path)
+        .map_err(|e| format!("Failed to read previous schedule file: {}", e))?;
+    let previous: YamlSchedule = serde_yaml::from_str(&content)
+        .map_err(|e| format!("Failed to parse previous schedule file: {}", e))?;
+    Ok(schedule
+        .diff(&previous.schedule)
+        .iter()
+        .map(|change| change.to_string())
+        .collect())
+}
+
+/// A warning if `schedule` is less balanced than the `baseline` schedule.
+fn compare_with_baseline(
+    schedule: &output::Schedule,
+    baseline: &output::Schedule,
+) -> Option<String> {
+    let (spread, baseline_spread) = (schedule.load_spread(), baseline.load_spread());
+    (spread > baseline_spread).then(|| {
+        format!(
+            "The schedule is less balanced than plain round robin: days on call differ by up to {} instead of {}; check the preferences",
+            spread.num_days(),
+            baseline_spread.num_days()
+        )
+    })
+}
+
+/// A warning for every turn starting from `today` on, but less than
+/// `min_notice_days` after it.
+fn notice_warnings(
+    schedule: &output::Schedule,
+    today: NaiveDate,
+    min_notice_days: u32,
+) -> Vec<String> {
+    let notice_end = today + Days::new(min_notice_days.into());
+    schedule
+        .turns
+        .iter()
+        .filter(|turn| today <= turn.start && turn.start < notice_end)
+        .map(|turn| {
+            format!(
+                "{}'s turn starting on {} gives less than {} days of notice",
+                schedule.people[turn.person].name, turn.start, min_notice_days
+            )
+        })
+        .collect()
+}
+
+/// The on-call status of the schedule at `path`, see
+/// [`YamlSchedule::status`].
+fn status(path: &PathBuf, at: NaiveDateTime) -> Result<serde_json::Value, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let schedule: YamlSchedule = serde_yaml::from_str(&content).map_err(|e| e.to_string())?;
+    Ok(schedule.status(at))
+}
+
+/// The changes from the schedule at `old_path` to the one at `new_path` as a
+/// ledger recorded on `date`, see [`output::Schedule::ledger`].
+fn ledger(
+    old_path: &Path,
+    new_path: &Path,
+    date: NaiveDate,
+    format: LedgerFormat,
+) -> Result<String, String> {
+    let old = fs::read_to_string(old_path).map_err(|e| e.to_string())?;
+    let old: YamlSchedule = serde_yaml::from_str(&old).map_err(|e| e.to_string())?;
+    let new = fs::read_to_string(new_path).map_err(|e| e.to_string())?;
+    let new: YamlSchedule = serde_yaml::from_str(&new).map_err(|e| e.to_string())?;
+    let schedule = new.to_schedule();
+    let entries = schedule.ledger(&old.schedule, date);
+    match format {
+        LedgerFormat::Text => Ok(entries.iter().map(|entry| format!("{}\n", entry)).collect()),
+        LedgerFormat::Json => serde_json::to_string_pretty(&entries)
+            .map(|json| json + "\n")
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// Writes `content` to `path`, creating its directory first with `mkdir`.
+/// A missing directory is reported as such rather than as a bare "not
+/// found".
+fn write_output(path: &Path, content: &str, mkdir: bool) -> Result<(), String> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        if mkdir {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        } else if !dir.is_dir() {
+            return Err(format!(
+                "output directory does not exist: {} (create it or pass --mkdir)",
+                dir.display()
+            ));
+        }
+    }
+    fs::write(path, content).map_err(|e| e.to_string())
+}
+
+/// Writes `content` to `path` with [`write_output`], or with `dry_run`
+/// leaves `path` untouched and returns a summary of what would be written.
+fn write_or_preview(
+    path: &Path,
+    content: &str,
+    format: Format,
+    turns: usize,
+    dry_run: bool,
+    mkdir: bool,
+) -> Result<Option<String>, String> {
+    if !dry_run {
+        return write_output(path, content, mkdir).map(|_| None);
+    }
+    let format = format.to_possible_value().unwrap();
+    Ok(Some(format!(
+        "Dry run: would write {} turns as {} ({} bytes) to {}",
+        turns,
+        format.get_name(),
+        content.len(),
+        path.display()
+    )))
+}
+
+fn serialize(
+    schedule: &output::Schedule,
+    format: Format,
+    tentative_after: Option<NaiveDate>,
+    calendar_name: &str,
+) -> Result<String, String> {
+    match format {
+        Format::Yaml => match tentative_after {
+            Some(date) => schedule.to_yaml_partitioned(date),
+            None => schedule.to_yaml(),
+        }
+        .map_err(|e| format!("Error serializing to YAML: {}", e)),
+        Format::Fullcalendar => serde_json::to_string_pretty(&schedule.to_fullcalendar())
+            .map_err(|e| format!("Error serializing to JSON: {}", e)),
+        Format::Weeks => Ok(schedule.to_iso_weeks()),
+        Format::Prometheus => Ok(schedule.to_prometheus()),
+        Format::Ics => Ok(schedule.to_ics(calendar_name)),
+        Format::Csv => Ok(schedule.to_csv()),
+        Format::Markdown => Ok(schedule.to_markdown()),
+    }
+}
+
+/// The load summary written by `--output-load`, as JSON if the schedule is
+/// written as JSON and as YAML otherwise.
+fn serialize_load(schedule: &output::Schedule, format: Format) -> Result<String, String> {
+    let load = schedule.load_summary();
+    match format {
+        Format::Fullcalendar => serde_json::to_string_pretty(&load)
+            .map_err(|e| format!("Error serializing to JSON: {}", e)),
+        _ => serde_yaml::to_string(&load).map_err(|e| format!("Error serializing to YAML: {}", e)),
     }
-    Ok(initial_load)
+}
+
+/// Prints the candidates evaluated by the balanced algorithm, one per line.
+fn print_trace(schedule: &output::Schedule, trace: &[algo::balanced::Candidate]) {
+    for candidate in trace {
+        eprintln!(
+            "{} - {}\t{}\tpreference group {}\tvariance {:.3} days²",
+            candidate.start,
+            candidate.end,
+            schedule.people[candidate.person].name,
+            candidate.preference_group,
+            candidate.variance_days()
+        );
+    }
+}
+
+/// Prints why each turn went to its assignee, one per line.
+fn print_rationale(schedule: &output::Schedule, rationale: &[algo::greedy::TurnRationale]) {
+    for turn in rationale {
+        let candidates: Vec<String> = turn
+            .candidates
+            .iter()
+            .map(|c| {
+                format!(
+                    "{} ({:.1} days, preference group {})",
+                    schedule.people[c.person].name,
+                    c.load.num_seconds() as f64 / 86_400.0,
+                    c.preference_group
+                )
+            })
+            .collect();
+        eprintln!(
+            "{} - {}\t{}\t{}\t{}",
+            turn.start,
+            turn.end,
+            schedule.people[turn.person].name,
+            turn.reason,
+            candidates.join(", ")
+        );
+    }
+}
+
+/// Reads the config at `path`, or from stdin if it is `-`, with the
+/// overrides of the environment.
+fn read_config(path: &Path) -> Result<config::Config, String> {
+    let parsed = if path == Path::new("-") {
+        let content = std::io::read_to_string(std::io::stdin())
+            .map_err(|e| format!("Error reading config: {}", e))?;
+        config::parse_str(&content)
+    } else {
+        config::parse_path(path)
+    };
+    let mut cfg = parsed.map_err(|e| format!("Error parsing config: {}", e))?;
+    cfg.apply_env(|var| std::env::var(var).ok())
+        .map_err(|e| format!("Error parsing config: {}", e))?;
+    Ok(cfg)
+}
+
+/// The statistics of the schedule in the file at `path`.
+fn schedule_stats(path: &Path) -> Result<output::ScheduleStats, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let schedule: YamlSchedule = serde_yaml::from_str(&content).map_err(|e| e.to_string())?;
+    Ok(schedule.to_schedule().statistics())
 }
 
 fn main() {
-    let args = Cli::parse();
+    let cli = Cli::parse();
 
-    let log_level = match args.verbose {
+    let log_level = match cli.verbose {
         0 => LevelFilter::Warn,
         1 => LevelFilter::Info,
         2 => LevelFilter::Debug,
@@ -61,16 +713,160 @@ fn main() {
         .filter(None, log_level)
         .init();
 
-    let cfg = match config::parse(&args.config) {
-        Ok(cfg) => cfg,
+    let args = match cli.command {
+        Some(Command::Validate) => {
+            match read_config(&cli.config) {
+                Ok(cfg) => println!("{}", cfg.summary()),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Command::Stats { schedule }) => {
+            match schedule_stats(&schedule) {
+                Ok(stats) => println!("{}", stats),
+                Err(e) => {
+                    eprintln!("Error reading schedule: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Command::Status { schedule, at }) => {
+            let at = at.unwrap_or_else(|| chrono::Local::now().naive_local());
+            match status(&schedule, at) {
+                Ok(status) => println!("{}", status),
+                Err(e) => {
+                    eprintln!("Error reading schedule: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Command::Ledger {
+            old,
+            new,
+            date,
+            format,
+        }) => {
+            let date = date.unwrap_or_else(|| chrono::Local::now().date_naive());
+            match ledger(&old, &new, date, format) {
+                Ok(ledger) => print!("{}", ledger),
+                Err(e) => {
+                    eprintln!("Error reading schedule: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Command::Doctor {
+            config_file,
+            generate,
+        }) => {
+            let cfg = match read_config(config_file.as_deref().unwrap_or(&cli.config)) {
+                Ok(cfg) => cfg,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            };
+            let sources = unpinned_randomness(&cfg, &generate);
+            if sources.is_empty() {
+                println!("Deterministic: the same config and flags give the same schedule");
+                return;
+            }
+            println!("Not deterministic:");
+            for source in sources {
+                println!("  - {}", source);
+            }
+            std::process::exit(1);
+        }
+        Some(Command::Generate(args)) => *args,
+        None => cli.generate,
+    };
+
+    let mut cfg = match read_config(&cli.config) {
+        Ok(config) => config,
         Err(e) => {
-            eprintln!("Error parsing config: {}", e);
+            eprintln!("{}", e);
             std::process::exit(1);
         }
     };
+    if let Err(e) = cfg.override_range(args.from, args.to) {
+        eprintln!("Error parsing config: {}", e);
+        std::process::exit(1);
+    }
+    let today = args
+        .today
+        .unwrap_or_else(|| chrono::Local::now().date_naive());
+    if let Some(days) = args.rolling
+        && let Err(e) = cfg.set_range(today, today + Days::new(days))
+    {
+        eprintln!("Error parsing config: {}", e);
+        std::process::exit(1);
+    }
+    if args.normalize_ids
+        && let Err(e) = cfg.normalize_ids()
+    {
+        eprintln!("Error parsing config: {}", e);
+        std::process::exit(1);
+    }
+    if args.check {
+        println!("{}", cfg.summary());
+        return;
+    }
 
-    let initial_load = if let Some(previous_path) = &args.previous {
-        match calculate_initial_load(previous_path) {
+    for diagnostic in cfg.availability_diagnostics() {
+        if args.assume_available {
+            info!("{}", diagnostic);
+        } else {
+            warn!("{}", diagnostic);
+        }
+    }
+
+    let existing = args.append.as_ref().map(|path| {
+        fs::read_to_string(path).unwrap_or_else(|e| {
+            eprintln!("Error reading schedule to append to: {}", e);
+            std::process::exit(1);
+        })
+    });
+    let existing: Option<YamlSchedule> = existing.as_deref().map(|content| {
+        serde_yaml::from_str(content).unwrap_or_else(|e| {
+            eprintln!("Error parsing schedule to append to: {}", e);
+            std::process::exit(1);
+        })
+    });
+
+    let freeze_end = args
+        .freeze_before
+        .or(args.freeze.map(|days| today + Days::new(days)));
+    let previous = freeze_end.and(args.previous.as_ref()).map(|path| {
+        fs::read_to_string(path).unwrap_or_else(|e| {
+            eprintln!("Error reading previous schedule: {}", e);
+            std::process::exit(1);
+        })
+    });
+    let frozen: Vec<YamlAssignment> = match (previous.as_deref(), freeze_end) {
+        (Some(content), Some(freeze_end)) => {
+            let previous: YamlSchedule = serde_yaml::from_str(content).unwrap_or_else(|e| {
+                eprintln!("Error parsing previous schedule: {}", e);
+                std::process::exit(1);
+            });
+            frozen_turns(previous.schedule, cfg.schedule.from, freeze_end)
+        }
+        _ => vec![],
+    };
+    // `--freeze-before` cuts the turn straddling the day, `--freeze` keeps it.
+    let frozen_until = args.freeze_before.or(frozen.iter().map(|a| a.end).max());
+
+    // The schedule being appended to is also the history of the new turns.
+    let history = args.previous.as_ref().or(args.append.as_ref());
+    let initial_load = if let Some(previous_path) = history {
+        // Frozen turns count as history, the ones being replaced don't.
+        let until = frozen_until.or(args.rolling.map(|_| today));
+        match calculate_initial_load(previous_path, args.normalize_ids, until, args.load_decay) {
             Ok(load) => Some(load),
             Err(e) => {
                 eprintln!("Error processing previous schedule: {}", e);
@@ -81,47 +877,326 @@ fn main() {
         None
     };
 
-    let people: Vec<Person> = cfg.people.iter().map(|p| p.into()).collect();
-    let start = cfg.schedule.from;
-    let end = cfg.schedule.to;
-
-    let output = match cfg.schedule.algo {
-        config::Algo::RoundRobin { turn_length_days } => {
-            algo::roundrobin::schedule(people, start, end, turn_length_days, initial_load)
+    if let Some(format) = args.heatmap {
+        let people = turns::people(&cfg);
+        let heatmap = input::Heatmap::new(&people, cfg.schedule.from, cfg.schedule.end());
+        match format {
+            HeatmapFormat::Text => print!("{}", heatmap),
+            HeatmapFormat::Csv => print!("{}", heatmap.to_csv()),
+        }
+        return;
+    }
+    let seed = match cfg.schedule.shuffle_seed {
+        Some(seed) => Some(seed),
+        None if args.seed_from_config_hash => match config_seed(&cfg) {
+            Ok(seed) => Some(seed),
+            Err(e) => {
+                eprintln!("Error hashing config: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+    let people = turns::rotation(&cfg, seed);
+    for warning in input::availability_warnings(
+        &people,
+        cfg.schedule.from,
+        cfg.schedule.end(),
+        cfg.schedule.algo.min_turn_days(),
+    ) {
+        warn!("{}", warning);
+    }
+    if args.lint {
+        for warning in input::preference_conflicts(
+            &people,
+            cfg.schedule.from,
+            cfg.schedule.end(),
+            cfg.schedule.algo.min_turn_days(),
+        ) {
+            warn!("{}", warning);
         }
+    }
+    let mut start = cfg.schedule.from;
+    let end = cfg.schedule.end();
+    let last_day = existing
+        .iter()
+        .flat_map(|e| &e.schedule)
+        .map(|a| a.end)
+        .max();
+    if let Some(last_day) = last_day {
+        if last_day >= end {
+            eprintln!(
+                "Error appending to schedule: it already covers up to {} and the schedule ends on {}",
+                last_day, end
+            );
+            std::process::exit(1);
+        }
+        start = start.max(last_day);
+    }
+    if let Some(frozen_until) = frozen_until {
+        start = start.max(frozen_until);
+    }
+    if args.anchor_end
+        && !matches!(
+            cfg.schedule.algo,
+            config::Algo::RoundRobin { .. } | config::Algo::Greedy { .. }
+        )
+    {
+        eprintln!("Error: --anchor-end only applies to the RoundRobin and Greedy algorithms");
+        std::process::exit(1);
+    }
+    if args.explain && !matches!(cfg.schedule.algo, config::Algo::Greedy { .. }) {
+        eprintln!("Error: --explain only applies to the Greedy algorithm");
+        std::process::exit(1);
+    }
+    let options = algo::Options {
+        initial_load,
+        max_turns: Some(args.max_turns),
+        collect_trace: args.trace_candidates,
+        explain: args.explain,
+        optimize_swaps: args.optimize_swaps,
+        anchor_end: args.anchor_end,
+        tie_break_seed: args.seed.or_else(|| {
+            cfg.schedule.random_tie_break.then(|| {
+                let seed = rand::random();
+                info!(
+                    "Breaking ties with seed {}, pass `--seed {}` to reproduce",
+                    seed, seed
+                );
+                seed
+            })
+        }),
+        previous_turns: frozen_until.map_or_else(Vec::new, |until| previous_turns(&frozen, until)),
+        ..turns::options(&cfg)
+    };
+
+    // Plain round robin on the same input, to check that greedy's
+    // preference handling doesn't cost more fairness than it should.
+    let baseline = match cfg.schedule.algo {
         config::Algo::Greedy {
-            turn_length_days,
-            preference_weight,
-        } => algo::greedy::schedule(people, start, end, turn_length_days, preference_weight, initial_load),
-        config::Algo::Balanced {
-            min_turn_days,
-            max_turn_days,
-        } => algo::balanced::schedule(people, start, end, min_turn_days, max_turn_days, initial_load),
+            turn_length_days, ..
+        } if args.stats && cfg.schedule.rotations == 1 => {
+            algo::roundrobin::schedule(people.clone(), start, end, turn_length_days, None, &options)
+                .ok()
+        }
+        _ => None,
     };
 
+    let rotations = cfg.schedule.rotations;
+    let output = algo::with_rotations(rotations, people, &options, |people, options| {
+        let generated = if args.candidates.get() > 1 {
+            turns::best_of_candidates(
+                args.candidates,
+                &cfg.schedule.algo,
+                people,
+                start,
+                end,
+                options,
+                seed.unwrap_or(0),
+            )
+        } else {
+            turns::run_algo(&cfg.schedule.algo, people, start, end, options)
+        }?;
+        if let Some(trace) = &generated.trace {
+            print_trace(&generated.schedule, trace);
+        }
+        if let Some(rationale) = &generated.rationale {
+            print_rationale(&generated.schedule, rationale);
+        }
+        Ok(generated.schedule)
+    });
+
     match output {
         Ok(schedule) => {
-            if let Some(output_path) = args.output {
-                match schedule.to_yaml() {
-                    Ok(yaml) => {
-                        if let Err(e) = std::fs::write(output_path, yaml) {
-                            eprintln!("Error writing to output file: {}", e);
+            let schedule = match (&args.merge_edits, args.cutoff) {
+                (Some(paths), Some(cutoff)) => {
+                    match merge_edits(schedule, &paths[0], &paths[1], cutoff) {
+                        Ok(merged) => merged,
+                        Err(e) => {
+                            eprintln!("Error merging edits: {}", e);
                             std::process::exit(1);
                         }
                     }
+                }
+                _ => schedule,
+            };
+            let mut schedule = match frozen_until {
+                Some(frozen_until) => {
+                    let (schedule, conflicts) =
+                        schedule.merge_edits(&frozen, &frozen, frozen_until);
+                    // The frozen turns aren't edited, only unknown people conflict.
+                    for conflict in conflicts {
+                        if let output::MergeConflict::UnknownPerson { person, start, end } =
+                            conflict
+                        {
+                            warn!(
+                                "Frozen turn {} - {} refers to unknown person {} and was dropped",
+                                start, end, person
+                            );
+                        }
+                    }
+                    schedule
+                }
+                None => schedule,
+            };
+            if args.coalesce {
+                schedule.coalesce();
+            }
+            for (a, b) in schedule.overlaps() {
+                warn!(
+                    "Turns {} - {} and {} - {} overlap",
+                    schedule.turns[a].start,
+                    schedule.turns[a].end,
+                    schedule.turns[b].start,
+                    schedule.turns[b].end
+                );
+            }
+            // The `no_coverage` periods are meant to be gaps.
+            let uncovered = cfg.schedule.uncovered_days();
+            let gaps: Vec<_> = schedule
+                .coverage_gaps(start, end)
+                .into_iter()
+                .filter(|&(from, to)| {
+                    from.iter_days()
+                        .take_while(|&day| day < to)
+                        .any(|day| !uncovered.contains(&day))
+                })
+                .collect();
+            for (from, to) in &gaps {
+                warn!("Nobody is on call from {} to {}", from, to);
+            }
+            if args.strict_coverage && !gaps.is_empty() {
+                eprintln!("Error: some days have nobody on call");
+                std::process::exit(1);
+            }
+            if let Some(min_notice_days) = cfg.schedule.min_notice_days {
+                let warnings = notice_warnings(&schedule, today, min_notice_days);
+                for warning in &warnings {
+                    warn!("{}", warning);
+                }
+                if args.strict && !warnings.is_empty() {
+                    eprintln!("Error: some turns don't give enough notice");
+                    std::process::exit(1);
+                }
+            }
+            if args.stats {
+                eprintln!("{}", schedule.report_card());
+                eprintln!("{}", schedule.statistics());
+                if let Some(groups) = &cfg.groups {
+                    eprint!("{}", schedule.group_report(groups));
+                }
+            }
+            if args.summary {
+                eprintln!("{}", schedule.rotation_summary());
+            }
+            if let Some(path) = &args.diff {
+                match diff(&schedule, path) {
+                    Ok(changes) if changes.is_empty() => eprintln!("No changes"),
+                    Ok(changes) => {
+                        for change in changes {
+                            eprintln!("{}", change);
+                        }
+                    }
                     Err(e) => {
-                        eprintln!("Error serializing to YAML: {}", e);
+                        eprintln!("Error comparing schedules: {}", e);
                         std::process::exit(1);
                     }
                 }
-            } else if args.verbose > 0 {
-                match schedule.to_yaml() {
-                    Ok(yaml) => println!("{}", yaml),
+            }
+            if let Some(warning) = baseline.and_then(|b| compare_with_baseline(&schedule, &b)) {
+                warn!("{}", warning);
+            }
+            // Like the output, left untouched by a dry run.
+            if let Some(path) = &args.output_load
+                && !args.dry_run
+            {
+                let format = args.format.unwrap_or(Format::Yaml);
+                let written = serialize_load(&schedule, format)
+                    .and_then(|load| write_output(path, &load, args.mkdir));
+                if let Err(e) = written {
+                    eprintln!("Error writing the load summary: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            if let (Some(path), Some(existing)) = (&args.append, existing) {
+                let turns = existing.schedule.len() + schedule.turns.len();
+                match schedule.append_to_yaml(existing.schedule) {
+                    Ok(serialized) => {
+                        match write_or_preview(
+                            path,
+                            &serialized,
+                            Format::Yaml,
+                            turns,
+                            args.dry_run,
+                            false,
+                        ) {
+                            Ok(Some(summary)) => {
+                                eprintln!("{}", summary);
+                                print!("{}", serialized);
+                            }
+                            Ok(None) => {}
+                            Err(e) => {
+                                eprintln!("Error writing to output file: {}", e);
+                                std::process::exit(1);
+                            }
+                        }
+                    }
                     Err(e) => {
                         eprintln!("Error serializing to YAML: {}", e);
                         std::process::exit(1);
                     }
                 }
+            } else if let Some(output_path) = args.output {
+                match serialize(
+                    &schedule,
+                    args.format.unwrap_or(Format::Yaml),
+                    args.tentative_after,
+                    &args.calendar_name,
+                ) {
+                    Ok(serialized) => match write_or_preview(
+                        &output_path,
+                        &serialized,
+                        args.format.unwrap_or(Format::Yaml),
+                        schedule.turns.len(),
+                        args.dry_run,
+                        args.mkdir,
+                    ) {
+                        Ok(Some(summary)) => {
+                            eprintln!("{}", summary);
+                            print!("{}", serialized);
+                        }
+                        Ok(None) => {}
+                        Err(e) => {
+                            eprintln!("Error writing to output file: {}", e);
+                            std::process::exit(1);
+                        }
+                    },
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                }
+            } else if args.format.is_some() || cli.verbose > 0 {
+                match serialize(
+                    &schedule,
+                    args.format.unwrap_or(Format::Yaml),
+                    args.tentative_after,
+                    &args.calendar_name,
+                ) {
+                    Ok(serialized) => println!("{}", serialized),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                }
+            } else if args.group_by == Some(GroupBy::Person) {
+                if args.human_durations {
+                    print!("{:#}", schedule.roster());
+                } else {
+                    print!("{}", schedule.roster());
+                }
+            } else if args.human_durations {
+                println!("{:#}", schedule);
             } else {
                 println!("{}", schedule);
             }
@@ -132,3 +1207,507 @@ fn main() {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+    use turns::input::{Person, PreferenceType};
+
+    fn person(id: &str) -> Person {
+        Person {
+            id: id.to_string(),
+            name: id.to_string(),
+            ooo: Default::default(),
+            preferences: HashMap::new(),
+            contact: Default::default(),
+            weight: 1.0,
+            max_days: None,
+            turn_length_days: None,
+        }
+    }
+
+    fn write_previous_schedule(content: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "{}", content).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_initial_load_with_normalized_ids() {
+        let file = write_previous_schedule(
+            r#"
+schedule:
+  - person: "Alice "
+    start: 2025-01-01
+    end: 2025-01-08
+  - person: bob
+    start: 2025-01-08
+    end: 2025-01-10
+"#,
+        );
+
+        let load = calculate_initial_load(&file.path().to_path_buf(), true, None, 1.0).unwrap();
+        assert_eq!(load["alice"], TimeDelta::days(7));
+        assert_eq!(load["bob"], TimeDelta::days(2));
+
+        let load = calculate_initial_load(&file.path().to_path_buf(), false, None, 1.0).unwrap();
+        assert!(!load.contains_key("alice"));
+        assert_eq!(load["Alice "], TimeDelta::days(7));
+    }
+
+    #[test]
+    fn test_load_decay() {
+        let file = write_previous_schedule(
+            r#"
+schedule:
+  - person: alice
+    start: 2025-01-01
+    end: 2025-01-08
+  - person: bob
+    start: 2025-01-08
+    end: 2025-01-10
+"#,
+        );
+        let load = |decay| calculate_initial_load(&file.path().to_path_buf(), false, None, decay);
+
+        let halved = load(0.5).unwrap();
+        assert_eq!(halved["alice"], TimeDelta::hours(84));
+        assert_eq!(halved["bob"], TimeDelta::days(1));
+        assert_eq!(load(1.0).unwrap()["alice"], TimeDelta::days(7));
+        assert!(load(0.0).unwrap().values().all(|l| l.is_zero()));
+
+        assert_eq!(parse_load_decay("0.5"), Ok(0.5));
+        assert!(parse_load_decay("1.5").is_err());
+        assert!(parse_load_decay("-0.1").is_err());
+    }
+
+    #[test]
+    fn test_debt_is_carried_over() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let person = |id: &str, ooo: &[NaiveDate]| Person {
+            ooo: ooo.iter().copied().collect(),
+            ..person(id)
+        };
+        // With Alice away for three weeks, Bob can't avoid covering two.
+        let alice_away: Vec<NaiveDate> = (1..=21).map(day).collect();
+        let people = vec![
+            person("alice", &alice_away),
+            person("bob", &[]),
+            person("charlie", &[]),
+        ];
+        let options = algo::Options::default();
+        let january = algo::greedy::schedule(people, day(1), day(29), 7, None, &options).unwrap();
+        let file = write_previous_schedule(&january.to_yaml().unwrap());
+
+        let load = calculate_initial_load(&file.path().to_path_buf(), false, None, 1.0).unwrap();
+        assert!(load["bob"] > TimeDelta::zero());
+        assert!(load["alice"] < TimeDelta::zero());
+
+        let people = vec![
+            person("alice", &[]),
+            person("bob", &[]),
+            person("charlie", &[]),
+        ];
+        let options = algo::Options {
+            initial_load: Some(load),
+            ..Default::default()
+        };
+        let february = algo::greedy::schedule(people, day(1), day(22), 7, None, &options).unwrap();
+        assert!(february.turns.iter().all(|t| t.person != 1));
+    }
+
+    #[test]
+    fn test_rolling_window() {
+        let args = Cli::try_parse_from(["turns", "--rolling", "30d", "--today", "2025-01-10"])
+            .unwrap()
+            .generate;
+        let today = args.today.unwrap();
+        assert_eq!(args.rolling, Some(30));
+        assert!(Cli::try_parse_from(["turns", "--rolling", "a month"]).is_err());
+
+        // Yesterday's run: Bob's turn from today on was only planned.
+        let file = write_previous_schedule(
+            r#"
+schedule:
+  - person: alice
+    start: 2025-01-03
+    end: 2025-01-10
+  - person: bob
+    start: 2025-01-10
+    end: 2025-01-17
+"#,
+        );
+        let load =
+            calculate_initial_load(&file.path().to_path_buf(), false, Some(today), 1.0).unwrap();
+        assert_eq!(load["alice"], TimeDelta::days(7));
+        assert!(!load.contains_key("bob"));
+
+        let people = ["alice", "bob"].iter().map(|id| person(id)).collect();
+        let options = algo::Options {
+            initial_load: Some(load),
+            ..Default::default()
+        };
+        let end = today + Days::new(30);
+        let schedule = algo::greedy::schedule(people, today, end, 7, None, &options).unwrap();
+        assert_eq!(schedule.turns[0].start, today);
+        assert_eq!(schedule.turns[0].person, 1);
+        assert_eq!(schedule.turns.last().unwrap().end, end);
+    }
+
+    #[test]
+    fn test_range_overrides() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(
+            file,
+            r#"
+people:
+  alice:
+    name: Alice
+    ooo:
+      - !Day 2025-01-10
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+"#
+        )
+        .unwrap();
+        let parse = |flags: &[&str]| {
+            let args = Cli::try_parse_from([&["turns"], flags].concat())
+                .unwrap()
+                .generate;
+            let mut cfg = config::parse_path(file.path()).unwrap();
+            cfg.override_range(args.from, args.to).map(|_| cfg)
+        };
+
+        // Alice's OOO day falls outside the new range.
+        let cfg = parse(&["--from", "2025-02-01", "--to", "2025-03-01"]).unwrap();
+        let first = |month| NaiveDate::from_ymd_opt(2025, month, 1).unwrap();
+        assert_eq!((cfg.schedule.from, cfg.schedule.to), (first(2), first(3)));
+
+        let result = parse(&["--to", "2024-12-31"]);
+        assert!(matches!(result, Err(config::ConfigError::InvalidDateRange)));
+        let rolling = ["turns", "--from", "2025-02-01", "--rolling", "30d"];
+        assert!(Cli::try_parse_from(rolling).is_err());
+    }
+
+    #[test]
+    fn test_compare_with_baseline() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        // Alice and Bob want every day, so greedy never picks Carol.
+        let wants_everything: HashMap<NaiveDate, PreferenceType> =
+            (1..=28).map(|d| (day(d), PreferenceType::Want)).collect();
+        let person = |id: &str, preferences: &HashMap<NaiveDate, PreferenceType>| Person {
+            preferences: preferences.clone(),
+            ..person(id)
+        };
+        let people = vec![
+            person("alice", &wants_everything),
+            person("bob", &wants_everything),
+            person("carol", &HashMap::new()),
+        ];
+        let options = algo::Options::default();
+
+        let greedy =
+            algo::greedy::schedule(people.clone(), day(1), day(29), 7, None, &options).unwrap();
+        let baseline =
+            algo::roundrobin::schedule(people, day(1), day(29), 7, None, &options).unwrap();
+        let warning = compare_with_baseline(&greedy, &baseline).unwrap();
+        assert!(warning.contains("up to 14 instead of 7"), "{}", warning);
+        assert!(compare_with_baseline(&baseline, &baseline).is_none());
+    }
+
+    #[test]
+    fn test_freeze_keeps_near_term_turns() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let previous = r#"
+schedule:
+  - person: alice
+    start: 2025-01-01
+    end: 2025-01-08
+  - person: bob
+    start: 2025-01-08
+    end: 2025-01-15
+  - person: alice
+    start: 2025-01-15
+    end: 2025-01-22
+  - person: bob
+    start: 2025-01-22
+    end: 2025-01-29
+"#;
+        let previous: YamlSchedule = serde_yaml::from_str(previous).unwrap();
+        // Today is the 6th: Alice's turn is running and Bob's starts within
+        // the 7 days, the later turns get regenerated now that Carol joined.
+        let frozen = frozen_turns(previous.schedule, day(1), day(6) + Days::new(7));
+        assert_eq!(frozen.len(), 2);
+        // With `--freeze-before`, the new turns follow the cut one.
+        let turn = |person: &str, from, to| config::FixedAssignment {
+            person: person.to_string(),
+            from,
+            to,
+            co_primary: vec![],
+        };
+        assert_eq!(
+            previous_turns(&frozen, day(12)),
+            vec![turn("alice", day(1), day(8)), turn("bob", day(8), day(12))]
+        );
+
+        let people: Vec<Person> = ["alice", "bob", "carol"]
+            .iter()
+            .map(|id| person(id))
+            .collect();
+        let options = algo::Options {
+            initial_load: Some(HashMap::from([
+                ("alice".to_string(), TimeDelta::days(7)),
+                ("bob".to_string(), TimeDelta::days(7)),
+            ])),
+            previous_turns: previous_turns(&frozen, day(15)),
+            ..Default::default()
+        };
+        let schedule = algo::greedy::schedule(people, day(15), day(29), 7, None, &options).unwrap();
+        let (schedule, conflicts) = schedule.merge_edits(&frozen, &frozen, day(15));
+        assert!(conflicts.is_empty());
+
+        let turns: Vec<(&str, NaiveDate, NaiveDate)> = schedule
+            .turns
+            .iter()
+            .map(|t| (schedule.people[t.person].id.as_str(), t.start, t.end))
+            .collect();
+        assert_eq!(
+            turns,
+            vec![
+                ("alice", day(1), day(8)),
+                ("bob", day(8), day(15)),
+                ("carol", day(15), day(22)),
+                ("alice", day(22), day(29)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_notice_warnings() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let schedule = output::Schedule {
+            people: vec![person("alice"), person("bob")],
+            turns: [(0, 1, 6), (1, 6, 11), (0, 11, 16)]
+                .into_iter()
+                .map(|(person, start, end)| output::Assignment {
+                    person,
+                    start: day(start),
+                    end: day(end),
+                    rotation: 0,
+                    co_primary: vec![],
+                })
+                .collect(),
+            algo: None,
+        };
+
+        // Alice's running turn already started, Bob's starts tomorrow.
+        assert_eq!(
+            notice_warnings(&schedule, day(5), 3),
+            vec!["bob's turn starting on 2025-01-06 gives less than 3 days of notice"]
+        );
+        assert!(notice_warnings(&schedule, day(2), 3).is_empty());
+    }
+
+    #[test]
+    fn test_write_output_to_missing_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("schedules").join("january.yaml");
+
+        let error = write_output(&path, "schedule: []\n", false).unwrap_err();
+        assert_eq!(
+            error,
+            format!(
+                "output directory does not exist: {} (create it or pass --mkdir)",
+                dir.path().join("schedules").display()
+            )
+        );
+        assert!(!path.exists());
+
+        write_output(&path, "schedule: []\n", true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "schedule: []\n");
+    }
+
+    #[test]
+    fn test_dry_run_leaves_output_untouched() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("foo.yaml");
+        fs::write(&path, "schedule: []\n").unwrap();
+
+        let summary = write_or_preview(&path, "new content\n", Format::Yaml, 4, true, false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            summary,
+            format!(
+                "Dry run: would write 4 turns as yaml (12 bytes) to {}",
+                path.display()
+            )
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "schedule: []\n");
+
+        let written = write_or_preview(&path, "new content\n", Format::Yaml, 4, false, false);
+        assert_eq!(written, Ok(None));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new content\n");
+    }
+
+    #[test]
+    fn test_config_seed() {
+        let config = r#"
+people:
+  alice:
+    name: Alice
+  bob:
+    name: Bob
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin
+    turn_length_days: 7
+"#;
+        let seed = |content: &str| config_seed(&config::parse_str(content).unwrap()).unwrap();
+        assert_eq!(seed(config), seed(config));
+        // Comments and the order of the people don't matter.
+        let reordered = r#"
+# The January rotation.
+people:
+  bob:
+    name: Bob
+  alice:
+    name: Alice  # The lead.
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !RoundRobin { turn_length_days: 7 }
+"#;
+        assert_eq!(seed(config), seed(reordered));
+        let changed = config.replace("name: Alice", "name: Alice Smith");
+        assert_ne!(seed(config), seed(&changed));
+
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let rotation = |content: &str| {
+            let mut people: Vec<Person> = ["alice", "bob", "carol", "dave", "erin"]
+                .iter()
+                .map(|id| person(id))
+                .collect();
+            input::shuffle(&mut people, seed(content));
+            let options = algo::Options::default();
+            let schedule =
+                algo::roundrobin::schedule(people, day(1), day(29), 7, None, &options).unwrap();
+            assert!(schedule.overlaps().is_empty());
+            schedule
+                .people
+                .iter()
+                .map(|p| p.id.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rotation(config), rotation(config));
+        assert_ne!(rotation(config), rotation(&changed));
+    }
+
+    #[test]
+    fn test_output_load() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let person = |id: &str| Person {
+            name: id.to_uppercase(),
+            ..person(id)
+        };
+        let turn = |person, start, end| output::Assignment {
+            person,
+            start: day(start),
+            end: day(end),
+            rotation: 0,
+            co_primary: vec![],
+        };
+        let schedule = output::Schedule {
+            people: vec![person("alice"), person("bob"), person("carol")],
+            turns: vec![
+                turn(0, 1, 8),
+                turn(1, 8, 15),
+                turn(0, 15, 18),
+                turn(1, 18, 22),
+            ],
+            algo: None,
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("load.yaml");
+
+        let load = serialize_load(&schedule, Format::Yaml).unwrap();
+        write_output(&path, &load, false).unwrap();
+        let load: Vec<output::PersonLoad> =
+            serde_yaml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let summary: Vec<_> = load
+            .iter()
+            .map(|p| (p.id.as_str(), p.name.as_str(), p.days, p.turns))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("alice", "ALICE", 10, 2),
+                ("bob", "BOB", 11, 2),
+                ("carol", "CAROL", 0, 0)
+            ]
+        );
+        assert_eq!(load.iter().map(|p| p.days).sum::<i64>(), 21);
+
+        let json = serialize_load(&schedule, Format::Fullcalendar).unwrap();
+        let from_json: Vec<output::PersonLoad> = serde_json::from_str(&json).unwrap();
+        assert_eq!(from_json, load);
+    }
+
+    #[test]
+    fn test_unpinned_randomness() {
+        let config = |extra: &str| {
+            config::parse_str(&format!(
+                r#"
+people:
+  alice:
+    name: Alice
+  bob:
+    name: Bob
+schedule:
+  from: 2025-01-01
+  to: 2025-01-31
+  algo: !Greedy
+    turn_length_days: 7
+{extra}"#
+            ))
+            .unwrap()
+        };
+        let doctor = |cfg: &config::Config, flags: &[&str]| {
+            let cli = Cli::try_parse_from([&["turns", "doctor", "turns.yaml"], flags].concat());
+            match cli.unwrap().command {
+                Some(Command::Doctor { generate, .. }) => unpinned_randomness(cfg, &generate),
+                command => panic!("unexpected command {:?}", command),
+            }
+        };
+
+        let sources = doctor(&config("  random_tie_break: true\n"), &[]);
+        assert_eq!(sources.len(), 1);
+        assert!(sources[0].contains("`--seed`"), "{}", sources[0]);
+        let sources = doctor(
+            &config(""),
+            &["--seed-from-config-hash", "--rolling", "30d"],
+        );
+        assert_eq!(sources.len(), 2);
+
+        let pinned = config("  random_tie_break: true\n  shuffle_seed: 3\n");
+        let flags = [
+            "--seed",
+            "7",
+            "--seed-from-config-hash",
+            "--candidates",
+            "4",
+            "--rolling",
+            "30d",
+            "--today",
+            "2025-01-10",
+        ];
+        assert_eq!(doctor(&pinned, &flags), Vec::<String>::new());
+        assert_eq!(doctor(&config(""), &[]), Vec::<String>::new());
+    }
+}
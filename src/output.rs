@@ -1,7 +1,9 @@
+use crate::algo::balanced::calculate_load_variance;
+use crate::config::{Algo, Contact};
 use crate::input::Person;
-use chrono::{NaiveDate, TimeDelta};
+use chrono::{Datelike, IsoWeek, NaiveDate, NaiveDateTime, TimeDelta, Weekday};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Display, Formatter};
 use thiserror::Error;
 
@@ -9,38 +11,352 @@ use thiserror::Error;
 pub enum ScheduleError {
     #[error("No one is available on {0}")]
     NoOneAvailable(NaiveDate),
+    #[error("Schedule exceeds the maximum of {0} turns; check `from`/`to` and the turn length")]
+    TooManyTurns(usize),
 }
 
 #[derive(Debug)]
 pub struct Assignment {
-    pub(crate) person: usize,
-    pub(crate) start: NaiveDate,
-    pub(crate) end: NaiveDate,
+    pub person: usize,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// The parallel rotation of the turn: 0 for the primary on-call, 1 for
+    /// the backup, and so on.
+    pub rotation: u8,
+    /// For a co-primary turn, the other people covering a part of the same
+    /// turn window.
+    pub co_primary: Vec<usize>,
+}
+
+impl Assignment {
+    /// The ISO weeks of the first and last day covered by the turn.
+    pub fn iso_weeks(&self) -> (IsoWeek, IsoWeek) {
+        let last_day = self.end.pred_opt().unwrap().max(self.start);
+        (self.start.iso_week(), last_day.iso_week())
+    }
+
+    /// The parts of the turn before and from `date`, if any.
+    pub fn split_at(&self, date: NaiveDate) -> (Option<Assignment>, Option<Assignment>) {
+        let before = (self.start < date).then(|| Assignment {
+            person: self.person,
+            start: self.start,
+            end: self.end.min(date),
+            rotation: self.rotation,
+            co_primary: self.co_primary.clone(),
+        });
+        let after = (self.end > date).then(|| Assignment {
+            person: self.person,
+            start: self.start.max(date),
+            end: self.end,
+            rotation: self.rotation,
+            co_primary: self.co_primary.clone(),
+        });
+        (before, after)
+    }
 }
 
 #[derive(Debug)]
 pub struct Schedule {
-    pub(crate) people: Vec<Person>,
-    pub(crate) turns: Vec<Assignment>,
+    pub people: Vec<Person>,
+    pub turns: Vec<Assignment>,
+    /// The algorithm and parameters the schedule was generated with.
+    pub algo: Option<Algo>,
 }
 
 #[derive(Debug)]
 pub struct Load<'a> {
-    pub(crate) days: HashMap<&'a Person, TimeDelta>,
+    pub days: HashMap<&'a Person, TimeDelta>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub(crate) struct YamlAssignment<'a> {
+/// Displays a schedule grouped by person. Like [`Schedule`], it prints
+/// human-friendly durations with the alternate flag.
+pub struct Roster<'a> {
+    schedule: &'a Schedule,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct YamlAssignment<'a> {
     #[serde(borrow)]
-    pub(crate) person: &'a str,
-    pub(crate) start: NaiveDate,
-    pub(crate) end: NaiveDate,
+    pub person: &'a str,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// Only written for the backup rotations.
+    #[serde(default, skip_serializing_if = "is_primary")]
+    pub rotation: u8,
+    /// The other people of a co-primary turn, only written for those.
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub co_primary: Vec<&'a str>,
+}
+
+fn is_primary(rotation: &u8) -> bool {
+    *rotation == 0
+}
+
+/// Describes how a schedule was generated. Only the `debt` is used when the
+/// schedule is read back, the rest is informational.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct YamlMetadata {
+    pub algo: Algo,
+    /// Days on call of each person minus the fair share (the average): positive
+    /// when overloaded, negative when owed turns.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debt: Option<BTreeMap<String, f64>>,
+    /// Identifies the turns of the schedule, see [`Schedule::content_id`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_id: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
-pub(crate) struct YamlSchedule<'a> {
+pub struct YamlSchedule<'a> {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<YamlMetadata>,
     #[serde(borrow)]
-    pub(crate) schedule: Vec<YamlAssignment<'a>>,
+    pub schedule: Vec<YamlAssignment<'a>>,
+}
+
+/// A YAML schedule split at the date after which turns may still change.
+/// Unlike [`YamlSchedule`], it can't be read back.
+#[derive(Serialize, Debug)]
+pub struct YamlPartitionedSchedule<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<YamlMetadata>,
+    /// Turns before the date, which are firm.
+    pub published: Vec<YamlAssignment<'a>>,
+    /// Turns from the date on, which are subject to change.
+    pub tentative: Vec<YamlAssignment<'a>>,
+}
+
+/// A one-letter summary of how fair a schedule is, for people who won't read
+/// the numbers. See [`Schedule::report_card`] for the rubric.
+#[derive(Debug)]
+pub struct ReportCard {
+    pub grade: char,
+    /// Difference between the most and the fewest turns anyone has.
+    pub spread: usize,
+    /// Gini coefficient of the days on call: 0 is perfectly even, 1 is one
+    /// person doing everything.
+    pub gini: f64,
+    /// Share of the `Want`/`NotWant` days within the schedule that were
+    /// honored; 1.0 when there are none.
+    pub preferences_honored: f64,
+    /// Number of `Want`/`NotWant` days within the schedule.
+    pub preferences: usize,
+}
+
+/// Numbers to discuss the fairness of a schedule: see
+/// [`Schedule::statistics`].
+#[derive(Debug)]
+pub struct ScheduleStats {
+    /// One entry per person, in the order of the schedule's people.
+    pub people: Vec<PersonStats>,
+    /// Variance of the days on call, in days squared.
+    pub load_variance: f64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PersonStats {
+    pub name: String,
+    pub days: i64,
+    pub turns: usize,
+    /// Saturdays and Sundays on call.
+    pub weekend_days: i64,
+}
+
+/// The load of one person, as written by `--output-load`.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct PersonLoad {
+    pub id: String,
+    pub name: String,
+    pub days: i64,
+    pub turns: usize,
+}
+
+impl Display for ScheduleStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for person in &self.people {
+            writeln!(
+                f,
+                "{}: {} days in {} turns, {} on weekends",
+                person.name, person.days, person.turns, person.weekend_days
+            )?;
+        }
+        write!(f, "Load variance: {:.2} days²", self.load_variance)
+    }
+}
+
+impl Display for ReportCard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Grade {}: turn counts differ by up to {}, load Gini {:.2}, {:.0}% of preferences honored",
+            self.grade,
+            self.spread,
+            self.gini,
+            self.preferences_honored * 100.0
+        )
+    }
+}
+
+/// A manual edit that [`Schedule::merge_edits`] couldn't keep.
+#[derive(Debug, PartialEq)]
+pub enum MergeConflict {
+    /// The edit reaches past the cutoff, where the regenerated turns win.
+    AfterCutoff {
+        person: String,
+        start: NaiveDate,
+        end: NaiveDate,
+    },
+    /// The edited turn is assigned to someone who isn't scheduled anymore.
+    UnknownPerson {
+        person: String,
+        start: NaiveDate,
+        end: NaiveDate,
+    },
+}
+
+/// A difference between a previous schedule and a regenerated one, see
+/// [`Schedule::diff`].
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(tag = "change", rename_all = "lowercase")]
+pub enum TurnChange<'a> {
+    Added(YamlAssignment<'a>),
+    Removed(YamlAssignment<'a>),
+    /// The turn was reassigned or its boundaries moved, or both.
+    Changed {
+        previous: YamlAssignment<'a>,
+        current: YamlAssignment<'a>,
+    },
+}
+
+impl TurnChange<'_> {
+    fn rotation(&self) -> u8 {
+        match self {
+            TurnChange::Added(turn) | TurnChange::Removed(turn) => turn.rotation,
+            TurnChange::Changed { current, .. } => current.rotation,
+        }
+    }
+}
+
+impl Display for TurnChange<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let rotation = self.rotation();
+        match self {
+            TurnChange::Added(turn) => {
+                write!(f, "Added {} - {} for {}", turn.start, turn.end, turn.person)?
+            }
+            TurnChange::Removed(turn) => write!(
+                f,
+                "Removed {} - {} of {}",
+                turn.start, turn.end, turn.person
+            )?,
+            TurnChange::Changed { previous, current } if previous.person == current.person => {
+                write!(
+                    f,
+                    "Moved {} - {} of {} to {} - {}",
+                    previous.start, previous.end, current.person, current.start, current.end
+                )?
+            }
+            TurnChange::Changed { previous, current }
+                if (previous.start, previous.end) == (current.start, current.end) =>
+            {
+                write!(
+                    f,
+                    "Changed {} - {} from {} to {}",
+                    current.start, current.end, previous.person, current.person
+                )?
+            }
+            TurnChange::Changed { previous, current } => write!(
+                f,
+                "Changed {} - {} of {} to {} - {} of {}",
+                previous.start,
+                previous.end,
+                previous.person,
+                current.start,
+                current.end,
+                current.person
+            )?,
+        }
+        if rotation > 0 {
+            write!(f, " [backup {}]", rotation)?;
+        }
+        Ok(())
+    }
+}
+
+/// A [`TurnChange`] recorded on `date`, for an append-only audit log of who
+/// was on call when. See [`Schedule::ledger`].
+#[derive(Serialize, Debug, PartialEq)]
+pub struct LedgerEntry<'a> {
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    pub change: TurnChange<'a>,
+}
+
+impl Display for LedgerEntry<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let days = |turn: &YamlAssignment| {
+            format!(
+                "{}–{}",
+                turn.start.format("%-m/%-d"),
+                turn.end.format("%-m/%-d")
+            )
+        };
+        write!(f, "{}: ", self.date)?;
+        match &self.change {
+            TurnChange::Added(turn) => write!(f, "added {} {}", turn.person, days(turn))?,
+            TurnChange::Removed(turn) => write!(f, "removed {} {}", turn.person, days(turn))?,
+            TurnChange::Changed { previous, current } if previous.person == current.person => {
+                write!(
+                    f,
+                    "moved {} {} to {}",
+                    current.person,
+                    days(previous),
+                    days(current)
+                )?
+            }
+            TurnChange::Changed { previous, current }
+                if (previous.start, previous.end) == (current.start, current.end) =>
+            {
+                write!(
+                    f,
+                    "changed {} from {} to {}",
+                    days(current),
+                    previous.person,
+                    current.person
+                )?
+            }
+            TurnChange::Changed { previous, current } => write!(
+                f,
+                "changed {} of {} to {} of {}",
+                days(previous),
+                previous.person,
+                days(current),
+                current.person
+            )?,
+        }
+        let rotation = self.change.rotation();
+        if rotation > 0 {
+            write!(f, " [backup {}]", rotation)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for MergeConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MergeConflict::AfterCutoff { person, start, end } => write!(
+                f,
+                "Manual edit {} - {} for {} is past the cutoff and was replaced",
+                start, end, person
+            ),
+            MergeConflict::UnknownPerson { person, start, end } => write!(
+                f,
+                "Manual edit {} - {} refers to unknown person {}",
+                start, end, person
+            ),
+        }
+    }
 }
 
 impl Schedule {
@@ -54,21 +370,707 @@ impl Schedule {
         Load { days }
     }
 
-    pub(crate) fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
-        let assignments: Vec<YamlAssignment> = self
+    /// The days on call of the members of each group, by group name. Ids that
+    /// aren't in the schedule count as no load.
+    pub fn group_load(&self, groups: &HashMap<String, Vec<String>>) -> HashMap<String, TimeDelta> {
+        let load: HashMap<&str, TimeDelta> = self
+            .load()
+            .days
+            .into_iter()
+            .map(|(person, days)| (person.id.as_str(), days))
+            .collect();
+        groups
+            .iter()
+            .map(|(group, members)| {
+                let days = members.iter().filter_map(|id| load.get(id.as_str())).sum();
+                (group.clone(), days)
+            })
+            .collect()
+    }
+
+    /// One line per group, sorted by name, with its days on call and the
+    /// average per member, to compare groups of different sizes.
+    pub fn group_report(&self, groups: &HashMap<String, Vec<String>>) -> String {
+        let load: BTreeMap<String, TimeDelta> = self.group_load(groups).into_iter().collect();
+        load.iter()
+            .map(|(group, days)| {
+                let members = groups[group].len().max(1);
+                format!(
+                    "{}: {} days on call, {:.1} per member\n",
+                    group,
+                    days.num_days(),
+                    days.num_days() as f64 / members as f64
+                )
+            })
+            .collect()
+    }
+
+    /// Every day from the start of the first turn to the end of the last one,
+    /// with who is on call in the primary rotation: `None` in the gaps
+    /// between turns. When turns overlap, the one listed last wins.
+    pub fn days(&self) -> impl Iterator<Item = (NaiveDate, Option<&Person>)> {
+        let mut assignee = HashMap::new();
+        for turn in self.turns.iter().filter(|t| t.rotation == 0) {
+            for date in turn.start.iter_days().take_while(|d| *d < turn.end) {
+                assignee.insert(date, turn.person);
+            }
+        }
+        let start = self.turns.iter().map(|t| t.start).min();
+        let end = self.turns.iter().map(|t| t.end).max();
+        start
+            .into_iter()
+            .flat_map(move |start| start.iter_days().take_while(move |d| Some(*d) < end))
+            .map(move |date| (date, assignee.get(&date).map(|&i| &self.people[i])))
+    }
+
+    /// Pairs of turn indices (lower index first) of the same rotation whose
+    /// `[start, end)` ranges overlap. Turns are swept in start order,
+    /// comparing each one only with the turns still running when it starts.
+    pub fn overlaps(&self) -> Vec<(usize, usize)> {
+        let mut order: Vec<usize> = (0..self.turns.len()).collect();
+        order.sort_by_key(|&i| (self.turns[i].start, self.turns[i].end));
+
+        let mut pairs = vec![];
+        let mut running: Vec<usize> = vec![];
+        for i in order {
+            let turn = &self.turns[i];
+            running.retain(|&j| self.turns[j].end > turn.start);
+            for &j in &running {
+                if self.turns[j].rotation == turn.rotation {
+                    pairs.push((i.min(j), i.max(j)));
+                }
+            }
+            running.push(i);
+        }
+        pairs.sort();
+        pairs
+    }
+
+    /// The `[start, end)` ranges between `from` and `to` (exclusive) that no
+    /// primary turn covers, in date order. Backup turns don't count, since
+    /// they only back up a primary.
+    pub fn coverage_gaps(&self, from: NaiveDate, to: NaiveDate) -> Vec<(NaiveDate, NaiveDate)> {
+        let mut turns: Vec<&Assignment> = self.turns.iter().filter(|t| t.rotation == 0).collect();
+        turns.sort_by_key(|t| (t.start, t.end));
+
+        let mut gaps = vec![];
+        let mut covered_until = from;
+        for turn in turns {
+            if covered_until >= to {
+                break;
+            }
+            if turn.start > covered_until {
+                gaps.push((covered_until, turn.start.min(to)));
+            }
+            covered_until = covered_until.max(turn.end);
+        }
+        if covered_until < to {
+            gaps.push((covered_until, to));
+        }
+        gaps
+    }
+
+    /// Merges back-to-back turns of the same person in the same rotation
+    /// into one, e.g. after a turn was split around an OOO day or a manual
+    /// edit. The parts of co-primary turns are kept apart. Turns are expected
+    /// in start order.
+    pub fn coalesce(&mut self) {
+        let mut turns: Vec<Assignment> = Vec::with_capacity(self.turns.len());
+        for turn in self.turns.drain(..) {
+            let previous = turns.iter_mut().rev().find(|t| t.rotation == turn.rotation);
+            match previous {
+                Some(previous)
+                    if previous.person == turn.person
+                        && previous.end == turn.start
+                        && previous.co_primary.is_empty()
+                        && turn.co_primary.is_empty() =>
+                {
+                    previous.end = turn.end;
+                }
+                _ => turns.push(turn),
+            }
+        }
+        self.turns = turns;
+    }
+
+    /// Difference between the most and the fewest days on call anyone has,
+    /// counting people without turns.
+    pub fn load_spread(&self) -> TimeDelta {
+        let mut days = vec![TimeDelta::zero(); self.people.len()];
+        for turn in &self.turns {
+            days[turn.person] += turn.end - turn.start;
+        }
+        let max = days.iter().max().copied().unwrap_or_default();
+        let min = days.iter().min().copied().unwrap_or_default();
+        max - min
+    }
+
+    /// Grades the schedule from A to F:
+    ///
+    /// | Grade | Turn spread | Gini   | Preferences honored |
+    /// |-------|-------------|--------|---------------------|
+    /// | A     | ≤ 1         | ≤ 0.10 | ≥ 90%               |
+    /// | B     | ≤ 2         | ≤ 0.20 | ≥ 75%               |
+    /// | C     | ≤ 3         | ≤ 0.30 | ≥ 50%               |
+    /// | D     | any         | ≤ 0.40 | ≥ 25%               |
+    /// | F     | any         | any    | any                 |
+    pub fn report_card(&self) -> ReportCard {
+        let mut turn_counts = vec![0; self.people.len()];
+        let mut days = vec![0.0; self.people.len()];
+        for turn in &self.turns {
+            turn_counts[turn.person] += 1;
+            days[turn.person] += (turn.end - turn.start).num_days() as f64;
+        }
+        let assignee: HashMap<NaiveDate, &Person> = self
+            .days()
+            .filter_map(|(date, person)| Some((date, person?)))
+            .collect();
+        let spread =
+            turn_counts.iter().max().unwrap_or(&0) - turn_counts.iter().min().unwrap_or(&0);
+
+        // Mean absolute difference between all pairs, over twice the mean.
+        let total: f64 = days.iter().sum();
+        let gini = if total == 0.0 {
+            0.0
+        } else {
+            let differences: f64 = days
+                .iter()
+                .flat_map(|a| days.iter().map(move |b| (a - b).abs()))
+                .sum();
+            differences / (2.0 * days.len() as f64 * total)
+        };
+
+        let mut preferences = 0;
+        let mut honored = 0;
+        for person in &self.people {
+            for (date, preference) in &person.preferences {
+                let Some(covered_by) = assignee.get(date) else {
+                    continue;
+                };
+                preferences += 1;
+                if (covered_by.id == person.id) == preference.is_want() {
+                    honored += 1;
+                }
+            }
+        }
+        let preferences_honored = if preferences == 0 {
+            1.0
+        } else {
+            honored as f64 / preferences as f64
+        };
+
+        let grade = if spread <= 1 && gini <= 0.1 && preferences_honored >= 0.9 {
+            'A'
+        } else if spread <= 2 && gini <= 0.2 && preferences_honored >= 0.75 {
+            'B'
+        } else if spread <= 3 && gini <= 0.3 && preferences_honored >= 0.5 {
+            'C'
+        } else if gini <= 0.4 && preferences_honored >= 0.25 {
+            'D'
+        } else {
+            'F'
+        };
+        ReportCard {
+            grade,
+            spread,
+            gini,
+            preferences_honored,
+            preferences,
+        }
+    }
+
+    /// Days, turns and weekend days on call of each person, and the variance
+    /// of their load as computed by the balanced algorithm.
+    pub fn statistics(&self) -> ScheduleStats {
+        let mut people: Vec<PersonStats> = self
+            .people
+            .iter()
+            .map(|p| PersonStats {
+                name: p.name.clone(),
+                days: 0,
+                turns: 0,
+                weekend_days: 0,
+            })
+            .collect();
+        for turn in &self.turns {
+            let stats = &mut people[turn.person];
+            stats.days += (turn.end - turn.start).num_days();
+            stats.turns += 1;
+            stats.weekend_days += turn
+                .start
+                .iter_days()
+                .take_while(|&date| date < turn.end)
+                .filter(|date| matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+                .count() as i64;
+        }
+        let load: Vec<TimeDelta> = people.iter().map(|p| TimeDelta::days(p.days)).collect();
+        let seconds_per_day = TimeDelta::days(1).num_seconds() as f64;
+        ScheduleStats {
+            people,
+            load_variance: calculate_load_variance(&load) / (seconds_per_day * seconds_per_day),
+        }
+    }
+
+    /// The days on call and turns of each person, in the order of the
+    /// schedule's people, including those without turns.
+    pub fn load_summary(&self) -> Vec<PersonLoad> {
+        let load = self.load();
+        self.people
+            .iter()
+            .enumerate()
+            .map(|(i, person)| PersonLoad {
+                id: person.id.clone(),
+                name: person.name.clone(),
+                days: load.days.get(person).map_or(0, |days| days.num_days()),
+                turns: self.turns.iter().filter(|t| t.person == i).count(),
+            })
+            .collect()
+    }
+
+    /// Describes the schedule in one sentence, for wikis and tickets, e.g.
+    /// "6-person rotation, ~7-day turns, running 2025-01-06 to 2025-03-31,
+    /// balanced algorithm, load spread 2 days, 4 of 5 preferences honored."
+    pub fn rotation_summary(&self) -> String {
+        let (Some(first), Some(last)) = (self.turns.first(), self.turns.last()) else {
+            return format!("{}-person rotation without turns.", self.people.len());
+        };
+        let days: i64 = self
             .turns
             .iter()
-            .map(|turn| {
-                let person = &self.people[turn.person];
-                YamlAssignment {
-                    person: &person.id,
+            .map(|t| (t.end - t.start).num_days())
+            .sum();
+        let average = (days as f64 / self.turns.len() as f64).round();
+        let mut parts = vec![
+            format!("{}-person rotation", self.people.len()),
+            format!("~{}-day turns", average),
+            format!("running {} to {}", first.start, last.end),
+        ];
+        if let Some(algo) = &self.algo {
+            parts.push(format!("{} algorithm", algo.name()));
+        }
+        let spread = format_days(self.load_spread(), true);
+        parts.push(format!("load spread {}", spread));
+        let card = self.report_card();
+        let honored = (card.preferences_honored * card.preferences as f64).round();
+        parts.push(format!(
+            "{} of {} preferences honored",
+            honored, card.preferences
+        ));
+        format!("{}.", parts.join(", "))
+    }
+
+    /// Three-way merge of a regenerated schedule (`self`) with the manual
+    /// edits made to the `original` schedule, resulting in `edited`: the
+    /// edited turns are kept before `cutoff` and the regenerated ones from
+    /// `cutoff` on, splitting turns that straddle it. Edits that can't be kept
+    /// are returned as conflicts.
+    pub fn merge_edits(
+        self,
+        original: &[YamlAssignment],
+        edited: &[YamlAssignment],
+        cutoff: NaiveDate,
+    ) -> (Schedule, Vec<MergeConflict>) {
+        let mut conflicts = vec![];
+        let mut turns = vec![];
+        for turn in edited {
+            let is_edit = !original
+                .iter()
+                .any(|o| o.person == turn.person && o.start == turn.start && o.end == turn.end);
+            if is_edit && turn.end > cutoff {
+                conflicts.push(MergeConflict::AfterCutoff {
+                    person: turn.person.to_string(),
+                    start: turn.start,
+                    end: turn.end,
+                });
+            }
+            if turn.start >= cutoff {
+                continue;
+            }
+            let position = |id: &str| self.people.iter().position(|p| p.id == id);
+            match position(turn.person) {
+                Some(person) => turns.push(Assignment {
+                    person,
+                    start: turn.start,
+                    end: turn.end.min(cutoff),
+                    rotation: turn.rotation,
+                    co_primary: turn
+                        .co_primary
+                        .iter()
+                        .filter_map(|id| position(id))
+                        .collect(),
+                }),
+                None => conflicts.push(MergeConflict::UnknownPerson {
+                    person: turn.person.to_string(),
                     start: turn.start,
                     end: turn.end,
+                }),
+            }
+        }
+        turns.extend(self.turns.iter().filter_map(|turn| turn.split_at(cutoff).1));
+        turns.sort_by_key(|t| (t.start, t.end));
+
+        let schedule = Schedule {
+            people: self.people,
+            turns,
+            algo: self.algo,
+        };
+        (schedule, conflicts)
+    }
+
+    /// The differences between the `previous` turns and the turns of this
+    /// schedule, in date order. Turns of the same rotation that overlap are
+    /// paired up as changed, rather than reported as removed and added: first
+    /// those of the same person, whose boundaries moved, then the others.
+    pub fn diff<'a>(&'a self, previous: &[YamlAssignment<'a>]) -> Vec<TurnChange<'a>> {
+        let all: Vec<YamlAssignment> = self.turns.iter().map(|t| self.yaml_assignment(t)).collect();
+        // The unchanged turns are left out on both sides.
+        let mut current: Vec<YamlAssignment> = all
+            .iter()
+            .filter(|c| !previous.contains(c))
+            .cloned()
+            .collect();
+        current.sort_by_key(|t| (t.start, t.end));
+        let mut previous: Vec<YamlAssignment> = previous
+            .iter()
+            .filter(|p| !all.contains(p))
+            .cloned()
+            .collect();
+        previous.sort_by_key(|t| (t.start, t.end));
+
+        let mut changes = vec![];
+        for same_person in [true, false] {
+            current.retain(|c| {
+                let paired = previous.iter().position(|p| {
+                    p.rotation == c.rotation
+                        && p.start < c.end
+                        && c.start < p.end
+                        && (p.person == c.person || !same_person)
+                });
+                match paired {
+                    Some(i) => {
+                        changes.push(TurnChange::Changed {
+                            previous: previous.remove(i),
+                            current: c.clone(),
+                        });
+                        false
+                    }
+                    None => true,
                 }
+            });
+        }
+        changes.extend(previous.into_iter().map(TurnChange::Removed));
+        changes.extend(current.into_iter().map(TurnChange::Added));
+        changes.sort_by_key(|change| match change {
+            TurnChange::Added(turn) | TurnChange::Removed(turn) => turn.start,
+            TurnChange::Changed { previous, current } => previous.start.min(current.start),
+        });
+        changes
+    }
+
+    /// The [`diff`](Self::diff) from the `previous` turns as ledger entries
+    /// recorded on `date`, in date order.
+    pub fn ledger<'a>(
+        &'a self,
+        previous: &[YamlAssignment<'a>],
+        date: NaiveDate,
+    ) -> Vec<LedgerEntry<'a>> {
+        self.diff(previous)
+            .into_iter()
+            .map(|change| LedgerEntry { date, change })
+            .collect()
+    }
+
+    /// Groups the turns by person, sorted by person id, for a roster view
+    /// ("when am I on call?") rather than a timeline. People without any
+    /// turn are listed with an empty list.
+    pub fn by_person(&self) -> Vec<(String, Vec<&Assignment>)> {
+        let mut indices: Vec<usize> = (0..self.people.len()).collect();
+        indices.sort_by(|&a, &b| self.people[a].id.cmp(&self.people[b].id));
+        indices
+            .into_iter()
+            .map(|i| {
+                let turns = self.turns.iter().filter(|t| t.person == i).collect();
+                (self.people[i].id.clone(), turns)
+            })
+            .collect()
+    }
+
+    pub fn roster(&self) -> Roster<'_> {
+        Roster { schedule: self }
+    }
+
+    /// Renders the turns as FullCalendar all-day events. FullCalendar treats
+    /// the `end` of all-day events as exclusive, like our assignments do. The
+    /// contact details of the person, if any, are in `extendedProps`.
+    pub fn to_fullcalendar(&self) -> serde_json::Value {
+        let events = self
+            .turns
+            .iter()
+            .map(|turn| {
+                let person = &self.people[turn.person];
+                let mut event = serde_json::json!({
+                    "title": person.name,
+                    "start": turn.start.format("%Y-%m-%d").to_string(),
+                    "end": turn.end.format("%Y-%m-%d").to_string(),
+                    "allDay": true,
+                });
+                if person.contact != Contact::default() {
+                    event["extendedProps"] = serde_json::json!(person.contact);
+                }
+                if turn.rotation > 0 {
+                    event["rotation"] = serde_json::json!(turn.rotation);
+                }
+                event
             })
             .collect();
+        serde_json::Value::Array(events)
+    }
+
+    /// Renders the turns as CSV, one row per turn, with the exclusive end
+    /// date like in YAML.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("person_id,person_name,start,end,days\n");
+        for turn in &self.turns {
+            let person = &self.people[turn.person];
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                csv_field(&person.id),
+                csv_field(&person.name),
+                turn.start,
+                turn.end,
+                (turn.end - turn.start).num_days()
+            ));
+        }
+        csv
+    }
 
+    /// Renders the turns as a Markdown table, with the exclusive end date like
+    /// in YAML, followed by a table with the days on call of each person.
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::from("| Person | Start | End | Days |\n|---|---|---|---|\n");
+        for turn in &self.turns {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                markdown_cell(&self.people[turn.person].name),
+                turn.start,
+                turn.end,
+                (turn.end - turn.start).num_days()
+            ));
+        }
+        md.push_str("\n| Person | Days |\n|---|---|\n");
+        let load = self.load();
+        // In rotation order rather than the load's, so that the output is
+        // stable, and with the people who have no turns too.
+        for person in &self.people {
+            let days = load.days.get(person).copied().unwrap_or_default();
+            md.push_str(&format!(
+                "| {} | {} |\n",
+                markdown_cell(&person.name),
+                days.num_days()
+            ));
+        }
+        md
+    }
+
+    /// The title of a turn in calendars: the name of the person, followed by
+    /// the others sharing the turn for a co-primary one.
+    fn turn_summary(&self, turn: &Assignment) -> String {
+        let name = &self.people[turn.person].name;
+        if turn.co_primary.is_empty() {
+            return name.clone();
+        }
+        let others: Vec<&str> = turn
+            .co_primary
+            .iter()
+            .map(|&i| self.people[i].name.as_str())
+            .collect();
+        format!("{} (co-primary with {})", name, others.join(", "))
+    }
+
+    /// Renders the turns as an iCalendar feed with one all-day event per
+    /// turn. DTEND is exclusive, like our assignments. UIDs and DTSTAMPs only
+    /// depend on the turn, so that regenerating an unchanged schedule gives
+    /// the same feed. People with an email are added as attendees.
+    pub fn to_ics(&self, calendar_name: &str) -> String {
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//turns//on-call schedule//EN".to_string(),
+            format!("X-WR-CALNAME:{}", escape_ics_text(calendar_name)),
+        ];
+        for turn in &self.turns {
+            let person = &self.people[turn.person];
+            let start = turn.start.format("%Y%m%d");
+            lines.extend([
+                "BEGIN:VEVENT".to_string(),
+                format!("UID:{}-{}@turns", person.id, start),
+                format!("DTSTAMP:{}T000000Z", start),
+                format!("DTSTART;VALUE=DATE:{}", start),
+                format!("DTEND;VALUE=DATE:{}", turn.end.format("%Y%m%d")),
+                format!("SUMMARY:{}", escape_ics_text(&self.turn_summary(turn))),
+            ]);
+            if let Some(email) = &person.contact.email {
+                lines.push(format!(
+                    "ATTENDEE;CN={}:mailto:{}",
+                    escape_ics_text(&person.name),
+                    email
+                ));
+            }
+            lines.push("END:VEVENT".to_string());
+        }
+        lines.push("END:VCALENDAR".to_string());
+        lines.iter().map(|line| format!("{}\r\n", line)).collect()
+    }
+
+    /// Lists the turns by ISO week, the way ops docs usually refer to them.
+    pub fn to_iso_weeks(&self) -> String {
+        let mut out = String::new();
+        for turn in &self.turns {
+            let weeks = match turn.iso_weeks() {
+                (first, last) if first == last => format!("week {:?}", first),
+                (first, last) => format!("weeks {:?} - {:?}", first, last),
+            };
+            out.push_str(&format!(
+                "{}\t{}\t({} - {})\n",
+                self.people[turn.person].name, weeks, turn.start, turn.end
+            ));
+        }
+        out
+    }
+
+    /// Renders the days on call of each person and the fairness metrics in
+    /// the Prometheus text exposition format, for dashboards.
+    pub fn to_prometheus(&self) -> String {
+        // Label values escape backslashes, double quotes and newlines.
+        let escape = |value: &str| {
+            value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+        };
+        let mut days = vec![TimeDelta::zero(); self.people.len()];
+        for turn in &self.turns {
+            days[turn.person] += turn.end - turn.start;
+        }
+        let mut people: Vec<usize> = (0..self.people.len()).collect();
+        people.sort_by(|&a, &b| self.people[a].id.cmp(&self.people[b].id));
+
+        let mut out = String::new();
+        out.push_str("# HELP turns_person_load_days Days on call of each person.\n");
+        out.push_str("# TYPE turns_person_load_days gauge\n");
+        for i in people {
+            out.push_str(&format!(
+                "turns_person_load_days{{person=\"{}\"}} {}\n",
+                escape(&self.people[i].id),
+                days[i].num_days()
+            ));
+        }
+        out.push_str(
+            "# HELP turns_load_spread_days Difference between the most and fewest days on call.\n",
+        );
+        out.push_str("# TYPE turns_load_spread_days gauge\n");
+        out.push_str(&format!(
+            "turns_load_spread_days {}\n",
+            self.load_spread().num_days()
+        ));
+        out.push_str(
+            "# HELP turns_preference_satisfaction_ratio Share of the preferences honored.\n",
+        );
+        out.push_str("# TYPE turns_preference_satisfaction_ratio gauge\n");
+        out.push_str(&format!(
+            "turns_preference_satisfaction_ratio {}\n",
+            self.report_card().preferences_honored
+        ));
+        out
+    }
+
+    /// The on-call debt of everyone in the schedule or with one of the
+    /// `assignments`, in days rounded to the hundredth: what they covered
+    /// minus the average.
+    fn debt(&self, assignments: &[YamlAssignment]) -> BTreeMap<String, f64> {
+        let mut days: BTreeMap<String, f64> =
+            self.people.iter().map(|p| (p.id.clone(), 0.0)).collect();
+        for assignment in assignments {
+            *days.entry(assignment.person.to_string()).or_insert(0.0) +=
+                (assignment.end - assignment.start).num_days() as f64;
+        }
+        let fair_share = days.values().sum::<f64>() / days.len().max(1) as f64;
+        days.into_iter()
+            .map(|(id, days)| (id, ((days - fair_share) * 100.0).round() / 100.0))
+            .collect()
+    }
+
+    /// A short hex digest of the turns, the same for any two schedules with
+    /// the same turns regardless of their order or how they were generated,
+    /// to tell whether a schedule actually changed without comparing files.
+    pub fn content_id(&self) -> String {
+        let assignments: Vec<YamlAssignment> =
+            self.turns.iter().map(|t| self.yaml_assignment(t)).collect();
+        content_id(&assignments)
+    }
+
+    pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+        self.append_to_yaml(vec![])
+    }
+
+    fn yaml_assignment(&self, turn: &Assignment) -> YamlAssignment<'_> {
+        YamlAssignment {
+            person: &self.people[turn.person].id,
+            start: turn.start,
+            end: turn.end,
+            rotation: turn.rotation,
+            co_primary: turn
+                .co_primary
+                .iter()
+                .map(|&i| self.people[i].id.as_str())
+                .collect(),
+        }
+    }
+
+    /// Serializes the turns before `tentative_after` as `published` and the
+    /// rest as `tentative`, splitting the turn that straddles the date.
+    pub fn to_yaml_partitioned(
+        &self,
+        tentative_after: NaiveDate,
+    ) -> Result<String, serde_yaml::Error> {
+        let mut published = vec![];
+        let mut tentative = vec![];
+        for turn in &self.turns {
+            let (before, after) = turn.split_at(tentative_after);
+            published.extend(before.map(|t| self.yaml_assignment(&t)));
+            tentative.extend(after.map(|t| self.yaml_assignment(&t)));
+        }
+
+        let all: Vec<YamlAssignment> = self.turns.iter().map(|t| self.yaml_assignment(t)).collect();
+        let yaml_schedule = YamlPartitionedSchedule {
+            metadata: self.algo.clone().map(|algo| YamlMetadata {
+                algo,
+                debt: Some(self.debt(&all)),
+                content_id: Some(self.content_id()),
+            }),
+            published,
+            tentative,
+        };
+
+        serde_yaml::to_string(&yaml_schedule)
+    }
+
+    /// Serializes the `previous` assignments followed by the turns of this
+    /// schedule, to extend an existing schedule file.
+    pub fn append_to_yaml<'a>(
+        &'a self,
+        previous: Vec<YamlAssignment<'a>>,
+    ) -> Result<String, serde_yaml::Error> {
+        let mut assignments = previous;
+        assignments.extend(self.turns.iter().map(|turn| self.yaml_assignment(turn)));
+
+        let debt = self.debt(&assignments);
         let yaml_schedule = YamlSchedule {
+            metadata: self.algo.clone().map(|algo| YamlMetadata {
+                algo,
+                debt: Some(debt),
+                content_id: Some(content_id(&assignments)),
+            }),
             schedule: assignments,
         };
 
@@ -76,25 +1078,1061 @@ impl Schedule {
     }
 }
 
+impl YamlSchedule<'_> {
+    /// The schedule the file describes. Only the ids of the people are known,
+    /// so they are also their names; people are sorted by id.
+    pub fn to_schedule(&self) -> Schedule {
+        let mut ids: Vec<&str> = self.schedule.iter().map(|a| a.person).collect();
+        ids.sort();
+        ids.dedup();
+        let people = ids
+            .iter()
+            .map(|id| Person {
+                id: id.to_string(),
+                name: id.to_string(),
+                ooo: Default::default(),
+                preferences: HashMap::new(),
+                contact: Contact::default(),
+                weight: 1.0,
+                max_days: None,
+                turn_length_days: None,
+            })
+            .collect();
+        let turns = self
+            .schedule
+            .iter()
+            .map(|a| Assignment {
+                person: ids.binary_search(&a.person).unwrap(),
+                start: a.start,
+                end: a.end,
+                rotation: a.rotation,
+                co_primary: a
+                    .co_primary
+                    .iter()
+                    .filter_map(|id| ids.binary_search(id).ok())
+                    .collect(),
+            })
+            .collect();
+        Schedule {
+            people,
+            turns,
+            algo: self.metadata.as_ref().map(|m| m.algo.clone()),
+        }
+    }
+
+    /// The primary turn covering `day`, if any, and the one after it: the
+    /// next turn starting once the current one ends, or after `day` when no
+    /// one is on call.
+    pub fn who_is_oncall(
+        &self,
+        day: NaiveDate,
+    ) -> (Option<&YamlAssignment<'_>>, Option<&YamlAssignment<'_>>) {
+        let primary = || self.schedule.iter().filter(|a| a.rotation == 0);
+        let current = primary().find(|a| a.start <= day && day < a.end);
+        let next = primary()
+            .filter(|a| match current {
+                Some(c) => a.start >= c.end,
+                None => a.start > day,
+            })
+            .min_by_key(|a| a.start);
+        (current, next)
+    }
+
+    /// Who is on call at `at` and who is next, as a JSON object for status
+    /// pages. Either is `null` when there is no such turn.
+    pub fn status(&self, at: NaiveDateTime) -> serde_json::Value {
+        let (current, next) = self.who_is_oncall(at.date());
+        serde_json::json!({
+            "current": current.map(|a| serde_json::json!({
+                "person": a.person,
+                "until": a.end.format("%Y-%m-%d").to_string(),
+            })),
+            "next": next.map(|a| serde_json::json!({
+                "person": a.person,
+                "from": a.start.format("%Y-%m-%d").to_string(),
+                "until": a.end.format("%Y-%m-%d").to_string(),
+            })),
+        })
+    }
+}
+
+/// Quotes a CSV field when needed, as per RFC 4180.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Escapes the pipes that would otherwise end a Markdown table cell.
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+/// Escapes iCalendar TEXT values.
+fn escape_ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// 64-bit FNV-1a, which unlike the standard library hashers is stable across
+/// Rust releases.
+pub fn stable_hash(content: &[u8]) -> u64 {
+    content.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// The [`stable_hash`] of the assignments, one `person,start,end` line each
+/// (followed by `,rotation` for backups) in chronological order, in hex.
+fn content_id(assignments: &[YamlAssignment]) -> String {
+    let mut turns: Vec<(NaiveDate, NaiveDate, u8, &str)> = assignments
+        .iter()
+        .map(|a| (a.start, a.end, a.rotation, a.person))
+        .collect();
+    turns.sort();
+    let canonical: String = turns
+        .iter()
+        .map(|(start, end, rotation, person)| match rotation {
+            0 => format!("{},{},{}\n", person, start, end),
+            _ => format!("{},{},{},{}\n", person, start, end, rotation),
+        })
+        .collect();
+    format!("{:016x}", stable_hash(canonical.as_bytes()))
+}
+
+/// Formats a length in days, or with `human` set in the most natural unit:
+/// weeks when it is a whole number of them, days otherwise.
+pub fn format_days(length: TimeDelta, human: bool) -> String {
+    let days = length.num_days();
+    if !human {
+        return format!("{} days", days);
+    }
+    match days {
+        7 => "1 week".to_string(),
+        _ if days > 0 && days % 7 == 0 => format!("{} weeks", days / 7),
+        1 => "1 day".to_string(),
+        _ => format!("{} days", days),
+    }
+}
+
+/// The alternate flag (`{:#}`) prints human-friendly durations, see
+/// [`format_days`].
 impl Display for Schedule {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         for turn in &self.turns {
             let length = turn.end - turn.start;
-            writeln!(
+            write!(
                 f,
-                "{}	{} - {} ({} days)",
+                "{}	{} - {} ({})",
                 self.people[turn.person].name,
                 turn.start,
                 turn.end,
-                length.num_days()
+                format_days(length, f.alternate())
             )?;
+            if turn.rotation > 0 {
+                write!(f, " [backup {}]", turn.rotation)?;
+            }
+            writeln!(f)?;
         }
         
         writeln!(f, "\nLoad summary:")?;
         let load = self.load();
         for (person, days) in load.days {
-            writeln!(f, "{}: {} days", person.name, days.num_days())?;
+            writeln!(f, "{}: {}", person.name, format_days(days, f.alternate()))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Roster<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (id, turns) in self.schedule.by_person() {
+            let name = self
+                .schedule
+                .people
+                .iter()
+                .find(|p| p.id == id)
+                .map(|p| p.name.as_str())
+                .unwrap_or(&id);
+            let total = turns
+                .iter()
+                .fold(TimeDelta::zero(), |acc, t| acc + (t.end - t.start));
+            writeln!(f, "{} ({})", name, format_days(total, f.alternate()))?;
+            for turn in turns {
+                writeln!(
+                    f,
+                    "\t{} - {} ({})",
+                    turn.start,
+                    turn.end,
+                    format_days(turn.end - turn.start, f.alternate())
+                )?;
+            }
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::PreferenceType;
+
+    fn person(id: &str, name: &str) -> Person {
+        Person {
+            name: name.to_string(),
+            ..crate::input::person(id)
+        }
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 1, day).unwrap()
+    }
+
+    fn turn(person: usize, start: u32, end: u32) -> Assignment {
+        Assignment {
+            person,
+            start: date(start),
+            end: date(end),
+            rotation: 0,
+            co_primary: vec![],
+        }
+    }
+
+    #[test]
+    fn test_by_person_lists_every_assignment_once() {
+        let schedule = Schedule {
+            people: vec![
+                person("charlie", "Charlie"),
+                person("alice", "Alice"),
+                person("bob", "Bob"),
+            ],
+            turns: vec![turn(0, 1, 3), turn(1, 3, 5), turn(0, 5, 7), turn(1, 7, 9)],
+            algo: None,
+        };
+
+        let grouped = schedule.by_person();
+        let ids: Vec<&str> = grouped.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["alice", "bob", "charlie"]);
+
+        let total: usize = grouped.iter().map(|(_, turns)| turns.len()).sum();
+        assert_eq!(total, schedule.turns.len());
+        for turn in &schedule.turns {
+            let count = grouped
+                .iter()
+                .flat_map(|(_, turns)| turns.iter())
+                .filter(|t| std::ptr::eq(**t, turn))
+                .count();
+            assert_eq!(count, 1);
+        }
+
+        assert_eq!(grouped[0].1[0].start, date(3));
+        assert_eq!(grouped[0].1[1].start, date(7));
+        assert!(grouped[1].1.is_empty());
+
+        let again: Vec<String> = schedule.by_person().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(again, ids);
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let mut schedule = Schedule {
+            people: vec![person("alice", "Alice"), person("bob", "Bob")],
+            turns: vec![turn(0, 1, 4), turn(1, 4, 8), turn(0, 8, 10)],
+            algo: None,
+        };
+        assert!(schedule.overlaps().is_empty());
+
+        schedule.turns.push(turn(1, 3, 9));
+        schedule.turns.push(turn(0, 20, 21));
+        assert_eq!(schedule.overlaps(), vec![(0, 3), (1, 3), (2, 3)]);
+    }
+
+    #[test]
+    fn test_coverage_gaps() {
+        let mut schedule = Schedule {
+            people: vec![person("alice", "Alice"), person("bob", "Bob")],
+            turns: vec![turn(1, 4, 8), turn(0, 1, 4), turn(0, 8, 10)],
+            algo: None,
+        };
+        assert!(schedule.coverage_gaps(date(1), date(10)).is_empty());
+        assert!(schedule.coverage_gaps(date(2), date(9)).is_empty());
+
+        // A backup turn doesn't fill the gap.
+        schedule.turns = vec![
+            turn(0, 1, 4),
+            Assignment {
+                rotation: 1,
+                ..turn(1, 4, 6)
+            },
+            turn(1, 6, 8),
+        ];
+        assert_eq!(
+            schedule.coverage_gaps(date(1), date(10)),
+            vec![(date(4), date(6)), (date(8), date(10))]
+        );
+    }
+
+    #[test]
+    fn test_coalesce() {
+        let backup = |person, start, end| Assignment {
+            rotation: 1,
+            ..turn(person, start, end)
+        };
+        let mut schedule = Schedule {
+            people: vec![person("alice", "Alice"), person("bob", "Bob")],
+            turns: vec![
+                turn(0, 1, 4),
+                backup(1, 1, 4),
+                turn(0, 4, 8),
+                backup(1, 4, 8),
+                turn(1, 8, 10),
+                turn(0, 11, 12),
+            ],
+            algo: None,
+        };
+        schedule.coalesce();
+
+        let turns: Vec<_> = schedule
+            .turns
+            .iter()
+            .map(|t| (t.person, t.start.day(), t.end.day(), t.rotation))
+            .collect();
+        // Bob's turns from the 8th and Alice's from the 11th aren't adjacent.
+        assert_eq!(
+            turns,
+            vec![(0, 1, 8, 0), (1, 1, 8, 1), (1, 8, 10, 0), (0, 11, 12, 0)]
+        );
+    }
+
+    #[test]
+    fn test_iso_weeks() {
+        // Monday 2025-01-06 for 10 days: weeks 2 and 3.
+        let ten_days = turn(0, 6, 16);
+        let (first, last) = ten_days.iso_weeks();
+        assert_eq!((first.year(), first.week()), (2025, 2));
+        assert_eq!((last.year(), last.week()), (2025, 3));
+
+        let schedule = Schedule {
+            people: vec![person("alice", "Alice"), person("bob", "Bob")],
+            turns: vec![ten_days, turn(1, 16, 19)],
+            algo: None,
+        };
+        assert_eq!(
+            schedule.to_iso_weeks(),
+            "Alice\tweeks 2025-W02 - 2025-W03\t(2025-01-06 - 2025-01-16)\n\
+             Bob\tweek 2025-W03\t(2025-01-16 - 2025-01-19)\n"
+        );
+    }
+
+    #[test]
+    fn test_to_fullcalendar() {
+        let schedule = Schedule {
+            people: vec![person("alice", "Alice"), person("bob", "Bob")],
+            turns: vec![turn(0, 1, 8), turn(1, 8, 15)],
+            algo: None,
+        };
+
+        let events = schedule.to_fullcalendar();
+        assert_eq!(
+            events,
+            serde_json::json!([
+                { "title": "Alice", "start": "2025-01-01", "end": "2025-01-08", "allDay": true },
+                { "title": "Bob", "start": "2025-01-08", "end": "2025-01-15", "allDay": true },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let schedule = Schedule {
+            people: vec![
+                person("alice", "Alice"),
+                person("bob", "Bob \"Bobby\", Jr."),
+            ],
+            turns: vec![turn(0, 1, 8), turn(1, 8, 10)],
+            algo: None,
+        };
+
+        assert_eq!(
+            schedule.to_csv(),
+            "person_id,person_name,start,end,days\n\
+             alice,Alice,2025-01-01,2025-01-08,7\n\
+             bob,\"Bob \"\"Bobby\"\", Jr.\",2025-01-08,2025-01-10,2\n"
+        );
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let schedule = Schedule {
+            people: vec![
+                person("alice", "Alice"),
+                person("bob", "Bob | Jr."),
+                person("carol", "Carol"),
+            ],
+            turns: vec![turn(0, 1, 8), turn(1, 8, 10)],
+            algo: None,
+        };
+
+        let md = schedule.to_markdown();
+        let lines: Vec<&str> = md.lines().collect();
+        assert_eq!(lines[0], "| Person | Start | End | Days |");
+        assert_eq!(lines[1], "|---|---|---|---|");
+        assert_eq!(lines[2], "| Alice | 2025-01-01 | 2025-01-08 | 7 |");
+        assert_eq!(lines[3], "| Bob \\| Jr. | 2025-01-08 | 2025-01-10 | 2 |");
+        assert_eq!(
+            lines[4..],
+            [
+                "",
+                "| Person | Days |",
+                "|---|---|",
+                "| Alice | 7 |",
+                "| Bob \\| Jr. | 2 |",
+                "| Carol | 0 |",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_ics() {
+        let mut alice = person("alice", "Alice");
+        alice.contact.email = Some("alice@example.com".to_string());
+        let schedule = Schedule {
+            people: vec![alice, person("bob", "Bob, Jr.")],
+            turns: vec![turn(0, 1, 8), turn(1, 8, 15)],
+            algo: None,
+        };
+
+        let ics = schedule.to_ics("Team on call");
+        assert!(ics.ends_with("\r\n"));
+        let lines: Vec<&str> = ics.split_terminator("\r\n").collect();
+        assert_eq!(
+            lines,
+            vec![
+                "BEGIN:VCALENDAR",
+                "VERSION:2.0",
+                "PRODID:-//turns//on-call schedule//EN",
+                "X-WR-CALNAME:Team on call",
+                "BEGIN:VEVENT",
+                "UID:alice-20250101@turns",
+                "DTSTAMP:20250101T000000Z",
+                "DTSTART;VALUE=DATE:20250101",
+                "DTEND;VALUE=DATE:20250108",
+                "SUMMARY:Alice",
+                "ATTENDEE;CN=Alice:mailto:alice@example.com",
+                "END:VEVENT",
+                "BEGIN:VEVENT",
+                "UID:bob-20250108@turns",
+                "DTSTAMP:20250108T000000Z",
+                "DTSTART;VALUE=DATE:20250108",
+                "DTEND;VALUE=DATE:20250115",
+                "SUMMARY:Bob\\, Jr.",
+                "END:VEVENT",
+                "END:VCALENDAR",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_fullcalendar_with_contact() {
+        let mut alice = person("alice", "Alice");
+        alice.contact.email = Some("alice@example.com".to_string());
+        alice.contact.slack = Some("@alice".to_string());
+        let schedule = Schedule {
+            people: vec![alice, person("bob", "Bob")],
+            turns: vec![turn(0, 1, 8), turn(1, 8, 15)],
+            algo: None,
+        };
+
+        let events = schedule.to_fullcalendar();
+        assert_eq!(
+            events[0]["extendedProps"],
+            serde_json::json!({ "email": "alice@example.com", "slack": "@alice" })
+        );
+        assert!(events[1].get("extendedProps").is_none());
+    }
+
+    #[test]
+    fn test_merge_edits() {
+        let regenerated = Schedule {
+            people: vec![person("alice", "Alice"), person("bob", "Bob")],
+            turns: vec![
+                turn(0, 1, 8),
+                turn(1, 8, 15),
+                turn(1, 15, 22),
+                turn(0, 22, 29),
+            ],
+            algo: None,
+        };
+        let yaml = |person, start, end| YamlAssignment {
+            person,
+            start: date(start),
+            end: date(end),
+            rotation: 0,
+            co_primary: vec![],
+        };
+        let original = vec![
+            yaml("alice", 1, 8),
+            yaml("bob", 8, 15),
+            yaml("alice", 15, 22),
+            yaml("alice", 22, 29),
+        ];
+        // Alice and Bob swapped their first turns, and Bob took the last one.
+        let edited = vec![
+            yaml("bob", 1, 8),
+            yaml("alice", 8, 15),
+            yaml("alice", 15, 22),
+            yaml("bob", 22, 29),
+        ];
+
+        let (merged, conflicts) = regenerated.merge_edits(&original, &edited, date(15));
+        let turns: Vec<(usize, NaiveDate, NaiveDate)> = merged
+            .turns
+            .iter()
+            .map(|t| (t.person, t.start, t.end))
+            .collect();
+        assert_eq!(
+            turns,
+            vec![
+                (1, date(1), date(8)),
+                (0, date(8), date(15)),
+                (1, date(15), date(22)),
+                (0, date(22), date(29)),
+            ]
+        );
+        assert_eq!(
+            conflicts,
+            vec![MergeConflict::AfterCutoff {
+                person: "bob".to_string(),
+                start: date(22),
+                end: date(29),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let regenerated = Schedule {
+            people: vec![
+                person("alice", "Alice"),
+                person("bob", "Bob"),
+                person("carol", "Carol"),
+            ],
+            turns: vec![
+                turn(0, 1, 8),
+                turn(2, 8, 15),
+                turn(0, 15, 20),
+                turn(1, 20, 29),
+            ],
+            algo: None,
+        };
+        let yaml = |person, start, end| YamlAssignment {
+            person,
+            start: date(start),
+            end: date(end),
+            rotation: 0,
+            co_primary: vec![],
+        };
+        let previous = vec![
+            yaml("alice", 1, 8),
+            yaml("bob", 8, 15),
+            yaml("alice", 15, 22),
+            yaml("bob", 22, 29),
+        ];
+
+        let changes = regenerated.diff(&previous);
+        assert_eq!(
+            changes,
+            vec![
+                TurnChange::Changed {
+                    previous: yaml("bob", 8, 15),
+                    current: yaml("carol", 8, 15),
+                },
+                TurnChange::Changed {
+                    previous: yaml("alice", 15, 22),
+                    current: yaml("alice", 15, 20),
+                },
+                TurnChange::Changed {
+                    previous: yaml("bob", 22, 29),
+                    current: yaml("bob", 20, 29),
+                },
+            ]
+        );
+        assert_eq!(
+            changes[0].to_string(),
+            "Changed 2025-01-08 - 2025-01-15 from bob to carol"
+        );
+        assert_eq!(
+            changes[1].to_string(),
+            "Moved 2025-01-15 - 2025-01-22 of alice to 2025-01-15 - 2025-01-20"
+        );
+        assert!(
+            regenerated
+                .diff(&previous[..1])
+                .iter()
+                .all(|c| matches!(c, TurnChange::Added(_)))
+        );
+    }
+
+    #[test]
+    fn test_ledger() {
+        let regenerated = Schedule {
+            people: vec![
+                person("alice", "Alice"),
+                person("bob", "Bob"),
+                person("carol", "Carol"),
+            ],
+            turns: vec![turn(0, 1, 8), turn(0, 10, 17), turn(2, 20, 27)],
+            algo: None,
+        };
+        let yaml = |person, start, end| YamlAssignment {
+            person,
+            start: date(start),
+            end: date(end),
+            rotation: 0,
+            co_primary: vec![],
+        };
+        let previous = vec![
+            yaml("alice", 1, 8),
+            yaml("bob", 20, 27),
+            yaml("bob", 27, 31),
+        ];
+
+        let ledger = regenerated.ledger(&previous, date(10));
+        let lines: Vec<String> = ledger.iter().map(|entry| entry.to_string()).collect();
+        assert_eq!(
+            lines,
+            [
+                "2025-01-10: added alice 1/10–1/17",
+                "2025-01-10: changed 1/20–1/27 from bob to carol",
+                "2025-01-10: removed bob 1/27–1/31",
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&ledger[..2]).unwrap(),
+            serde_json::json!([
+                {
+                    "date": "2025-01-10",
+                    "change": "added",
+                    "person": "alice",
+                    "start": "2025-01-10",
+                    "end": "2025-01-17",
+                },
+                {
+                    "date": "2025-01-10",
+                    "change": "changed",
+                    "previous": { "person": "bob", "start": "2025-01-20", "end": "2025-01-27" },
+                    "current": { "person": "carol", "start": "2025-01-20", "end": "2025-01-27" },
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_merge_edits_splits_turns_at_cutoff() {
+        let regenerated = Schedule {
+            people: vec![person("alice", "Alice"), person("bob", "Bob")],
+            turns: vec![turn(0, 1, 8), turn(1, 8, 15)],
+            algo: None,
+        };
+        let edited = vec![YamlAssignment {
+            person: "bob",
+            start: date(1),
+            end: date(10),
+            rotation: 0,
+            co_primary: vec![],
+        }];
+
+        let (merged, _) = regenerated.merge_edits(&edited, &edited, date(5));
+        let turns: Vec<(usize, NaiveDate, NaiveDate)> = merged
+            .turns
+            .iter()
+            .map(|t| (t.person, t.start, t.end))
+            .collect();
+        assert_eq!(
+            turns,
+            vec![
+                (1, date(1), date(5)),
+                (0, date(5), date(8)),
+                (1, date(8), date(15)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_report_card() {
+        let mut alice = person("alice", "Alice");
+        alice.preferences.insert(date(1), PreferenceType::Want);
+        let mut bob = person("bob", "Bob");
+        bob.preferences.insert(date(1), PreferenceType::NotWant);
+
+        let balanced = Schedule {
+            people: vec![alice.clone(), bob.clone()],
+            turns: vec![
+                turn(0, 1, 8),
+                turn(1, 8, 15),
+                turn(0, 15, 22),
+                turn(1, 22, 29),
+            ],
+            algo: None,
+        };
+        let card = balanced.report_card();
+        assert_eq!(card.grade, 'A');
+        assert_eq!(card.spread, 0);
+        assert_eq!(card.gini, 0.0);
+        assert_eq!(card.preferences_honored, 1.0);
+
+        let lopsided = Schedule {
+            people: vec![alice, bob],
+            turns: vec![
+                turn(1, 1, 8),
+                turn(1, 8, 15),
+                turn(1, 15, 22),
+                turn(0, 22, 29),
+            ],
+            algo: None,
+        };
+        let card = lopsided.report_card();
+        assert_eq!(card.grade, 'F');
+        assert_eq!(card.spread, 2);
+        assert_eq!(card.gini, 0.25);
+        assert_eq!(card.preferences_honored, 0.0);
+    }
+
+    #[test]
+    fn test_append_to_yaml() {
+        let feb = |day| NaiveDate::from_ymd_opt(2025, 2, day).unwrap();
+        let january = "
+schedule:
+  - person: alice
+    start: 2025-01-01
+    end: 2025-01-16
+  - person: bob
+    start: 2025-01-16
+    end: 2025-02-01
+";
+        let previous: YamlSchedule = serde_yaml::from_str(january).unwrap();
+        let february = Schedule {
+            people: vec![person("alice", "Alice"), person("bob", "Bob")],
+            turns: vec![
+                Assignment {
+                    person: 0,
+                    start: feb(1),
+                    end: feb(15),
+                    rotation: 0,
+                    co_primary: vec![],
+                },
+                Assignment {
+                    person: 1,
+                    start: feb(15),
+                    end: NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+                    rotation: 0,
+                    co_primary: vec![],
+                },
+            ],
+            algo: Some(Algo::RoundRobin {
+                turn_length_days: 14,
+                start_person: None,
+            }),
+        };
+
+        let yaml = february.append_to_yaml(previous.schedule).unwrap();
+        let combined: YamlSchedule = serde_yaml::from_str(&yaml).unwrap();
+        let turns: Vec<(&str, NaiveDate)> = combined
+            .schedule
+            .iter()
+            .map(|a| (a.person, a.start))
+            .collect();
+        assert_eq!(
+            turns,
+            vec![
+                ("alice", date(1)),
+                ("bob", date(16)),
+                ("alice", feb(1)),
+                ("bob", feb(15)),
+            ]
+        );
+        assert!(combined.metadata.is_some());
+    }
+
+    #[test]
+    fn test_to_yaml_partitioned() {
+        let schedule = Schedule {
+            people: vec![person("alice", "Alice"), person("bob", "Bob")],
+            turns: vec![turn(0, 1, 8), turn(1, 8, 15), turn(0, 15, 22)],
+            algo: Some(Algo::RoundRobin {
+                turn_length_days: 7,
+                start_person: None,
+            }),
+        };
+
+        let yaml = schedule.to_yaml_partitioned(date(10)).unwrap();
+        let value: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+        let turns = |section: &str| -> Vec<(&str, &str, &str)> {
+            value[section]
+                .as_sequence()
+                .unwrap()
+                .iter()
+                .map(|a| {
+                    let field = |name: &str| a[name].as_str().unwrap();
+                    (field("person"), field("start"), field("end"))
+                })
+                .collect()
+        };
+        assert_eq!(
+            turns("published"),
+            vec![
+                ("alice", "2025-01-01", "2025-01-08"),
+                ("bob", "2025-01-08", "2025-01-10"),
+            ]
+        );
+        assert_eq!(
+            turns("tentative"),
+            vec![
+                ("bob", "2025-01-10", "2025-01-15"),
+                ("alice", "2025-01-15", "2025-01-22"),
+            ]
+        );
+        assert!(value["metadata"]["debt"].is_mapping());
+        assert!(value.get("schedule").is_none());
+    }
+
+    #[test]
+    fn test_statistics() {
+        let schedule = Schedule {
+            people: vec![
+                person("alice", "Alice"),
+                person("bob", "Bob"),
+                person("carol", "Carol"),
+            ],
+            turns: vec![turn(0, 1, 8), turn(1, 8, 15), turn(0, 15, 22)],
+            algo: None,
+        };
+
+        let stats = schedule.statistics();
+        let alice = &stats.people[0];
+        assert_eq!((alice.days, alice.turns, alice.weekend_days), (14, 2, 4));
+        let bob = &stats.people[1];
+        assert_eq!((bob.days, bob.turns, bob.weekend_days), (7, 1, 2));
+        assert_eq!(stats.people[2].turns, 0);
+        // Loads of 14, 7 and 0 days around a mean of 7.
+        assert!((stats.load_variance - 98.0 / 3.0).abs() < 1e-9);
+        let report = stats.to_string();
+        assert!(report.starts_with("Alice: 14 days in 2 turns, 4 on weekends\n"));
+    }
+
+    #[test]
+    fn test_rotation_summary() {
+        let mut alice = person("alice", "Alice");
+        alice.preferences.insert(date(2), PreferenceType::Want);
+        alice.preferences.insert(date(9), PreferenceType::Want);
+        let schedule = Schedule {
+            people: vec![alice, person("bob", "Bob"), person("carol", "Carol")],
+            turns: vec![turn(0, 6, 13), turn(1, 13, 20), turn(2, 20, 29)],
+            algo: Some(Algo::Balanced {
+                min_turn_days: 7,
+                max_turn_days: 9,
+                preference_weight: None,
+                balance_weekends: false,
+            }),
+        };
+
+        assert_eq!(
+            schedule.rotation_summary(),
+            "3-person rotation, ~8-day turns, running 2025-01-06 to 2025-01-29, \
+             balanced algorithm, load spread 2 days, 1 of 1 preferences honored."
+        );
+        let empty = Schedule {
+            turns: vec![],
+            algo: None,
+            ..schedule
+        };
+        assert_eq!(empty.rotation_summary(), "3-person rotation without turns.");
+    }
+
+    #[test]
+    fn test_group_load() {
+        let schedule = Schedule {
+            people: vec![
+                person("alice", "Alice"),
+                person("bob", "Bob"),
+                person("carol", "Carol"),
+            ],
+            turns: vec![
+                turn(0, 1, 8),
+                turn(1, 8, 15),
+                turn(2, 15, 18),
+                turn(0, 18, 22),
+            ],
+            algo: None,
+        };
+        let groups = HashMap::from([
+            (
+                "backend".to_string(),
+                vec!["alice".to_string(), "bob".to_string()],
+            ),
+            ("frontend".to_string(), vec!["carol".to_string()]),
+            ("empty".to_string(), vec![]),
+        ]);
+
+        let load = schedule.group_load(&groups);
+        assert_eq!(load["backend"], TimeDelta::days(11 + 7));
+        assert_eq!(load["frontend"], TimeDelta::days(3));
+        assert_eq!(load["empty"], TimeDelta::zero());
+        assert_eq!(
+            schedule.group_report(&groups),
+            "backend: 18 days on call, 9.0 per member\n\
+             empty: 0 days on call, 0.0 per member\n\
+             frontend: 3 days on call, 3.0 per member\n"
+        );
+    }
+
+    #[test]
+    fn test_content_id() {
+        let schedule = |turns| Schedule {
+            people: vec![person("alice", "Alice"), person("bob", "Bob")],
+            turns,
+            algo: None,
+        };
+        let id_of = |turns| schedule(turns).content_id();
+        let id = id_of(vec![turn(0, 1, 8), turn(1, 8, 15)]);
+        assert_eq!(id.len(), 16);
+        assert_eq!(id_of(vec![turn(0, 1, 8), turn(1, 8, 15)]), id);
+        assert_eq!(id_of(vec![turn(1, 8, 15), turn(0, 1, 8)]), id);
+
+        assert_ne!(id_of(vec![turn(0, 1, 8), turn(0, 8, 15)]), id);
+        assert_ne!(id_of(vec![turn(0, 1, 9), turn(1, 9, 15)]), id);
+
+        let with_algo = Schedule {
+            algo: Some(Algo::RoundRobin {
+                turn_length_days: 7,
+                start_person: None,
+            }),
+            ..schedule(vec![turn(0, 1, 8), turn(1, 8, 15)])
+        };
+        let yaml = with_algo.to_yaml().unwrap();
+        let yaml: YamlSchedule = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(yaml.metadata.unwrap().content_id, Some(id));
+    }
+
+    #[test]
+    fn test_format_days() {
+        assert_eq!(format_days(TimeDelta::days(14), true), "2 weeks");
+        assert_eq!(format_days(TimeDelta::days(7), true), "1 week");
+        assert_eq!(format_days(TimeDelta::days(10), true), "10 days");
+        assert_eq!(format_days(TimeDelta::days(1), true), "1 day");
+        assert_eq!(format_days(TimeDelta::days(14), false), "14 days");
+
+        let schedule = Schedule {
+            people: vec![person("alice", "Alice")],
+            turns: vec![turn(0, 1, 15)],
+            algo: None,
+        };
+        assert!(format!("{:#}", schedule).contains("(2 weeks)"));
+        assert!(format!("{}", schedule).contains("(14 days)"));
+    }
+
+    #[test]
+    fn test_to_prometheus() {
+        let schedule = Schedule {
+            people: vec![person("bob", "Bob"), person("al\"ice\\", "Alice")],
+            turns: vec![turn(0, 1, 8), turn(1, 8, 11)],
+            algo: None,
+        };
+
+        let metrics = schedule.to_prometheus();
+        let samples: Vec<&str> = metrics.lines().filter(|l| !l.starts_with('#')).collect();
+        assert_eq!(
+            samples,
+            vec![
+                r#"turns_person_load_days{person="al\"ice\\"} 3"#,
+                r#"turns_person_load_days{person="bob"} 7"#,
+                "turns_load_spread_days 4",
+                "turns_preference_satisfaction_ratio 1",
+            ]
+        );
+        // Every metric has its HELP and TYPE lines before the samples.
+        for name in [
+            "turns_person_load_days",
+            "turns_load_spread_days",
+            "turns_preference_satisfaction_ratio",
+        ] {
+            let help = metrics.find(&format!("# HELP {} ", name)).unwrap();
+            let kind = metrics.find(&format!("# TYPE {} gauge\n", name)).unwrap();
+            let sample = metrics.find(&format!("\n{}", name)).unwrap();
+            assert!(help < kind && kind < sample);
+        }
+        assert!(metrics.ends_with('\n'));
+    }
+
+    #[test]
+    fn test_days() {
+        let schedule = Schedule {
+            people: vec![person("alice", "Alice"), person("bob", "Bob")],
+            turns: vec![turn(0, 1, 3), turn(1, 4, 6)],
+            algo: None,
+        };
+
+        let days: Vec<(NaiveDate, Option<&str>)> = schedule
+            .days()
+            .map(|(date, person)| (date, person.map(|p| p.id.as_str())))
+            .collect();
+        assert_eq!(
+            days,
+            vec![
+                (date(1), Some("alice")),
+                (date(2), Some("alice")),
+                (date(3), None),
+                (date(4), Some("bob")),
+                (date(5), Some("bob")),
+            ]
+        );
+
+        let empty = Schedule {
+            people: vec![],
+            turns: vec![],
+            algo: None,
+        };
+        assert_eq!(empty.days().count(), 0);
+    }
+
+    #[test]
+    fn test_status() {
+        let content = "
+schedule:
+  - person: alice
+    start: 2025-01-01
+    end: 2025-01-08
+  - person: bob
+    start: 2025-01-08
+    end: 2025-01-15
+  - person: carol
+    start: 2025-01-20
+    end: 2025-01-27
+";
+        let schedule: YamlSchedule = serde_yaml::from_str(content).unwrap();
+        let at = |day: u32| date(day).and_hms_opt(12, 0, 0).unwrap();
+
+        assert_eq!(
+            schedule.status(at(5)),
+            serde_json::json!({
+                "current": { "person": "alice", "until": "2025-01-08" },
+                "next": { "person": "bob", "from": "2025-01-08", "until": "2025-01-15" },
+            })
+        );
+        assert_eq!(
+            schedule.status(at(17)),
+            serde_json::json!({
+                "current": null,
+                "next": { "person": "carol", "from": "2025-01-20", "until": "2025-01-27" },
+            })
+        );
+        assert_eq!(
+            schedule.status(at(27)),
+            serde_json::json!({ "current": null, "next": null })
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use std::fs;
+use std::path::Path;
+
+const CONFIG: &str = r#"
+people:
+  alice:
+    name: Alice
+  bob:
+    name: Bob
+schedule:
+  from: 2025-01-01
+  to: 2025-01-29
+  algo: !RoundRobin
+    turn_length_days: 7
+"#;
+
+fn write_config(dir: &Path, content: &str) -> String {
+    let path = dir.join("turns.yaml");
+    fs::write(&path, content).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+fn stdout(output: &std::process::Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn test_generate() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = write_config(dir.path(), CONFIG);
+    let output = dir.path().join("schedule.yaml");
+
+    cargo_bin_cmd!("turns")
+        .args(["generate", "--config", &config, "--output"])
+        .arg(&output)
+        .assert()
+        .success();
+    let schedule = fs::read_to_string(&output).unwrap();
+    assert_eq!(schedule.matches("person: alice").count(), 2);
+    assert_eq!(schedule.matches("person: bob").count(), 2);
+
+    // Without a subcommand, the schedule is generated too.
+    let assert = cargo_bin_cmd!("turns")
+        .args(["--config", &config])
+        .assert()
+        .success();
+    assert!(stdout(assert.get_output()).contains("Load summary:"));
+}
+
+#[test]
+fn test_validate() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = write_config(dir.path(), CONFIG);
+
+    let assert = cargo_bin_cmd!("turns")
+        .args(["validate", "--config", &config])
+        .assert()
+        .success();
+    assert_eq!(
+        stdout(assert.get_output()),
+        "2 people, schedule from 2025-01-01 to 2025-01-29, algo round robin\n"
+    );
+
+    let invalid = write_config(dir.path(), &CONFIG.replace("2025-01-29", "2024-12-01"));
+    let assert = cargo_bin_cmd!("turns")
+        .args(["validate", "--config", &invalid])
+        .assert()
+        .failure();
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(stderr.contains("Invalid date range"), "{}", stderr);
+}
+
+#[test]
+fn test_stats() {
+    let dir = tempfile::tempdir().unwrap();
+    let schedule = dir.path().join("schedule.yaml");
+    fs::write(
+        &schedule,
+        r#"
+schedule:
+- person: alice
+  start: 2025-01-01
+  end: 2025-01-08
+- person: bob
+  start: 2025-01-08
+  end: 2025-01-11
+"#,
+    )
+    .unwrap();
+
+    let assert = cargo_bin_cmd!("turns")
+        .arg("stats")
+        .arg("--schedule")
+        .arg(&schedule)
+        .assert()
+        .success();
+    assert_eq!(
+        stdout(assert.get_output()),
+        "alice: 7 days in 1 turns, 2 on weekends\n\
+         bob: 3 days in 1 turns, 0 on weekends\n\
+         Load variance: 4.00 days²\n"
+    );
+}
+
+#[test]
+fn test_freeze_before() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = write_config(dir.path(), CONFIG);
+    let previous = dir.path().join("previous.yaml");
+    fs::write(
+        &previous,
+        r#"
+schedule:
+- person: alice
+  start: 2025-01-01
+  end: 2025-01-08
+- person: bob
+  start: 2025-01-08
+  end: 2025-01-15
+- person: bob
+  start: 2025-01-15
+  end: 2025-01-22
+"#,
+    )
+    .unwrap();
+    let output = dir.path().join("schedule.yaml");
+
+    cargo_bin_cmd!("turns")
+        .args(["--config", &config, "--freeze-before", "2025-01-12"])
+        .arg("--previous")
+        .arg(&previous)
+        .arg("--output")
+        .arg(&output)
+        .assert()
+        .success();
+    let schedule = fs::read_to_string(&output).unwrap();
+    // Alice's turn is kept and Bob's is cut at the 12th. The rotation goes on
+    // after Bob, who was on call last.
+    assert!(
+        schedule.contains(
+            "- person: alice\n  start: 2025-01-01\n  end: 2025-01-08\n\
+             - person: bob\n  start: 2025-01-08\n  end: 2025-01-12\n\
+             - person: alice\n  start: 2025-01-12\n  end: 2025-01-19\n\
+             - person: bob\n  start: 2025-01-19\n  end: 2025-01-26\n"
+        ),
+        "{}",
+        schedule
+    );
+}
+
+#[test]
+fn test_status() {
+    let dir = tempfile::tempdir().unwrap();
+    let schedule = dir.path().join("schedule.yaml");
+    fs::write(
+        &schedule,
+        r#"
+schedule:
+- person: alice
+  start: 2025-01-01
+  end: 2025-01-08
+- person: bob
+  start: 2025-01-08
+  end: 2025-01-15
+"#,
+    )
+    .unwrap();
+
+    let assert = cargo_bin_cmd!("turns")
+        .arg("status")
+        .arg(&schedule)
+        .args(["--at", "2025-01-05T09:00:00"])
+        .assert()
+        .success();
+    let status: serde_json::Value = serde_json::from_str(&stdout(assert.get_output())).unwrap();
+    assert_eq!(
+        status,
+        serde_json::json!({
+            "current": { "person": "alice", "until": "2025-01-08" },
+            "next": { "person": "bob", "from": "2025-01-08", "until": "2025-01-15" },
+        })
+    );
+}
+
+#[test]
+fn test_ledger() {
+    let dir = tempfile::tempdir().unwrap();
+    let old = dir.path().join("old.yaml");
+    let new = dir.path().join("new.yaml");
+    fs::write(
+        &old,
+        r#"
+schedule:
+- person: alice
+  start: 2025-01-01
+  end: 2025-01-08
+- person: bob
+  start: 2025-01-20
+  end: 2025-01-27
+"#,
+    )
+    .unwrap();
+    fs::write(
+        &new,
+        r#"
+schedule:
+- person: alice
+  start: 2025-01-01
+  end: 2025-01-08
+- person: alice
+  start: 2025-01-10
+  end: 2025-01-17
+- person: carol
+  start: 2025-01-20
+  end: 2025-01-27
+"#,
+    )
+    .unwrap();
+
+    let assert = cargo_bin_cmd!("turns")
+        .arg("ledger")
+        .args([&old, &new])
+        .args(["--date", "2025-01-10"])
+        .assert()
+        .success();
+    assert_eq!(
+        stdout(assert.get_output()),
+        "2025-01-10: added alice 1/10–1/17\n2025-01-10: changed 1/20–1/27 from bob to carol\n"
+    );
+
+    let assert = cargo_bin_cmd!("turns")
+        .arg("ledger")
+        .args([&old, &new])
+        .args(["--date", "2025-01-10", "--format", "json"])
+        .assert()
+        .success();
+    let ledger: serde_json::Value = serde_json::from_str(&stdout(assert.get_output())).unwrap();
+    assert_eq!(ledger[0]["change"], "added");
+    assert_eq!(ledger[1]["previous"]["person"], "bob");
+    assert_eq!(ledger[1]["current"]["person"], "carol");
+}
@@ -0,0 +1,80 @@
+use chrono::NaiveDate;
+use turns::{Config, ConfigError, ScheduleError, SchedulerError};
+
+fn config(yaml: &str) -> Config {
+    serde_yaml::from_str(yaml).unwrap()
+}
+
+#[test]
+fn test_generate() {
+    let config = config(
+        r#"
+people:
+  alice:
+    name: Alice
+  bob:
+    name: Bob
+schedule:
+  from: 2025-01-01
+  to: 2025-01-29
+  algo: !RoundRobin
+    turn_length_days: 7
+"#,
+    );
+    let schedule = turns::generate(config).unwrap();
+
+    let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+    let turns: Vec<_> = schedule
+        .turns
+        .iter()
+        .map(|t| (schedule.people[t.person].id.as_str(), t.start, t.end))
+        .collect();
+    assert_eq!(
+        turns,
+        vec![
+            ("alice", day(1), day(8)),
+            ("bob", day(8), day(15)),
+            ("alice", day(15), day(22)),
+            ("bob", day(22), day(29)),
+        ]
+    );
+}
+
+#[test]
+fn test_generate_errors() {
+    let invalid = config(
+        r#"
+people:
+  alice:
+    name: Alice
+schedule:
+  from: 2025-01-29
+  to: 2025-01-01
+  algo: !RoundRobin
+    turn_length_days: 7
+"#,
+    );
+    assert!(matches!(
+        turns::generate(invalid),
+        Err(SchedulerError::Config(ConfigError::InvalidDateRange))
+    ));
+
+    let unavailable = config(
+        r#"
+people:
+  alice:
+    name: Alice
+    ooo:
+      - !Day 2025-01-01
+schedule:
+  from: 2025-01-01
+  to: 2025-01-29
+  algo: !RoundRobin
+    turn_length_days: 7
+"#,
+    );
+    assert!(matches!(
+        turns::generate(unavailable),
+        Err(SchedulerError::Schedule(ScheduleError::NoOneAvailable(_)))
+    ));
+}